@@ -1,4 +1,6 @@
-use manganis_cli_support::{AssetManifestExt, ManganisSupportGuard};
+use manganis_cli_support::{
+    enable_manganis_support, AssetManifestExt, RemoteAssetCache, LOCKFILE_NAME,
+};
 use manganis_common::{AssetManifest, Config};
 use std::{path::PathBuf, process::Command};
 
@@ -11,14 +13,6 @@ const ASSETS_SERVE_LOCATION: &str = "./assets/";
 fn main() {
     tracing_subscriber::fmt::init();
 
-    // First set any settings you need for the build.
-    Config::default()
-        .with_assets_serve_location(ASSETS_SERVE_LOCATION)
-        .save();
-
-    // Next, tell manganis that you support assets
-    let _guard = ManganisSupportGuard::default();
-
     // Handle the commands.
     let args: Vec<String> = std::env::args().collect();
     if let Some(arg) = args.get(1) {
@@ -36,11 +30,16 @@ fn main() {
 }
 
 fn build() {
+    // Set any settings you need for the build. The config is passed to the build through an environment variable
+    let config = Config::default().with_assets_serve_location(ASSETS_SERVE_LOCATION);
+
     // Build your application
     let current_dir = std::env::current_dir().unwrap();
 
     let args = ["--release"];
-    Command::new("cargo")
+    let mut build = Command::new("cargo");
+    config
+        .apply_to_command(enable_manganis_support(&mut build))
         .current_dir(&current_dir)
         .arg("build")
         .args(args)
@@ -53,7 +52,15 @@ fn build() {
     // We will pass the current working directory as it may get lost.
     let work_dir = std::env::current_dir().unwrap();
     let link_args = vec![format!("{}", work_dir.display())];
-    manganis_cli_support::start_linker_intercept("link", args, Some(link_args)).unwrap();
+    let (mut intercept, script) =
+        manganis_cli_support::linker_intercept_command("link", args, Some(link_args)).unwrap();
+    config
+        .apply_to_command(&mut intercept)
+        .spawn()
+        .unwrap()
+        .wait()
+        .unwrap();
+    manganis_cli_support::delete_linker_script(&script).unwrap();
 }
 
 fn link() {
@@ -61,7 +68,7 @@ fn link() {
         manganis_cli_support::linker_intercept(std::env::args()).unwrap();
 
     // Extract the assets
-    let assets = AssetManifest::load_from_objects(object_files);
+    let mut assets = AssetManifest::load_from_objects(object_files);
 
     let working_dir = PathBuf::from(link_args.first().unwrap());
     let assets_dir = working_dir.join(working_dir.join(ASSETS_FILE_LOCATION));
@@ -69,8 +76,15 @@ fn link() {
     // Remove the old assets
     let _ = std::fs::remove_dir_all(&assets_dir);
 
-    // And copy the static assets to the public directory
-    assets.copy_static_assets_to(&assets_dir).unwrap();
+    // And copy the static assets to the public directory. The manifest resolves served locations with the config of the build
+    let config = Config::default().with_assets_serve_location(ASSETS_SERVE_LOCATION);
+    // The lockfile with the digests of unpinned remote assets is kept in the project, outside of the folder that is removed on every build
+    let cache = RemoteAssetCache::default()
+        .with_lockfile(working_dir.join(LOCKFILE_NAME))
+        .with_revalidate(true);
+    assets
+        .copy_static_assets_to_with_cache(&assets_dir, &config, &cache)
+        .unwrap();
 
     // Then collect the tailwind CSS
     let css = assets.collect_tailwind_css(true, &mut Vec::new());
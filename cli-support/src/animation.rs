@@ -0,0 +1,110 @@
+use anyhow::Context;
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat, RgbaImage};
+use manganis_common::{AssetSource, ImageOptions};
+
+use crate::file::{resize_image, webp_config, ProcessStage, StageError, WithStage};
+
+/// A frame of an animation with the time it is shown for
+pub(crate) struct AnimationFrame {
+    image: RgbaImage,
+    delay_ms: u32,
+}
+
+/// Returns whether the image is a gif with more than one frame
+pub(crate) fn is_animated_gif(bytes: &[u8]) -> bool {
+    if image::guess_format(bytes).ok() != Some(ImageFormat::Gif) {
+        return false;
+    }
+    // Only the first two frames need to be decoded to know if the gif is animated
+    GifDecoder::new(std::io::Cursor::new(bytes))
+        .map(|decoder| decoder.into_frames().take(2).count() > 1)
+        .unwrap_or(false)
+}
+
+/// Decodes every frame of a gif and resizes them to the size in the options
+pub(crate) fn decode_frames(
+    bytes: &[u8],
+    source: &AssetSource,
+    options: &ImageOptions,
+) -> Result<Vec<AnimationFrame>, StageError> {
+    let decode = || -> anyhow::Result<Vec<image::Frame>> {
+        let decoder = GifDecoder::new(std::io::Cursor::new(bytes))?;
+        Ok(decoder.into_frames().collect_frames()?)
+    };
+    let frames = decode()
+        .with_context(|| format!("Failed to decode the frames of {source}"))
+        .stage(ProcessStage::Decode)?;
+
+    Ok(frames
+        .into_iter()
+        .map(|frame| {
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let delay_ms = numerator / denominator.max(1);
+            // Browsers show gif frames with a delay of 10ms or less for 100ms, so the webp does the same to keep the speed
+            let delay_ms = if delay_ms <= 10 { 100 } else { delay_ms };
+            let image = resize_image(DynamicImage::ImageRgba8(frame.into_buffer()), options);
+            AnimationFrame {
+                image: image.into_rgba8(),
+                delay_ms,
+            }
+        })
+        .collect())
+}
+
+/// Encodes the frames as an animated webp that loops forever
+pub(crate) fn encode_animated_webp(
+    frames: &[AnimationFrame],
+    options: &ImageOptions,
+) -> Result<Vec<u8>, StageError> {
+    let encode = || -> anyhow::Result<Vec<u8>> {
+        let first = frames
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("The animation doesn't have any frames"))?;
+        let config = webp_config(options)?;
+        let mut encoder =
+            webp::AnimEncoder::new(first.image.width(), first.image.height(), &config);
+        encoder.set_loop_count(0);
+        // Each frame starts when the frame before it ends
+        let mut timestamp = 0;
+        for frame in frames {
+            encoder.add_frame(webp::AnimFrame::from_rgba(
+                frame.image.as_raw(),
+                frame.image.width(),
+                frame.image.height(),
+                timestamp,
+            ));
+            timestamp += frame.delay_ms as i32;
+        }
+        let encoded = encoder
+            .try_encode()
+            .map_err(|err| anyhow::anyhow!("libwebp failed to encode the animation: {:?}", err))?;
+        let mut encoded = encoded.to_vec();
+        // The webp crate doesn't pass the end of the animation to libwebp, so the last frame gets the average duration instead of its own
+        let last = frames.last().unwrap();
+        set_last_frame_duration(&mut encoded, last.delay_ms)?;
+        Ok(encoded)
+    };
+    encode().stage(ProcessStage::Encode)
+}
+
+/// Overwrites the duration of the last `ANMF` chunk in an animated webp
+fn set_last_frame_duration(webp: &mut [u8], duration_ms: u32) -> anyhow::Result<()> {
+    // The RIFF header is followed by chunks with a four character code, a little endian size and an even padded payload
+    let mut offset = 12;
+    let mut last_frame = None;
+    while offset + 8 <= webp.len() {
+        let size = u32::from_le_bytes(webp[offset + 4..offset + 8].try_into()?) as usize;
+        if &webp[offset..offset + 4] == b"ANMF" {
+            last_frame = Some(offset + 8);
+        }
+        offset += 8 + size + size % 2;
+    }
+    // The duration is stored as 24 bits after the 12 bytes of the frame position and size
+    let duration = last_frame
+        .map(|payload| payload + 12)
+        .filter(|duration| duration + 3 <= webp.len())
+        .ok_or_else(|| anyhow::anyhow!("The encoded animation doesn't have any frames"))?;
+    webp[duration..duration + 3].copy_from_slice(&duration_ms.min(0xFF_FFFF).to_le_bytes()[..3]);
+    Ok(())
+}
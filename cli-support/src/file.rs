@@ -1,59 +1,221 @@
 use anyhow::Context;
-use image::{DynamicImage, EncodableLayout};
-use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
-use manganis_common::{
-    AssetSource, CssOptions, FileAsset, FileOptions, ImageOptions, ImageType, JsOptions,
-    JsonOptions,
+use image::{DynamicImage, EncodableLayout, ImageDecoder};
+use lightningcss::{
+    dependencies::{Dependency, DependencyOptions},
+    stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet},
 };
-use std::{
-    io::{BufWriter, Write},
-    path::Path,
-    sync::Arc,
+use manganis_common::{
+    is_svg, AssetSource, CssOptions, FileAsset, FileOptions, ImageOptions, ImageOrientation,
+    ImageType, JsOptions, JsonOptions,
 };
+use std::{borrow::Cow, fmt::Display, path::Path, sync::Arc};
 use swc::{config::JsMinifyOptions, try_with_handler, BoolOrDataConfig};
 use swc_common::{sync::Lrc, FileName};
 use swc_common::{SourceMap, GLOBALS};
 
+use crate::animation::{decode_frames, encode_animated_webp, is_animated_gif};
+use crate::image_metadata::{apply_orientation, embed_icc_profile};
+use crate::remote::{read_to_bytes, read_to_string, RemoteAssetCache, BROWSER_USER_AGENT};
+use crate::svg::rasterize_svg;
+
 pub trait Process {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()>;
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError>;
+}
+
+/// The stage of processing a file asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessStage {
+    /// Reading the source of the asset. For remote assets, this includes downloading the file and verifying its integrity
+    Read,
+    /// Decoding the source of the asset. For example, decoding the pixels of an image
+    Decode,
+    /// Optimizing and encoding the asset in the output format
+    Encode,
+    /// Writing the processed asset to the output folder
+    Write,
+}
+
+impl Display for ProcessStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Decode => write!(f, "decode"),
+            Self::Encode => write!(f, "encode"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// An error while processing a file asset
+#[derive(Debug)]
+pub struct ProcessError {
+    asset: Box<FileAsset>,
+    stage: ProcessStage,
+    error: anyhow::Error,
+}
+
+impl ProcessError {
+    /// Returns the asset that failed to process
+    pub fn asset(&self) -> &FileAsset {
+        &self.asset
+    }
+
+    /// Returns the stage of processing that failed
+    pub fn stage(&self) -> ProcessStage {
+        self.stage
+    }
+}
+
+impl Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "failed to {} {}: {:#}",
+            self.stage, self.asset, self.error
+        )
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// An error in one stage of processing an asset. [`process_file`] adds the asset to turn it into a [`ProcessError`]
+#[derive(Debug)]
+pub(crate) struct StageError {
+    stage: ProcessStage,
+    error: anyhow::Error,
+}
+
+impl Display for StageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to {}: {:#}", self.stage, self.error)
+    }
 }
 
-/// Process a specific file asset
-pub fn process_file(file: &FileAsset, output_folder: &Path) -> anyhow::Result<()> {
+impl std::error::Error for StageError {}
+
+impl StageError {
+    /// Adds the asset that failed to process to the error
+    pub(crate) fn for_asset(self, file: &FileAsset) -> ProcessError {
+        ProcessError {
+            asset: Box::new(file.clone()),
+            stage: self.stage,
+            error: self.error,
+        }
+    }
+}
+
+/// Records the stage of processing an error happened in
+pub(crate) trait WithStage<T> {
+    fn stage(self, stage: ProcessStage) -> Result<T, StageError>;
+}
+
+impl<T, E: Into<anyhow::Error>> WithStage<T> for Result<T, E> {
+    fn stage(self, stage: ProcessStage) -> Result<T, StageError> {
+        self.map_err(|error| StageError {
+            stage,
+            error: error.into(),
+        })
+    }
+}
+
+/// Process a specific file asset, downloading remote files through the default [`RemoteAssetCache`]
+pub fn process_file(file: &FileAsset, output_folder: &Path) -> Result<(), ProcessError> {
+    process_file_with_cache(file, output_folder, &RemoteAssetCache::default())
+}
+
+/// Process a specific file asset, downloading remote files through the cache
+///
+/// The cache decides where downloads are stored, where the lockfile with the digests of remote assets is kept and whether remote assets can be downloaded at all
+pub fn process_file_with_cache(
+    file: &FileAsset,
+    output_folder: &Path,
+    cache: &RemoteAssetCache,
+) -> Result<(), ProcessError> {
     let location = file.location();
     let source = location.source();
     let output_path = output_folder.join(location.unique_name());
-    file.options().process(source, &output_path)
+    let cache = cache_for_file(file, cache);
+    let verified = match source {
+        // Download the file and make sure it matches the expected contents before processing it
+        AssetSource::Remote(url) => cache
+            .fetch_verified(url, file.integrity())
+            .map(|_| ())
+            .stage(ProcessStage::Read),
+        AssetSource::Local(_) => Ok(()),
+    };
+    verified
+        .and_then(|_| file.options().process(source, &output_path, &cache))
+        .map_err(|error| error.for_asset(file))
+}
+
+/// Returns the cache the source of the file is read through. Every read of a remote source is checked against the integrity of the file
+fn cache_for_file<'a>(file: &FileAsset, cache: &'a RemoteAssetCache) -> Cow<'a, RemoteAssetCache> {
+    // Google fonts only links to woff2 fonts in the stylesheet if the request comes from a browser that supports them.
+    // Everything else, including the fonts the stylesheet links to, is requested with the default user agent
+    let user_agent = self_hosts_links(file).then_some(BROWSER_USER_AGENT);
+    cache.for_asset(user_agent, file.integrity())
+}
+
+/// Writes the processed asset to the output location
+pub(crate) fn write_output(
+    output_path: &Path,
+    contents: impl AsRef<[u8]>,
+    kind: &str,
+) -> Result<(), StageError> {
+    std::fs::write(output_path, contents)
+        .with_context(|| {
+            format!(
+                "Failed to write {kind} to output location: {}",
+                output_path.display()
+            )
+        })
+        .stage(ProcessStage::Write)
 }
 
 impl Process for FileOptions {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()> {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
         if output_path.exists() {
             return Ok(());
         }
         match self {
             Self::Other { .. } => {
-                let bytes = source.read_to_bytes()?;
-                std::fs::write(output_path, bytes).with_context(|| {
-                    format!(
-                        "Failed to write file to output location: {}",
-                        output_path.display()
-                    )
-                })?;
+                let bytes = read_to_bytes(source, cache).stage(ProcessStage::Read)?;
+                write_output(output_path, bytes, "file")?;
             }
             Self::Css(options) => {
-                options.process(source, output_path)?;
+                options.process(source, output_path, cache)?;
             }
             Self::Js(options) => {
-                options.process(source, output_path)?;
+                options.process(source, output_path, cache)?;
             }
             Self::Json(options) => {
-                options.process(source, output_path)?;
+                options.process(source, output_path, cache)?;
             }
             Self::Image(options) => {
-                options.process(source, output_path)?;
+                options.process(source, output_path, cache)?;
+            }
+            Self::Video(options) => {
+                options.process(source, output_path, cache)?;
+            }
+            Self::Font(options) => {
+                options.process(source, output_path, cache)?;
+            }
+            Self::Svg(options) => {
+                options.process(source, output_path, cache)?;
+            }
+            Self::Icon(options) => {
+                options.process(source, output_path, cache)?;
             }
-            _ => todo!(),
         }
 
         Ok(())
@@ -61,60 +223,182 @@ impl Process for FileOptions {
 }
 
 impl Process for ImageOptions {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()> {
-        let mut image = image::ImageReader::new(std::io::Cursor::new(&*source.read_to_bytes()?))
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let bytes = read_to_bytes(source, cache).stage(ProcessStage::Read)?;
+        if is_animated_gif(&bytes) {
+            if *self.ty() == ImageType::Webp {
+                let frames = decode_frames(&bytes, source, self)?;
+                let encoded = encode_animated_webp(&frames, self)?;
+                return write_output(output_path, encoded, "image");
+            }
+            tracing::warn!(
+                "Only webp images can be animated. Only the first frame of {} will be kept in the {} image",
+                source,
+                self.ty()
+            );
+        }
+        let image = decode_image(&bytes, source, self)?;
+        let encoded = encode_image(image, self)?;
+        write_output(output_path, encoded, "image")
+    }
+}
+
+/// The pixels of an image along with the metadata that is kept when the image is encoded again
+#[derive(Clone)]
+pub(crate) struct DecodedImage {
+    pub(crate) image: DynamicImage,
+    /// The ICC color profile of the image if the options preserve it
+    pub(crate) icc_profile: Option<Vec<u8>>,
+}
+
+/// Decodes the image, rotates it upright and resizes it to the size in the options
+///
+/// Only the pixels of the image are kept, so the EXIF, XMP and GPS metadata of the original image never makes it into the output
+pub(crate) fn decode_image(
+    bytes: &[u8],
+    source: &AssetSource,
+    options: &ImageOptions,
+) -> Result<DecodedImage, StageError> {
+    // Svgs are drawn at the output size instead of being scaled up from their own size
+    if is_svg(bytes) {
+        let image = rasterize_svg(bytes, options)
+            .with_context(|| format!("Failed to rasterize svg {source}"))
+            .stage(ProcessStage::Decode)?;
+        return Ok(DecodedImage {
+            image: resize_image(image, options),
+            icc_profile: None,
+        });
+    }
+
+    let decode = || -> anyhow::Result<(DynamicImage, Option<Vec<u8>>)> {
+        let mut decoder = image::ImageReader::new(std::io::Cursor::new(bytes))
             .with_guessed_format()?
-            .decode()?;
+            .into_decoder()?;
+        let icc_profile = if options.preserve_icc_profile() {
+            decoder.icc_profile()?
+        } else {
+            None
+        };
+        Ok((DynamicImage::from_decoder(decoder)?, icc_profile))
+    };
+    let (image, icc_profile) = decode()
+        .with_context(|| format!("Failed to decode image {source}"))
+        .stage(ProcessStage::Decode)?;
 
-        if let Some(size) = self.size() {
-            image = image.resize_exact(size.0, size.1, image::imageops::FilterType::Lanczos3);
-        }
+    // The orientation is part of the metadata, so it must be applied to the pixels before the metadata is dropped
+    let image = apply_orientation(image, ImageOrientation::read(bytes));
 
-        match self.ty() {
-            ImageType::Png => {
-                compress_png(image, output_path);
-            }
-            ImageType::Jpg => {
-                compress_jpg(image, output_path)?;
-            }
-            ImageType::Avif => {
-                if let Err(error) = image.save(output_path) {
-                    tracing::error!("Failed to save avif image: {} with path {}. You must have the avif feature enabled to use avif assets", error, output_path.display());
-                }
-            }
-            ImageType::Webp => {
-                if let Err(err) = image.save(output_path) {
-                    tracing::error!("Failed to save webp image: {}. You must have the avif feature enabled to use webp assets", err);
-                }
-            }
+    Ok(DecodedImage {
+        image: resize_image(image, options),
+        icc_profile,
+    })
+}
+
+/// Encodes the image in the format in the options
+pub(crate) fn encode_image(
+    image: DecodedImage,
+    options: &ImageOptions,
+) -> Result<Vec<u8>, StageError> {
+    let DecodedImage { image, icc_profile } = image;
+    let encode = || -> anyhow::Result<Vec<u8>> {
+        let encoded = match options.ty() {
+            ImageType::Png => compress_png(image, options),
+            ImageType::Jpg => compress_jpg(image, options),
+            ImageType::Avif => compress_avif(image, options),
+            ImageType::Webp => compress_webp(image, options),
+        }?;
+        match icc_profile {
+            Some(profile) => embed_icc_profile(*options.ty(), encoded, &profile),
+            None => Ok(encoded),
         }
+    };
+    encode()
+        .with_context(|| format!("Failed to encode {} image", options.ty()))
+        .stage(ProcessStage::Encode)
+}
 
-        Ok(())
+/// Returns the quality of the image clamped to 1..=100. Jpg and avif images can't be lossless, so lossless options use the highest quality for them. The macro rejects those options, but hand built options can still contain them
+fn quality(options: &ImageOptions) -> Option<u8> {
+    if options.lossless() {
+        Some(100)
+    } else {
+        options.quality().map(|quality| quality.clamp(1, 100))
     }
 }
 
-fn compress_jpg(image: DynamicImage, output_location: &Path) -> anyhow::Result<()> {
-    let mut comp = mozjpeg::Compress::new(mozjpeg::ColorSpace::JCS_EXT_RGBX);
-    let width = image.width() as usize;
-    let height = image.height() as usize;
+/// Returns the effort of the image clamped to 1..=10
+fn effort(options: &ImageOptions) -> Option<u8> {
+    options.effort().map(|effort| effort.clamp(1, 10))
+}
+
+/// Resizes an image to the size in the options
+pub(crate) fn resize_image(mut image: DynamicImage, options: &ImageOptions) -> DynamicImage {
+    let target = options.resized_size((image.width(), image.height()));
 
-    comp.set_size(width, height);
-    let mut comp = comp.start_compress(Vec::new())?; // any io::Write will work
+    // Crop the image to the aspect ratio of the size before resizing it so the image is not distorted
+    if let Some((x, y, width, height)) = options.crop((image.width(), image.height())) {
+        image = image.crop_imm(x, y, width, height);
+    }
 
-    comp.write_scanlines(image.to_rgba8().as_bytes())?;
+    if (image.width(), image.height()) != target {
+        image = image.resize_exact(target.0, target.1, image::imageops::FilterType::Lanczos3);
+    }
+    image
+}
 
-    let jpeg_bytes = comp.finish()?;
+fn compress_jpg(image: DynamicImage, options: &ImageOptions) -> anyhow::Result<Vec<u8>> {
+    // Mozjpeg reports errors from libjpeg by unwinding, so we catch the panic and turn it into an error
+    std::panic::catch_unwind(|| -> std::io::Result<Vec<u8>> {
+        let mut comp = mozjpeg::Compress::new(mozjpeg::ColorSpace::JCS_EXT_RGBX);
+        let width = image.width() as usize;
+        let height = image.height() as usize;
+
+        comp.set_size(width, height);
+        // Low effort skips mozjpeg's trellis quantization and scan optimization. This resets the quality, so it must come first
+        if effort(options).is_some_and(|effort| effort <= 3) {
+            comp.set_fastest_defaults();
+        }
+        if let Some(quality) = quality(options) {
+            comp.set_quality(quality as f32);
+        }
+        let mut comp = comp.start_compress(Vec::new())?; // any io::Write will work
 
-    let file = std::fs::File::create(output_location)?;
-    let w = &mut BufWriter::new(file);
-    w.write_all(&jpeg_bytes)?;
-    Ok(())
+        comp.write_scanlines(image.to_rgba8().as_bytes())?;
+
+        comp.finish()
+    })
+    .map_err(|_| anyhow::anyhow!("mozjpeg failed to compress the image"))?
+    .map_err(Into::into)
 }
 
-fn compress_png(image: DynamicImage, output_location: &Path) {
+fn compress_png(image: DynamicImage, options: &ImageOptions) -> anyhow::Result<Vec<u8>> {
     // Image loading/saving is outside scope of this library
     let width = image.width() as usize;
     let height = image.height() as usize;
+    let compression = match effort(options) {
+        Some(1..=3) => png::Compression::Fast,
+        Some(4..=6) => png::Compression::Default,
+        _ => png::Compression::Best,
+    };
+    let mut output = Vec::new();
+
+    // Lossless images keep all of their colors instead of being quantized to a palette
+    if options.lossless() || !options.compress() {
+        let mut encoder = png::Encoder::new(&mut output, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(compression);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(image.to_rgba8().as_bytes())?;
+        writer.finish()?;
+        return Ok(output);
+    }
+
     let bitmap: Vec<_> = image
         .into_rgba8()
         .pixels()
@@ -123,24 +407,21 @@ fn compress_png(image: DynamicImage, output_location: &Path) {
 
     // Configure the library
     let mut liq = imagequant::new();
-    liq.set_speed(5).unwrap();
-    liq.set_quality(0, 99).unwrap();
+    // Imagequant's speed goes from 1 (slowest) to 10 (fastest)
+    liq.set_speed(effort(options).map_or(5, |effort| 11 - effort as i32))?;
+    liq.set_quality(0, quality(options).unwrap_or(99))?;
 
     // Describe the bitmap
-    let mut img = liq.new_image(&bitmap[..], width, height, 0.0).unwrap();
+    let mut img = liq.new_image(&bitmap[..], width, height, 0.0)?;
 
     // The magic happens in quantize()
-    let mut res = match liq.quantize(&mut img) {
-        Ok(res) => res,
-        Err(err) => panic!("Quantization failed, because: {err:?}"),
-    };
+    let mut res = liq
+        .quantize(&mut img)
+        .context("Failed to quantize the image")?;
 
-    let (palette, pixels) = res.remapped(&mut img).unwrap();
+    let (palette, pixels) = res.remapped(&mut img)?;
 
-    let file = std::fs::File::create(output_location).unwrap();
-    let w = &mut BufWriter::new(file);
-
-    let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+    let mut encoder = png::Encoder::new(&mut output, width as u32, height as u32);
     encoder.set_color(png::ColorType::Rgba);
     let mut flattened_palette = Vec::new();
     let mut alpha_palette = Vec::new();
@@ -154,44 +435,231 @@ fn compress_png(image: DynamicImage, output_location: &Path) {
     encoder.set_trns(alpha_palette);
     encoder.set_depth(png::BitDepth::Eight);
     encoder.set_color(png::ColorType::Indexed);
-    encoder.set_compression(png::Compression::Best);
-    let mut writer = encoder.write_header().unwrap();
-    writer.write_image_data(&pixels).unwrap();
-    writer.finish().unwrap();
+    encoder.set_compression(compression);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(output)
+}
+
+fn compress_avif(image: DynamicImage, options: &ImageOptions) -> anyhow::Result<Vec<u8>> {
+    let image = image.into_rgba8();
+    let pixels: Vec<_> = image
+        .pixels()
+        .map(|px| ravif::RGBA8::new(px[0], px[1], px[2], px[3]))
+        .collect();
+
+    let mut encoder = ravif::Encoder::new();
+    if let Some(quality) = quality(options) {
+        encoder = encoder
+            .with_quality(quality as f32)
+            .with_alpha_quality(quality as f32);
+    }
+    // Ravif's speed goes from 1 (slowest) to 10 (fastest)
+    if let Some(effort) = effort(options) {
+        encoder = encoder.with_speed(11 - effort);
+    }
+    let encoded = encoder.encode_rgba(ravif::Img::new(
+        &pixels[..],
+        image.width() as usize,
+        image.height() as usize,
+    ))?;
+    Ok(encoded.avif_file)
+}
+
+fn compress_webp(image: DynamicImage, options: &ImageOptions) -> anyhow::Result<Vec<u8>> {
+    let image = image.into_rgba8();
+    let config = webp_config(options)?;
+    let encoded = webp::Encoder::from_rgba(image.as_bytes(), image.width(), image.height())
+        .encode_advanced(&config)
+        .map_err(|err| anyhow::anyhow!("libwebp failed to encode the image: {:?}", err))?;
+    Ok(encoded.to_vec())
+}
+
+/// Configures libwebp with the quality, effort and lossless options
+pub(crate) fn webp_config(options: &ImageOptions) -> anyhow::Result<webp::WebPConfig> {
+    let mut config =
+        webp::WebPConfig::new().map_err(|_| anyhow::anyhow!("Failed to configure webp encoder"))?;
+    if let Some(quality) = options.quality() {
+        config.quality = quality.clamp(1, 100) as f32;
+    }
+    // Libwebp's method goes from 0 (fastest) to 6 (slowest)
+    if let Some(effort) = effort(options) {
+        config.method = ((effort as i32 - 1) * 6 + 4) / 9;
+    }
+    if options.lossless() {
+        // For lossless images, the quality controls how hard libwebp tries to make the image smaller
+        config.lossless = 1;
+        config.quality = effort(options).map_or(75., |effort| effort as f32 * 10.);
+    }
+    Ok(config)
 }
 
 impl Process for CssOptions {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()> {
-        let css = source.read_to_string()?;
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let mut css = read_to_string(source, cache).stage(ProcessStage::Read)?;
+
+        if self.self_host() {
+            let output_folder = output_path.parent().unwrap_or(Path::new("."));
+            css = self_host_urls(&css, source, output_folder, cache).stage(ProcessStage::Read)?;
+        }
 
-        let css = if self.minify() { minify_css(&css) } else { css };
+        let css = if self.minify() {
+            match minify_css(&css) {
+                Ok(minified) => minified,
+                Err(err) => {
+                    tracing::error!("Failed to minify css: {}", err);
+                    css
+                }
+            }
+        } else {
+            css
+        };
 
-        std::fs::write(output_path, css).with_context(|| {
-            format!(
-                "Failed to write css to output location: {}",
-                output_path.display()
-            )
-        })?;
+        write_output(output_path, css, "css")
+    }
+}
 
-        Ok(())
+/// Returns whether the file is a stylesheet that downloads the files it links to
+fn self_hosts_links(file: &FileAsset) -> bool {
+    matches!(file.options(), FileOptions::Css(options) if options.self_host())
+}
+
+/// Returns the files that are written while the file is processed: the remote files a self-hosted stylesheet links to or the `@font-face` stylesheet of a font. A self-hosted stylesheet must already be in the cache
+pub(crate) fn generated_files(
+    file: &FileAsset,
+    cache: &RemoteAssetCache,
+) -> anyhow::Result<Vec<FileAsset>> {
+    if let Some(stylesheet) = file.font_face_stylesheet() {
+        return Ok(vec![stylesheet]);
+    }
+    if !self_hosts_links(file) {
+        return Ok(Vec::new());
     }
+    let source = file.location().source();
+    let css = read_to_string(source, &cache_for_file(file, cache))?;
+    let (_, links) = css_links(&css, source)?;
+    Ok(links.into_iter().filter_map(|link| link.file).collect())
+}
+
+/// A `url(...)` or `@import` in a stylesheet
+struct CssLink {
+    /// The placeholder the link is replaced with in the printed stylesheet
+    placeholder: String,
+    /// The url as it was written in the stylesheet
+    url: String,
+    /// The asset the link is downloaded as if it is a remote `url(...)`
+    file: Option<FileAsset>,
+}
+
+/// Prints the stylesheet with every link replaced by a placeholder. The css is parsed, so urls in comments and strings are not links
+fn css_links(css: &str, source: &AssetSource) -> anyhow::Result<(String, Vec<CssLink>)> {
+    // Lightningcss errors borrow from the css, so they are converted to strings
+    let stylesheet = StyleSheet::parse(css, ParserOptions::default())
+        .map_err(|err| anyhow::anyhow!("Failed to parse css: {err}"))?;
+    let printed = stylesheet
+        .to_css(PrinterOptions {
+            analyze_dependencies: Some(DependencyOptions::default()),
+            ..Default::default()
+        })
+        .map_err(|err| anyhow::anyhow!("Failed to print css: {err}"))?;
+
+    let base = match source {
+        AssetSource::Remote(url) => Some(url),
+        AssetSource::Local(_) => None,
+    };
+    let mut links = Vec::new();
+    for dependency in printed.dependencies.unwrap_or_default() {
+        let link = match dependency {
+            Dependency::Url(dependency) => {
+                let url = match base {
+                    Some(base) => base.join(&dependency.url),
+                    None => url::Url::parse(&dependency.url),
+                };
+                let file =
+                    match url {
+                        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                            // Remote sources don't have an extension, so we use the extension in the url to process the file
+                            let extension = Path::new(url.path())
+                                .extension()
+                                .and_then(|extension| extension.to_str())
+                                .map(str::to_string);
+                            Some(FileAsset::new(AssetSource::Remote(url))?.with_options(
+                                FileOptions::default_for_extension(extension.as_deref()),
+                            ))
+                        }
+                        // Data urls and local files are left as-is
+                        _ => None,
+                    };
+                CssLink {
+                    placeholder: dependency.placeholder,
+                    url: dependency.url,
+                    file,
+                }
+            }
+            // Imported stylesheets are left as-is
+            Dependency::Import(dependency) => CssLink {
+                placeholder: dependency.placeholder,
+                url: dependency.url,
+                file: None,
+            },
+        };
+        links.push(link);
+    }
+
+    Ok((printed.code, links))
 }
 
-pub(crate) fn minify_css(css: &str) -> String {
-    let mut stylesheet = StyleSheet::parse(css, ParserOptions::default()).unwrap();
-    stylesheet.minify(MinifyOptions::default()).unwrap();
+/// Downloads every remote file the css links to with `url(...)` as its own asset and rewrites the css to load the
+/// files from the output folder instead. This is used to serve google fonts without loading anything from google
+fn self_host_urls(
+    css: &str,
+    source: &AssetSource,
+    output_folder: &Path,
+    cache: &RemoteAssetCache,
+) -> anyhow::Result<String> {
+    let (mut css, links) = css_links(css, source)?;
+    for link in links {
+        // The placeholders are printed as quoted strings
+        let url = match &link.file {
+            Some(file) => {
+                process_file_with_cache(file, output_folder, cache)?;
+                format!("./{}", file.location().unique_name())
+            }
+            None => link.url.replace('\\', "\\\\").replace('"', "\\\""),
+        };
+        css = css.replace(&link.placeholder, &url);
+    }
+
+    Ok(css)
+}
+
+pub(crate) fn minify_css(css: &str) -> anyhow::Result<String> {
+    // Lightningcss errors borrow from the css, so they are converted to strings
+    let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
+        .map_err(|err| anyhow::anyhow!("Failed to parse css: {err}"))?;
+    stylesheet
+        .minify(MinifyOptions::default())
+        .map_err(|err| anyhow::anyhow!("Failed to minify css: {err}"))?;
     let printer = PrinterOptions {
         minify: true,
         ..Default::default()
     };
-    let res = stylesheet.to_css(printer).unwrap();
-    res.code
+    let res = stylesheet
+        .to_css(printer)
+        .map_err(|err| anyhow::anyhow!("Failed to print css: {err}"))?;
+    Ok(res.code)
 }
 
-pub(crate) fn minify_js(source: &AssetSource) -> anyhow::Result<String> {
+pub(crate) fn minify_js(source: &AssetSource, cache: &RemoteAssetCache) -> anyhow::Result<String> {
     let cm = Arc::<SourceMap>::default();
 
-    let js = source.read_to_string()?;
+    let js = read_to_string(source, cache)?;
     let c = swc::Compiler::new(cm.clone());
     let output = GLOBALS
         .set(&Default::default(), || {
@@ -226,21 +694,21 @@ pub(crate) fn minify_js(source: &AssetSource) -> anyhow::Result<String> {
 }
 
 impl Process for JsOptions {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()> {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        // Minifying falls back to the original javascript, so only reading the source can fail
         let js = if self.minify() {
-            minify_js(source)?
+            minify_js(source, cache)
         } else {
-            source.read_to_string()?
-        };
-
-        std::fs::write(output_path, js).with_context(|| {
-            format!(
-                "Failed to write js to output location: {}",
-                output_path.display()
-            )
-        })?;
+            read_to_string(source, cache)
+        }
+        .stage(ProcessStage::Read)?;
 
-        Ok(())
+        write_output(output_path, js, "js")
     }
 }
 
@@ -253,8 +721,13 @@ pub(crate) fn minify_json(source: &str) -> anyhow::Result<String> {
 }
 
 impl Process for JsonOptions {
-    fn process(&self, source: &AssetSource, output_path: &Path) -> anyhow::Result<()> {
-        let source = source.read_to_string()?;
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let source = read_to_string(source, cache).stage(ProcessStage::Read)?;
         let json = match minify_json(&source) {
             Ok(json) => json,
             Err(err) => {
@@ -263,13 +736,6 @@ impl Process for JsonOptions {
             }
         };
 
-        std::fs::write(output_path, json).with_context(|| {
-            format!(
-                "Failed to write json to output location: {}",
-                output_path.display()
-            )
-        })?;
-
-        Ok(())
+        write_output(output_path, json, "json")
     }
 }
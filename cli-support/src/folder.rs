@@ -1,9 +1,11 @@
+use anyhow::Context;
 use std::path::Path;
 
 use manganis_common::{FileOptions, FolderAsset};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::file::Process;
+use crate::remote::RemoteAssetCache;
 
 /// Process a folder, optimizing and copying all assets into the output folder
 pub fn process_folder(folder: &FolderAsset, output_folder: &Path) -> anyhow::Result<()> {
@@ -54,6 +56,9 @@ fn process_file_minimal(input_path: &Path, output_path: &Path) -> anyhow::Result
     let options =
         FileOptions::default_for_extension(input_path.extension().and_then(|e| e.to_str()));
     let source = manganis_common::AssetSource::Local(input_path.to_path_buf());
-    options.process(&source, output_path)?;
+    // Folders only contain local files, so the download cache is never used
+    options
+        .process(&source, output_path, &RemoteAssetCache::default())
+        .with_context(|| format!("Failed to process {}", input_path.display()))?;
     Ok(())
 }
@@ -0,0 +1,106 @@
+use anyhow::{bail, Context};
+use manganis_common::{AssetSource, FontFace, FontOptions, FontType};
+use std::path::Path;
+
+use crate::{
+    file::{write_output, Process, ProcessStage, StageError, WithStage},
+    remote::{read_to_bytes, RemoteAssetCache},
+    sfnt::Sfnt,
+};
+
+impl Process for FontOptions {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let bytes = read_to_bytes(source, cache).stage(ProcessStage::Read)?;
+        let font = convert_font(bytes, self)
+            .with_context(|| format!("Failed to process font {source}"))
+            .stage(ProcessStage::Encode)?;
+
+        write_output(output_path, font, "font")?;
+
+        if let Some(font_face) = self.font_face() {
+            let file_name = output_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .context("Font output location is not valid unicode")
+                .stage(ProcessStage::Write)?;
+            let css_path = output_path.with_file_name(FontFace::css_file_name(file_name));
+            write_output(
+                &css_path,
+                font_face_css(font_face, file_name, self.ty()),
+                "font face",
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Subsets the font and converts it to the format in the options
+fn convert_font(bytes: Vec<u8>, options: &FontOptions) -> anyhow::Result<Vec<u8>> {
+    let format = detect_format(&bytes);
+    // If there is nothing to change, keep the font byte for byte
+    if options.text().is_none() && format.as_ref().is_none_or(|format| format == options.ty()) {
+        return Ok(bytes);
+    }
+
+    let mut font = match format {
+        Some(FontType::TTF) => Sfnt::parse(&bytes)?,
+        Some(FontType::WOFF) => Sfnt::parse_woff(&bytes)?,
+        Some(FontType::WOFF2) => {
+            bail!("woff2 fonts can't be subset or converted. Use the ttf, otf or woff version of the font instead")
+        }
+        None => bail!("the file is not a ttf, otf, woff or woff2 font"),
+    };
+
+    if let Some(text) = options.text() {
+        if font.has_truetype_outlines() {
+            font.subset(text)?;
+        } else {
+            tracing::warn!(
+                "Only fonts with TrueType outlines can be subset. The full font will be included"
+            );
+        }
+    }
+
+    match options.ty() {
+        FontType::TTF => Ok(font.into_sfnt()),
+        FontType::WOFF => font.into_woff(),
+        FontType::WOFF2 => font.into_woff2(),
+    }
+}
+
+/// Detects the format of a font from the first bytes of the file. Otf fonts are treated as ttf fonts
+fn detect_format(bytes: &[u8]) -> Option<FontType> {
+    match bytes.get(0..4)? {
+        [0, 1, 0, 0] | b"true" | b"OTTO" => Some(FontType::TTF),
+        b"wOFF" => Some(FontType::WOFF),
+        b"wOF2" => Some(FontType::WOFF2),
+        _ => None,
+    }
+}
+
+/// Creates a `@font-face` rule that loads the font from the file next to the css file
+fn font_face_css(font_face: &FontFace, file_name: &str, ty: &FontType) -> String {
+    let format = match ty {
+        FontType::TTF => "truetype",
+        FontType::WOFF => "woff",
+        FontType::WOFF2 => "woff2",
+    };
+    let family = font_face
+        .family()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let mut css = format!(
+        "@font-face {{\n  font-family: \"{family}\";\n  src: url(\"./{file_name}\") format(\"{format}\");\n"
+    );
+    if let Some(display) = font_face.display() {
+        css.push_str(&format!("  font-display: {display};\n"));
+    }
+    css.push_str("}\n");
+    css
+}
@@ -0,0 +1,73 @@
+use anyhow::Context;
+use image::codecs::ico::{IcoEncoder, IcoFrame};
+use image::{DynamicImage, ExtendedColorType, RgbaImage};
+use manganis_common::{AssetSource, IconOptions, IconPurpose, ImageFit, ImageOptions, ImageType};
+use std::path::Path;
+
+use crate::{
+    file::{
+        decode_image, encode_image, write_output, DecodedImage, Process, ProcessStage, StageError,
+        WithStage,
+    },
+    remote::{read_to_bytes, RemoteAssetCache},
+};
+
+impl Process for IconOptions {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let bytes = read_to_bytes(source, cache).stage(ProcessStage::Read)?;
+
+        let mut images = Vec::new();
+        for size in self.sizes() {
+            images.push((*size, square_png(&bytes, source, *size)?));
+        }
+
+        let icon = match self.purpose() {
+            IconPurpose::Favicon => encode_ico(&images)
+                .with_context(|| format!("Failed to encode the favicon for {source}"))
+                .stage(ProcessStage::Encode)?,
+            IconPurpose::AppleTouchIcon | IconPurpose::Icon => {
+                images.pop().map(|(_, png)| png).unwrap_or_default()
+            }
+        };
+
+        write_output(output_path, icon, "icon")
+    }
+}
+
+/// Resizes the image to fit in a square of the size and encodes it as a png. Images that aren't square are centered on a transparent background
+fn square_png(bytes: &[u8], source: &AssetSource, size: u32) -> Result<Vec<u8>, StageError> {
+    let mut options = ImageOptions::new(ImageType::Png, Some((size, size)));
+    options.set_fit(ImageFit::Contain);
+    let DecodedImage { image, .. } = decode_image(bytes, source, &options)?;
+
+    let mut square = RgbaImage::new(size, size);
+    let x = (size - image.width().min(size)) / 2;
+    let y = (size - image.height().min(size)) / 2;
+    image::imageops::overlay(&mut square, &image.into_rgba8(), x as i64, y as i64);
+
+    encode_image(
+        DecodedImage {
+            image: DynamicImage::ImageRgba8(square),
+            icc_profile: None,
+        },
+        &options,
+    )
+}
+
+/// Stores each png in an ico file. Every browser that supports favicons can read png images in ico files
+fn encode_ico(images: &[(u32, Vec<u8>)]) -> anyhow::Result<Vec<u8>> {
+    let frames = images
+        .iter()
+        .map(|(size, png)| {
+            IcoFrame::with_encoded(png.as_slice(), *size, *size, ExtendedColorType::Rgba8)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut ico = Vec::new();
+    IcoEncoder::new(&mut ico).encode_images(&frames)?;
+    Ok(ico)
+}
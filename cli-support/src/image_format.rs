@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use anyhow::Context;
+use manganis_common::{FileAsset, FileOptions, ImageOptions, ImageType};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use sha2::{Digest, Sha256};
+
+use crate::file::{decode_image, encode_image, ProcessError, ProcessStage, StageError, WithStage};
+use crate::image_similarity::{similarity, Planes};
+use crate::remote::{read_to_bytes, RemoteAssetCache};
+
+/// Picks the format in the candidates with the smallest output that still meets the minimum quality
+///
+/// Every candidate is encoded with the options of the image, decoded again and compared to the source with [SSIM](https://en.wikipedia.org/wiki/Structural_similarity_index_measure).
+/// Mozjpeg, libwebp and ravif each have their own quality scale, so the encoders can't be trusted to give the same visual quality for the same number.
+/// A candidate meets a minimum quality from 1 to 100 if its SSIM is at least `0.9 + min_quality / 1000`, so the default quality of 80 needs an SSIM of 0.98.
+/// If no candidate meets the quality, the one that looks the most like the source is used.
+///
+/// Jpg can't store transparency, so it is skipped for transparent images. If two formats are the same size, the one that comes first in the candidates is used. Remote images are read through the cache and checked against the integrity of the file.
+///
+/// The choice is cached in the folder by the contents of the image, the options, the candidates and the minimum quality. The macro and the CLI use the same cache, so they always agree on the format without encoding the image again.
+pub fn select_image_format(
+    file: &FileAsset,
+    candidates: &[ImageType],
+    min_quality: u8,
+    cache_folder: &Path,
+    cache: &RemoteAssetCache,
+) -> Result<ImageType, ProcessError> {
+    select_image_format_inner(file, candidates, min_quality, cache_folder, cache)
+        .map_err(|err| err.for_asset(file))
+}
+
+fn select_image_format_inner(
+    file: &FileAsset,
+    candidates: &[ImageType],
+    min_quality: u8,
+    cache_folder: &Path,
+    cache: &RemoteAssetCache,
+) -> Result<ImageType, StageError> {
+    let FileOptions::Image(options) = file.options() else {
+        return Err(anyhow::anyhow!(
+            "Only images can pick a format automatically"
+        ))
+        .stage(ProcessStage::Encode);
+    };
+    if candidates.is_empty() {
+        return Err(anyhow::anyhow!("There are no formats to pick from"))
+            .stage(ProcessStage::Encode);
+    }
+
+    let source = file.location().source();
+    let bytes = read_to_bytes(source, &cache.for_asset(None, file.integrity()))
+        .stage(ProcessStage::Read)?;
+    let cache_path = cache_folder.join(cache_key(&bytes, options, candidates, min_quality));
+    if let Some(ty) = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|ty| ty.parse::<ImageType>().ok())
+        .filter(|ty| candidates.contains(ty))
+    {
+        return Ok(ty);
+    }
+
+    let image = decode_image(&bytes, source, options)?;
+    let transparent =
+        image.image.color().has_alpha() && image.image.to_rgba8().pixels().any(|px| px[3] != 255);
+    let candidates: Vec<_> = candidates
+        .iter()
+        .filter(|ty| !(transparent && **ty == ImageType::Jpg))
+        .copied()
+        .collect();
+    if candidates.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Jpg is the only format to pick from, but the image is transparent"
+        ))
+        .stage(ProcessStage::Encode);
+    }
+
+    // Encoding is slow for some formats, so every candidate is encoded and measured at the same time
+    let source_planes = Planes::new(&image.image);
+    let measured = candidates
+        .par_iter()
+        .map(|ty| {
+            let mut options = options.clone();
+            options.set_ty(*ty);
+            let encoded = encode_image(image.clone(), &options)?;
+            let similarity = similarity(&source_planes, *ty, &encoded)
+                .with_context(|| format!("Failed to measure the quality of the {ty} image"))
+                .stage(ProcessStage::Encode)?;
+            Ok((*ty, encoded.len(), similarity))
+        })
+        .collect::<Result<Vec<_>, StageError>>()?;
+
+    let required = required_similarity(min_quality);
+    // min_by_key returns the first of equal elements, so ties go to the earlier candidate
+    let smallest = measured
+        .iter()
+        .filter(|(_, _, similarity)| *similarity >= required)
+        .min_by_key(|(_, size, _)| *size);
+    let ty = match smallest {
+        Some((ty, _, _)) => *ty,
+        None => {
+            let (ty, _, similarity) = measured
+                .iter()
+                .max_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+                .expect("there is at least one candidate");
+            tracing::warn!(
+                "None of the formats for {} meet quality {}. Using {} which looks the most like the source (SSIM {:.4}, needs {:.4})",
+                file,
+                min_quality,
+                ty,
+                similarity,
+                required
+            );
+            *ty
+        }
+    };
+
+    // The cache is only an optimization, so failing to write it is not an error
+    let cached = std::fs::create_dir_all(cache_folder)
+        .and_then(|_| std::fs::write(&cache_path, ty.extension()));
+    if let Err(err) = cached {
+        tracing::warn!(
+            "Failed to cache the format of {} in {}: {}",
+            file,
+            cache_path.display(),
+            err
+        );
+    }
+
+    Ok(ty)
+}
+
+/// The SSIM a candidate needs to meet a quality from 1 to 100
+fn required_similarity(min_quality: u8) -> f64 {
+    0.9 + min_quality.min(100) as f64 / 1000.
+}
+
+/// Hashes everything the choice of format depends on
+fn cache_key(
+    bytes: &[u8],
+    options: &ImageOptions,
+    candidates: &[ImageType],
+    min_quality: u8,
+) -> String {
+    let mut hasher = Sha256::new();
+    // Formats picked by size alone are not reused
+    hasher.update(b"ssim");
+    hasher.update([min_quality]);
+    hasher.update(bytes);
+    // The format in the options is replaced by the format that is picked, so it doesn't change the choice
+    let mut options = options.clone();
+    options.set_ty(candidates[0]);
+    hasher.update(serde_json::to_vec(&options).unwrap_or_default());
+    for candidate in candidates {
+        hasher.update(candidate.extension());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
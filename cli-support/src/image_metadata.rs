@@ -0,0 +1,177 @@
+use std::io::Write;
+
+use image::DynamicImage;
+use manganis_common::{ImageOrientation, ImageType};
+
+/// Rotates and flips the image so it is upright
+pub(crate) fn apply_orientation(
+    image: DynamicImage,
+    orientation: ImageOrientation,
+) -> DynamicImage {
+    match orientation {
+        ImageOrientation::Normal => image,
+        ImageOrientation::FlipHorizontal => image.fliph(),
+        ImageOrientation::Rotate180 => image.rotate180(),
+        ImageOrientation::FlipVertical => image.flipv(),
+        ImageOrientation::Transpose => image.rotate90().fliph(),
+        ImageOrientation::Rotate90 => image.rotate90(),
+        ImageOrientation::Transverse => image.rotate270().fliph(),
+        ImageOrientation::Rotate270 => image.rotate270(),
+    }
+}
+
+/// Adds the ICC color profile to an encoded image
+pub(crate) fn embed_icc_profile(
+    ty: ImageType,
+    encoded: Vec<u8>,
+    profile: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    match ty {
+        ImageType::Png => embed_png_icc_profile(encoded, profile),
+        ImageType::Jpg => embed_jpg_icc_profile(encoded, profile),
+        ImageType::Webp => embed_webp_icc_profile(encoded, profile),
+        ImageType::Avif => {
+            tracing::warn!("The avif encoder can't store color profiles, so the color profile of the image is removed");
+            Ok(encoded)
+        }
+    }
+}
+
+/// Inserts an iCCP chunk after the IHDR chunk. The profile must come before the palette and image data
+fn embed_png_icc_profile(png: Vec<u8>, profile: &[u8]) -> anyhow::Result<Vec<u8>> {
+    // The signature is 8 bytes and the IHDR chunk is 25 bytes
+    const IHDR_END: usize = 8 + 25;
+    anyhow::ensure!(
+        png.get(12..16) == Some(&b"IHDR"[..]),
+        "The encoded png doesn't start with a header"
+    );
+
+    // The chunk has the name of the profile, the compression method (always zlib) and the compressed profile
+    let mut data = b"ICC Profile\0\0".to_vec();
+    let mut compressed = flate2::write::ZlibEncoder::new(data, flate2::Compression::best());
+    compressed.write_all(profile)?;
+    data = compressed.finish()?;
+
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    chunk.extend((data.len() as u32).to_be_bytes());
+    chunk.extend(b"iCCP");
+    chunk.extend(&data);
+    chunk.extend(crc32fast::hash(&chunk[4..]).to_be_bytes());
+
+    let mut output = png;
+    output.splice(IHDR_END..IHDR_END, chunk);
+    Ok(output)
+}
+
+/// Inserts APP2 segments with the profile after the JFIF segment. Large profiles are split across multiple segments
+fn embed_jpg_icc_profile(jpg: Vec<u8>, profile: &[u8]) -> anyhow::Result<Vec<u8>> {
+    // Each segment has a 2 byte length, the identifier, and the index and count of the segment
+    const IDENTIFIER: &[u8] = b"ICC_PROFILE\0";
+    const MAX_CHUNK: usize = u16::MAX as usize - 2 - IDENTIFIER.len() - 2;
+    anyhow::ensure!(
+        jpg.starts_with(&[0xFF, 0xD8]),
+        "The encoded jpg doesn't start with a header"
+    );
+    let chunks: Vec<_> = profile.chunks(MAX_CHUNK).collect();
+    anyhow::ensure!(
+        chunks.len() <= u8::MAX as usize,
+        "The color profile is too large to store in a jpg"
+    );
+
+    let mut segments = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        segments.extend([0xFF, 0xE2]);
+        segments.extend(((2 + IDENTIFIER.len() + 2 + chunk.len()) as u16).to_be_bytes());
+        segments.extend(IDENTIFIER);
+        segments.extend([index as u8 + 1, chunks.len() as u8]);
+        segments.extend(*chunk);
+    }
+
+    let mut offset = 2;
+    if jpg.get(2..4) == Some(&[0xFF, 0xE0][..]) {
+        let length = jpg
+            .get(4..6)
+            .map(|length| u16::from_be_bytes([length[0], length[1]]))
+            .unwrap_or_default();
+        offset += 2 + length as usize;
+    }
+    let mut output = jpg;
+    output.splice(offset..offset, segments);
+    Ok(output)
+}
+
+/// Inserts an ICCP chunk after the VP8X chunk. Simple webp images are converted to the extended format which can store the profile
+fn embed_webp_icc_profile(webp: Vec<u8>, profile: &[u8]) -> anyhow::Result<Vec<u8>> {
+    const ICC_FLAG: u8 = 0x20;
+    const ALPHA_FLAG: u8 = 0x10;
+    anyhow::ensure!(
+        webp.starts_with(b"RIFF") && webp.get(8..12) == Some(&b"WEBP"[..]),
+        "The encoded webp doesn't start with a header"
+    );
+
+    let mut output = webp[..12].to_vec();
+    let chunks = &webp[12..];
+    if chunks.starts_with(b"VP8X") {
+        // The extended header is 8 bytes for the chunk header and 10 bytes of flags and size
+        let mut header = chunks[..18].to_vec();
+        header[8] |= ICC_FLAG;
+        output.extend(header);
+        push_webp_chunk(&mut output, b"ICCP", profile);
+        output.extend(&chunks[18..]);
+    } else {
+        let (width, height, alpha) = webp_bitstream_info(chunks)
+            .ok_or_else(|| anyhow::anyhow!("The encoded webp has an unknown bitstream"))?;
+        let mut flags = ICC_FLAG;
+        if alpha {
+            flags |= ALPHA_FLAG;
+        }
+        let mut header = vec![flags, 0, 0, 0];
+        header.extend(&(width - 1).to_le_bytes()[..3]);
+        header.extend(&(height - 1).to_le_bytes()[..3]);
+        push_webp_chunk(&mut output, b"VP8X", &header);
+        push_webp_chunk(&mut output, b"ICCP", profile);
+        output.extend(chunks);
+    }
+
+    // The size of the RIFF container doesn't include the RIFF header
+    let size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&size.to_le_bytes());
+    Ok(output)
+}
+
+/// Reads the size and whether the image has an alpha channel from the lossy (VP8) or lossless (VP8L) bitstream of a simple webp
+fn webp_bitstream_info(chunks: &[u8]) -> Option<(u32, u32, bool)> {
+    let data = chunks.get(8..)?;
+    match chunks.get(..4)? {
+        b"VP8 " => {
+            // The frame tag is followed by a start code and the 14 bit width and height. Lossy bitstreams never have alpha
+            if data.get(3..6)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            let width = u16::from_le_bytes([*data.get(6)?, *data.get(7)?]) & 0x3FFF;
+            let height = u16::from_le_bytes([*data.get(8)?, *data.get(9)?]) & 0x3FFF;
+            Some((width as u32, height as u32, false))
+        }
+        b"VP8L" => {
+            // The signature is followed by the 14 bit width and height minus one and the alpha bit
+            if *data.first()? != 0x2F {
+                return None;
+            }
+            let bits = u32::from_le_bytes(data.get(1..5)?.try_into().ok()?);
+            let width = (bits & 0x3FFF) + 1;
+            let height = ((bits >> 14) & 0x3FFF) + 1;
+            Some((width, height, bits & (1 << 28) != 0))
+        }
+        _ => None,
+    }
+}
+
+fn push_webp_chunk(output: &mut Vec<u8>, ty: &[u8; 4], data: &[u8]) {
+    output.extend(ty);
+    output.extend((data.len() as u32).to_le_bytes());
+    output.extend(data);
+    // Chunks are padded to an even length
+    if data.len() % 2 == 1 {
+        output.push(0);
+    }
+}
@@ -0,0 +1,259 @@
+use std::{mem::MaybeUninit, ptr::NonNull};
+
+use anyhow::Context;
+use image::DynamicImage;
+use manganis_common::ImageType;
+use rav1d::{
+    include::dav1d::{
+        data::Dav1dData,
+        dav1d::{Dav1dContext, Dav1dSettings},
+        picture::Dav1dPicture,
+    },
+    src::lib::{
+        dav1d_close, dav1d_data_create, dav1d_data_unref, dav1d_default_settings,
+        dav1d_get_picture, dav1d_open, dav1d_picture_unref, dav1d_send_data,
+    },
+};
+
+/// The luma and alpha of an image from 0 to 255. Similarity is measured on these planes
+pub(crate) struct Planes {
+    width: usize,
+    height: usize,
+    luma: Vec<f64>,
+    alpha: Vec<f64>,
+}
+
+impl Planes {
+    /// Reads the planes of a decoded image
+    pub(crate) fn new(image: &DynamicImage) -> Self {
+        let image = image.to_rgba8();
+        let (luma, alpha) = image
+            .pixels()
+            .map(|px| (bt601_luma(px[0], px[1], px[2]), px[3] as f64))
+            .unzip();
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            luma,
+            alpha,
+        }
+    }
+
+    /// The luma weighted by the alpha of each pixel. The color of transparent pixels is never visible and encoders are free to change it
+    fn visible_luma(&self) -> Vec<f64> {
+        self.luma
+            .iter()
+            .zip(&self.alpha)
+            .map(|(luma, alpha)| luma * alpha / 255.)
+            .collect()
+    }
+}
+
+/// The luma the avif encoder stores, so the planes of every format are compared in the same space
+fn bt601_luma(r: u8, g: u8, b: u8) -> f64 {
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+/// Decodes an image encoded as the type and returns how similar it is to the source from 0 to 1.
+///
+/// The similarity is the lower of the [SSIM](https://en.wikipedia.org/wiki/Structural_similarity_index_measure) of the visible luma and of the alpha
+pub(crate) fn similarity(source: &Planes, ty: ImageType, encoded: &[u8]) -> anyhow::Result<f64> {
+    let decoded = match ty {
+        ImageType::Avif => decode_avif(encoded)?,
+        _ => Planes::new(
+            &image::load_from_memory(encoded)
+                .with_context(|| format!("Failed to decode the encoded {ty} image"))?,
+        ),
+    };
+    if (decoded.width, decoded.height) != (source.width, source.height) {
+        anyhow::bail!(
+            "The encoded {ty} image is {}x{}, but the source is {}x{}",
+            decoded.width,
+            decoded.height,
+            source.width,
+            source.height
+        );
+    }
+
+    let luma = ssim(
+        &source.visible_luma(),
+        &decoded.visible_luma(),
+        source.width,
+        source.height,
+    );
+    let alpha = ssim(&source.alpha, &decoded.alpha, source.width, source.height);
+    Ok(luma.min(alpha))
+}
+
+/// The mean SSIM of two planes, measured in 8x8 windows that overlap by half
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize) -> f64 {
+    const C1: f64 = (0.01 * 255.) * (0.01 * 255.);
+    const C2: f64 = (0.03 * 255.) * (0.03 * 255.);
+
+    // Images smaller than a window are measured in one window
+    let window = 8.min(width).min(height);
+    if window == 0 {
+        return 1.;
+    }
+    let step = (window / 2).max(1);
+    let samples = (window * window) as f64;
+
+    let mut total = 0.;
+    let mut windows = 0;
+    for top in (0..=height - window).step_by(step) {
+        for left in (0..=width - window).step_by(step) {
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) = (0., 0., 0., 0., 0.);
+            for y in top..top + window {
+                for x in left..left + window {
+                    let (a, b) = (a[y * width + x], b[y * width + x]);
+                    sum_a += a;
+                    sum_b += b;
+                    sum_aa += a * a;
+                    sum_bb += b * b;
+                    sum_ab += a * b;
+                }
+            }
+            let (mean_a, mean_b) = (sum_a / samples, sum_b / samples);
+            let variance_a = sum_aa / samples - mean_a * mean_a;
+            let variance_b = sum_bb / samples - mean_b * mean_b;
+            let covariance = sum_ab / samples - mean_a * mean_b;
+            total += ((2. * mean_a * mean_b + C1) * (2. * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (variance_a + variance_b + C2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
+
+/// Decodes the luma and alpha of an avif image. The image crate can only decode avif with the system dav1d library, so the av1 data is decoded with rav1d.
+///
+/// Only the avif files ravif writes are read here. They always use full range samples and the luma plane is the bt601 luma of the image
+fn decode_avif(encoded: &[u8]) -> anyhow::Result<Planes> {
+    let avif = avif_parse::read_avif(&mut std::io::Cursor::new(encoded))
+        .context("Failed to parse the encoded avif image")?;
+    let color = decode_av1_luma(&avif.primary_item)?;
+    let alpha = match &avif.alpha_item {
+        Some(alpha) => decode_av1_luma(alpha)?,
+        None => Plane {
+            width: color.width,
+            height: color.height,
+            samples: vec![255.; color.samples.len()],
+        },
+    };
+    if (alpha.width, alpha.height) != (color.width, color.height) {
+        anyhow::bail!("The alpha of the encoded avif image is not the same size as the color");
+    }
+    Ok(Planes {
+        width: color.width,
+        height: color.height,
+        luma: color.samples,
+        alpha: alpha.samples,
+    })
+}
+
+/// A single plane of a decoded av1 frame from 0 to 255
+struct Plane {
+    width: usize,
+    height: usize,
+    samples: Vec<f64>,
+}
+
+/// Owns a rav1d decoder and closes it when dropped
+struct Decoder(Option<Dav1dContext>);
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        // SAFETY: The context is either `None` or was opened by `dav1d_open` and has not been closed yet
+        unsafe { dav1d_close(NonNull::new(&mut self.0)) };
+    }
+}
+
+/// Decodes the first frame of the av1 data and returns its luma plane
+fn decode_av1_luma(data: &[u8]) -> anyhow::Result<Plane> {
+    let mut settings = MaybeUninit::<Dav1dSettings>::uninit();
+    // SAFETY: `dav1d_default_settings` initializes every field of the settings
+    let mut settings = unsafe {
+        dav1d_default_settings(NonNull::new(settings.as_mut_ptr()).expect("not null"));
+        settings.assume_init()
+    };
+    // The image is a single frame, so there is nothing to decode in parallel
+    settings.n_threads = 1;
+    settings.max_frame_delay = 1;
+
+    let mut decoder = Decoder(None);
+    // SAFETY: Both pointers come from references that are valid for the duration of the call
+    let opened = unsafe { dav1d_open(NonNull::new(&mut decoder.0), NonNull::new(&mut settings)) };
+    if opened.0 != 0 {
+        anyhow::bail!("Failed to open the av1 decoder: {}", opened.0);
+    }
+    let context = decoder.0;
+
+    let mut input = Dav1dData::default();
+    // SAFETY: `input` is valid to write to. The returned buffer is `data.len()` bytes long and owned by `input`
+    unsafe {
+        let buffer = dav1d_data_create(NonNull::new(&mut input), data.len());
+        if buffer.is_null() {
+            anyhow::bail!(
+                "Failed to allocate {} bytes for the av1 decoder",
+                data.len()
+            );
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len());
+    }
+
+    let mut picture = Dav1dPicture::default();
+    let mut decoded = false;
+    // The decoder may ask for the picture to be taken out before it accepts the rest of the data
+    for _ in 0..4 {
+        if input.sz > 0 {
+            // SAFETY: The context is open and `input` was created by `dav1d_data_create`
+            unsafe { dav1d_send_data(context, NonNull::new(&mut input)) };
+        }
+        // SAFETY: The context is open and `picture` is valid to write to
+        if unsafe { dav1d_get_picture(context, NonNull::new(&mut picture)) }.0 == 0 {
+            decoded = true;
+            break;
+        }
+    }
+    // SAFETY: `input` was created by `dav1d_data_create`. Unreferencing data that was consumed does nothing
+    unsafe { dav1d_data_unref(NonNull::new(&mut input)) };
+    if !decoded {
+        anyhow::bail!("Failed to decode the av1 data");
+    }
+
+    let plane = read_luma(&picture);
+    // SAFETY: The picture was returned by `dav1d_get_picture` and is not used after this
+    unsafe { dav1d_picture_unref(NonNull::new(&mut picture)) };
+    plane
+}
+
+/// Copies the luma plane out of a decoded picture
+fn read_luma(picture: &Dav1dPicture) -> anyhow::Result<Plane> {
+    let width = usize::try_from(picture.p.w)?;
+    let height = usize::try_from(picture.p.h)?;
+    let stride = picture.stride[0];
+    let Some(data) = picture.data[0] else {
+        anyhow::bail!("The decoded av1 picture has no luma plane");
+    };
+    let max = ((1u32 << picture.p.bpc) - 1) as f64;
+
+    let mut samples = Vec::with_capacity(width * height);
+    for y in 0..height {
+        // SAFETY: Rows are `stride` bytes apart and every row of the plane holds `width` samples of the bit depth of the picture
+        unsafe {
+            let row = data.as_ptr().cast::<u8>().offset(y as isize * stride);
+            if picture.p.bpc == 8 {
+                let row = std::slice::from_raw_parts(row, width);
+                samples.extend(row.iter().map(|sample| *sample as f64));
+            } else {
+                let row = std::slice::from_raw_parts(row.cast::<u16>(), width);
+                samples.extend(row.iter().map(|sample| *sample as f64 * 255. / max));
+            }
+        }
+    }
+    Ok(Plane {
+        width,
+        height,
+        samples,
+    })
+}
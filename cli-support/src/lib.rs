@@ -1,16 +1,28 @@
 #![doc = include_str!("../README.md")]
 #![deny(missing_docs)]
 
+mod animation;
 #[allow(hidden_glob_reexports)]
 mod file;
 mod folder;
+mod font;
+mod icon;
+mod image_format;
+mod image_metadata;
+mod image_similarity;
 mod linker_intercept;
 mod manifest;
 mod marker;
+mod remote;
+mod sfnt;
+mod svg;
+mod video;
 
-pub use file::process_file;
+pub use file::{process_file, process_file_with_cache, ProcessError, ProcessStage};
 pub use folder::process_folder;
+pub use image_format::select_image_format;
 pub use linker_intercept::*;
 pub use manganis_common::*;
 pub use manifest::*;
 pub use marker::*;
+pub use remote::{set_offline, RemoteAssetCache, LOCKFILE_NAME};
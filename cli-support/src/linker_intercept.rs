@@ -1,4 +1,10 @@
-use std::{ffi::OsStr, fs, path::PathBuf, process::Stdio};
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 // The prefix to link args passed from parent process.
 const MG_ARG_NAME: &str = "mg-arg=";
@@ -103,15 +109,45 @@ where
     J: IntoIterator,
     J::Item: ToString,
 {
-    let exec_path = std::env::current_exe().unwrap();
+    let (mut cmd, script_path) = linker_intercept_command(subcommand, args, link_args)?;
+    let status = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| child.wait());
+    delete_linker_script(&script_path)?;
+    status?;
+    Ok(())
+}
 
-    let mut cmd = std::process::Command::new("cargo");
+/// Creates the cargo command that [`start_linker_intercept`] runs without starting it.
+///
+/// Manganis support is enabled for the command with [`enable_manganis_support`](crate::enable_manganis_support).
+///
+/// This lets you configure the build before it starts, for example by passing a [`manganis_common::Config`] to it with [`manganis_common::Config::apply_to_command`].
+///
+/// Returns the command and the path of the linker script it uses. Once the command finishes, delete the script with [`delete_linker_script`].
+pub fn linker_intercept_command<I, J>(
+    subcommand: &str,
+    args: I,
+    link_args: Option<J>,
+) -> Result<(Command, PathBuf), std::io::Error>
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+    J: IntoIterator,
+    J::Item: ToString,
+{
+    let exec_path = std::env::current_exe()?;
+
+    let mut cmd = Command::new("cargo");
+    crate::enable_manganis_support(&mut cmd);
     cmd.arg("rustc");
     cmd.args(args);
     cmd.arg("--");
 
     // Build a temporary redirect script.
-    let script_path = create_linker_script(exec_path, subcommand).unwrap();
+    let script_path = create_linker_script(exec_path, subcommand)?;
     let linker_arg = format!("-Clinker={}", script_path.display());
     cmd.arg(linker_arg);
 
@@ -124,15 +160,20 @@ where
         }
     }
 
-    cmd.stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?
-        .wait()?;
-    Ok(())
+    Ok((cmd, script_path))
 }
 
 const LINK_SCRIPT_NAME: &str = "mg-link";
 
+/// The number of linker scripts created by this process so far
+static LINK_SCRIPT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a new linker script name. Every call gets its own script so concurrent builds in the same or different processes don't overwrite or delete each other's script
+fn link_script_name(ext: &str) -> String {
+    let count = LINK_SCRIPT_COUNT.fetch_add(1, Ordering::Relaxed);
+    format!("{LINK_SCRIPT_NAME}-{}-{count}.{ext}", std::process::id())
+}
+
 /// Creates a temporary script that re-routes rustc linker args to a subcommand of an executable.
 fn create_linker_script(exec: PathBuf, subcommand: &str) -> Result<PathBuf, std::io::Error> {
     #[cfg(windows)]
@@ -147,8 +188,7 @@ fn create_linker_script(exec: PathBuf, subcommand: &str) -> Result<PathBuf, std:
     );
 
     let temp_path = std::env::temp_dir();
-    let out_name = format!("{LINK_SCRIPT_NAME}.{ext}");
-    let out = temp_path.join(out_name);
+    let out = temp_path.join(link_script_name(ext));
     fs::write(&out, script)?;
 
     // Set executable permissions.
@@ -172,15 +212,7 @@ fn create_linker_script(exec: PathBuf, subcommand: &str) -> Result<PathBuf, std:
     Ok(out)
 }
 
-/// Deletes the temporary script created by `create_linker_script`.
-pub fn delete_linker_script() -> Result<(), std::io::Error> {
-    #[cfg(windows)]
-    let ext = "bat";
-    #[cfg(not(windows))]
-    let ext = "sh";
-
-    let temp_path = std::env::temp_dir();
-    let file_name = format!("{LINK_SCRIPT_NAME}.{ext}");
-    let file = temp_path.join(file_name);
-    fs::remove_file(file)
+/// Deletes a linker script returned by [`linker_intercept_command`].
+pub fn delete_linker_script(path: &Path) -> Result<(), std::io::Error> {
+    fs::remove_file(path)
 }
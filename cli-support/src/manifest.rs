@@ -1,11 +1,24 @@
 pub use railwind::warning::Warning as TailwindWarning;
-use std::path::PathBuf;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
 
-use manganis_common::{linker, AssetManifest, AssetType};
+use manganis_common::{
+    linker, AssetManifest, AssetType, Config, FileAsset, Integrity, IntegrityAlgorithm,
+    MANIFEST_FILE_NAME,
+};
 
-use crate::{file::process_file, process_folder};
+use crate::{
+    file::{generated_files, process_file_with_cache},
+    process_folder,
+    remote::LOCKFILE_NAME,
+    RemoteAssetCache,
+};
 
 use object::{File, Object, ObjectSection};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rustc_hash::FxHashSet;
 use std::fs;
 
 // get the text containing all the asset descriptions
@@ -43,8 +56,34 @@ pub trait AssetManifestExt {
     ///
     /// The asset descriptions are stored inside a manifest file that is produced when the linker is intercepted.
     fn load_from_objects(object_paths: Vec<PathBuf>) -> Self;
-    /// Optimize and copy all assets in the manifest to a folder
-    fn copy_static_assets_to(&self, location: impl Into<PathBuf>) -> anyhow::Result<()>;
+    /// Optimize and copy all assets in the manifest to a folder.
+    ///
+    /// A versioned manifest file describing every collected asset is written to the folder as [`MANIFEST_FILE_NAME`]. It can be read with [`AssetManifest::read_from`].
+    /// The manifest and the manifest file include the [subresource integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity) (sha384) of every processed file. With the `html` feature, the integrity is included in the generated head.
+    /// The served locations in the manifest file are resolved with the config of the build the assets were collected from.
+    ///
+    /// Files that are written while processing other assets, like the fonts a self-hosted stylesheet links to or the `@font-face` stylesheet of a font, are added to the manifest.
+    fn copy_static_assets_to(
+        &mut self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+    ) -> anyhow::Result<()>;
+    /// Optimize and copy all assets in the manifest to a folder, downloading remote assets through the cache.
+    ///
+    /// CLIs can use this to keep the downloads and the lockfile with the digests of remote assets in the project instead of the shared cargo cache.
+    /// If the cache doesn't have a lockfile, the digests are recorded in [`LOCKFILE_NAME`] next to the manifest file.
+    fn copy_static_assets_to_with_cache(
+        &mut self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+        cache: &RemoteAssetCache,
+    ) -> anyhow::Result<()>;
+    /// Write the manifest file for the assets in a folder collected from a build with the given config
+    fn write_manifest_file(
+        &self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+    ) -> anyhow::Result<()>;
     /// Collect all tailwind classes and generate string with the output css
     fn collect_tailwind_css(
         &self,
@@ -76,8 +115,26 @@ impl AssetManifestExt for AssetManifest {
         Self::load(json)
     }
 
-    fn copy_static_assets_to(&self, location: impl Into<PathBuf>) -> anyhow::Result<()> {
+    fn copy_static_assets_to(
+        &mut self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+    ) -> anyhow::Result<()> {
+        self.copy_static_assets_to_with_cache(location, config, &RemoteAssetCache::default())
+    }
+
+    fn copy_static_assets_to_with_cache(
+        &mut self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+        cache: &RemoteAssetCache,
+    ) -> anyhow::Result<()> {
         let location = location.into();
+        let cache = match cache.lockfile() {
+            Some(_) => Cow::Borrowed(cache),
+            None => Cow::Owned(cache.clone().with_lockfile(location.join(LOCKFILE_NAME))),
+        };
+        let cache = cache.as_ref();
         match std::fs::create_dir_all(&location) {
             Ok(_) => {}
             Err(err) => {
@@ -86,32 +143,100 @@ impl AssetManifestExt for AssetManifest {
             }
         }
 
-        self.assets().iter().try_for_each(|asset| {
-            match asset {
+        // The same asset can be collected more than once. Process each unique file once so two threads never write the same output.
+        // Font stylesheets that were added to the manifest by an earlier copy are written by their font again
+        let stylesheets: Vec<_> = self
+            .assets()
+            .iter()
+            .filter_map(|asset| match asset {
+                AssetType::File(file_asset) => file_asset.font_face_stylesheet(),
+                _ => None,
+            })
+            .collect();
+        let mut unique_names: FxHashSet<_> = stylesheets
+            .iter()
+            .map(|stylesheet| stylesheet.location().unique_name())
+            .collect();
+        let assets: Vec<_> = self
+            .assets()
+            .iter()
+            .filter(|asset| match asset {
                 AssetType::File(file_asset) => {
-                    tracing::info!("Optimizing and bundling {}", file_asset);
-                    tracing::trace!("Copying asset from {:?} to {:?}", file_asset, location);
-                    match process_file(file_asset, &location) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            tracing::error!("Failed to copy static asset: {}", err);
-                            return Err(err);
+                    unique_names.insert(file_asset.location().unique_name())
+                }
+                _ => true,
+            })
+            .collect();
+
+        // Assets are independent, so they are optimized in parallel. This includes every width of a responsive image
+        let integrity = assets
+            .par_iter()
+            .map(|asset| {
+                match asset {
+                    AssetType::File(file_asset) => {
+                        tracing::info!("Optimizing and bundling {}", file_asset);
+                        tracing::trace!("Copying asset from {:?} to {:?}", file_asset, location);
+                        match process_file_with_cache(file_asset, &location, cache) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                // The error names the asset and the stage that failed
+                                tracing::error!("Failed to copy static asset: {}", err);
+                                return Err(err.into());
+                            }
                         }
+                        // Hash the file that was just written so the manifest file includes its integrity
+                        return output_integrity(file_asset, &location).map(Some);
                     }
-                }
-                AssetType::Folder(folder_asset) => {
-                    tracing::info!("Copying folder asset {}", folder_asset);
-                    match process_folder(folder_asset, &location) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            tracing::error!("Failed to copy static asset: {}", err);
-                            return Err(err);
+                    AssetType::Folder(folder_asset) => {
+                        tracing::info!("Copying folder asset {}", folder_asset);
+                        match process_folder(folder_asset, &location) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                tracing::error!("Failed to copy static asset: {}", err);
+                                return Err(err);
+                            }
                         }
                     }
+                    _ => {}
                 }
-                _ => {}
+                Ok::<_, anyhow::Error>(None)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Self-hosted stylesheets download the files they link to and fonts write their `@font-face` stylesheet while they are processed.
+        // They are added to the manifest so they are listed in the manifest file and get an integrity like every other asset
+        let mut generated = Vec::new();
+        for asset in assets {
+            if let AssetType::File(file_asset) = asset {
+                generated.extend(generated_files(file_asset, cache)?);
             }
-            Ok::<(), anyhow::Error>(())
+        }
+        for (unique_name, integrity) in integrity.into_iter().flatten() {
+            self.set_integrity(unique_name, integrity);
+        }
+        for file_asset in generated {
+            let (unique_name, integrity) = output_integrity(&file_asset, &location)?;
+            self.set_integrity(unique_name, integrity);
+            self.add_asset(AssetType::File(file_asset));
+        }
+
+        self.write_manifest_file(location, config)
+    }
+
+    fn write_manifest_file(
+        &self,
+        location: impl Into<PathBuf>,
+        config: &Config,
+    ) -> anyhow::Result<()> {
+        let location = location.into();
+        let manifest = serde_json::to_string_pretty(&self.to_manifest_file(&location, config))?;
+        let path = location.join(MANIFEST_FILE_NAME);
+        fs::write(&path, manifest).map_err(|err| {
+            anyhow::anyhow!(
+                "Failed to write asset manifest to {}: {}",
+                path.display(),
+                err
+            )
         })
     }
 
@@ -133,10 +258,37 @@ impl AssetManifestExt for AssetManifest {
 
         let css = railwind::parse_to_string(source, include_preflight, warnings);
 
-        crate::file::minify_css(&css)
+        match crate::file::minify_css(&css) {
+            Ok(minified) => minified,
+            Err(err) => {
+                tracing::error!("Failed to minify tailwind css: {}", err);
+                css
+            }
+        }
     }
 }
 
+/// Computes the subresource integrity of a file that was just written to the output folder
+fn output_integrity(
+    file_asset: &FileAsset,
+    location: &Path,
+) -> anyhow::Result<(String, Integrity)> {
+    let unique_name = file_asset.location().unique_name();
+    let output_path = location.join(unique_name);
+    let bytes = fs::read(&output_path).map_err(|err| {
+        anyhow::anyhow!(
+            "Failed to read processed asset {} from {}: {}",
+            file_asset,
+            output_path.display(),
+            err
+        )
+    })?;
+    Ok((
+        unique_name.to_string(),
+        Integrity::compute(IntegrityAlgorithm::Sha384, &bytes),
+    ))
+}
+
 fn deserialize_assets(json: &str) -> Vec<AssetType> {
     let deserializer = serde_json::Deserializer::from_str(json);
     deserializer
@@ -1,8 +1,25 @@
+use manganis_common::SUPPORT_ENV_VAR;
+use std::process::Command;
+
+/// Tells the macro that the application built by the command is being compiled with a CLI that supports assets
+///
+/// *If you do not enable support when compiling an application that uses the assets macro, the macro will display a warning about asset support*
+///
+/// Support is only passed to the command, so commands started from different threads don't affect each other. [`linker_intercept_command`](crate::linker_intercept_command) enables support automatically.
+pub fn enable_manganis_support(command: &mut Command) -> &mut Command {
+    command.env(SUPPORT_ENV_VAR, "true")
+}
+
 /// This guard tells the marco that the application is being compiled with a CLI that supports assets
 ///
 /// *If you do not hold this marker when compiling an application that uses the assets macro, the macro will display a warning about asset support*
+#[deprecated(
+    since = "0.3.0",
+    note = "The guard changes the environment of the whole process. Use `enable_manganis_support` to enable support for a single cargo command instead"
+)]
 pub struct ManganisSupportGuard(());
 
+#[allow(deprecated)]
 impl ManganisSupportGuard {
     /// Creates a new marker
     pub fn new() -> Self {
@@ -10,15 +27,17 @@ impl ManganisSupportGuard {
     }
 }
 
+#[allow(deprecated)]
 impl Default for ManganisSupportGuard {
     fn default() -> Self {
-        std::env::set_var("MANGANIS_SUPPORT", "true");
+        std::env::set_var(SUPPORT_ENV_VAR, "true");
         Self(())
     }
 }
 
+#[allow(deprecated)]
 impl Drop for ManganisSupportGuard {
     fn drop(&mut self) {
-        std::env::remove_var("MANGANIS_SUPPORT");
+        std::env::remove_var(SUPPORT_ENV_VAR);
     }
 }
@@ -0,0 +1,390 @@
+use anyhow::Context;
+use fs4::fs_std::FileExt;
+use manganis_common::{cache::remote_asset_cache_dir, AssetSource, Integrity, IntegrityAlgorithm};
+use sha2::{Digest, Sha256};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use url::Url;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Set whether remote assets should only be read from the download cache.
+///
+/// Offline mode is also enabled if the `CARGO_NET_OFFLINE` environment variable is set to `true`.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+fn offline_mode() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+        || std::env::var("CARGO_NET_OFFLINE").is_ok_and(|offline| offline == "true")
+}
+
+/// The name of the file that records the digests of remote assets that are not pinned. If the CLI doesn't set a lockfile, it is written next to the manifest file
+pub const LOCKFILE_NAME: &str = "manganis-integrity.lock";
+
+/// A persistent cache of downloaded remote assets, keyed by url
+#[derive(Debug, Clone)]
+pub struct RemoteAssetCache {
+    directory: PathBuf,
+    lockfile: Option<PathBuf>,
+    offline: bool,
+    /// Whether unpinned urls are downloaded again to check that they still match the lockfile
+    revalidate: bool,
+    /// The user agent downloads are requested with. Reqwest's default user agent is used if this is not set
+    user_agent: Option<&'static str>,
+    /// The integrity every remote read through this cache is checked against. Urls without an integrity are checked against the lockfile
+    integrity: Option<Integrity>,
+    /// The urls that were already downloaded again and checked against the lockfile with this cache
+    revalidated: Arc<Mutex<HashSet<Url>>>,
+}
+
+impl PartialEq for RemoteAssetCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.directory == other.directory
+            && self.lockfile == other.lockfile
+            && self.offline == other.offline
+            && self.revalidate == other.revalidate
+            && self.user_agent == other.user_agent
+            && self.integrity == other.integrity
+    }
+}
+
+impl Eq for RemoteAssetCache {}
+
+impl Default for RemoteAssetCache {
+    fn default() -> Self {
+        Self::new(remote_asset_cache_dir()).with_offline(offline_mode())
+    }
+}
+
+impl RemoteAssetCache {
+    /// Creates a new cache that stores downloads in the given directory
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            lockfile: None,
+            offline: false,
+            revalidate: false,
+            user_agent: None,
+            integrity: None,
+            revalidated: Default::default(),
+        }
+    }
+
+    /// Sets whether the cache should only serve assets that were already downloaded
+    pub fn with_offline(self, offline: bool) -> Self {
+        Self { offline, ..self }
+    }
+
+    /// Sets whether unpinned urls that are already cached are downloaded again the first time this cache reads them, so changes are noticed even if the
+    /// old contents are still cached. If the download fails, the cached contents are used as long as they match the lockfile.
+    ///
+    /// This is off by default so macro expansions and repeated builds don't hit the network. CLIs should enable it for the build that copies the assets
+    pub fn with_revalidate(self, revalidate: bool) -> Self {
+        Self { revalidate, ..self }
+    }
+
+    /// Returns whether unpinned urls are downloaded again to check that they still match the lockfile
+    pub fn revalidate(&self) -> bool {
+        self.revalidate
+    }
+
+    /// Returns a cache that shares the downloads of this cache, but requests them with a different user agent and checks them against the integrity of one asset
+    pub(crate) fn for_asset(
+        &self,
+        user_agent: Option<&'static str>,
+        integrity: Option<&Integrity>,
+    ) -> Cow<'_, Self> {
+        if self.user_agent == user_agent && self.integrity.as_ref() == integrity {
+            Cow::Borrowed(self)
+        } else {
+            Cow::Owned(Self {
+                user_agent,
+                integrity: integrity.cloned(),
+                ..self.clone()
+            })
+        }
+    }
+
+    /// Sets the location of the lockfile that records the digest of every remote asset that is not pinned with an integrity.
+    ///
+    /// The lockfile should live in the project and be committed with it, so changes are detected on every machine and after the download cache is cleared.
+    /// If no lockfile is set, unpinned urls are not checked, except when the assets are copied with [`crate::AssetManifestExt::copy_static_assets_to_with_cache`]
+    /// which records them in [`LOCKFILE_NAME`] next to the manifest file.
+    pub fn with_lockfile(self, lockfile: impl Into<PathBuf>) -> Self {
+        Self {
+            lockfile: Some(lockfile.into()),
+            ..self
+        }
+    }
+
+    /// Returns the location of the lockfile if one is set
+    pub fn lockfile(&self) -> Option<&Path> {
+        self.lockfile.as_deref()
+    }
+
+    /// Returns the directory the cache stores downloads in
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns whether the cache only serves assets that were already downloaded
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns the path the contents of the url are cached at
+    pub fn cache_path(&self, url: &Url) -> PathBuf {
+        let digest = Sha256::digest(url.as_str().as_bytes());
+        let name: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+        self.directory.join(name)
+    }
+
+    /// Returns the contents of the url, downloading it if it is not already cached
+    pub fn fetch(&self, url: &Url) -> anyhow::Result<Vec<u8>> {
+        let path = self.cache_path(url);
+        if path.is_file() {
+            tracing::trace!("Reading {} from the cache at {}", url, path.display());
+            return std::fs::read(&path).with_context(|| {
+                format!(
+                    "Failed to read cached asset from location: {}",
+                    path.display()
+                )
+            });
+        }
+
+        if self.offline {
+            anyhow::bail!("{url} is not in the download cache and remote assets cannot be downloaded in offline mode");
+        }
+
+        let bytes = download(url, self.user_agent)?;
+        self.store(&path, &bytes)?;
+        Ok(bytes)
+    }
+
+    /// Returns the contents of the url after checking them against the expected integrity.
+    ///
+    /// If no integrity is expected, the digest of the contents is compared to the digest recorded in the lockfile the first time the url was downloaded.
+    /// Unpinned urls can change at any time, so if [`RemoteAssetCache::with_revalidate`] is enabled they are downloaded again the first time this cache
+    /// verifies them and the cached copy is only replaced if the new contents match the lockfile.
+    pub fn fetch_verified(
+        &self,
+        url: &Url,
+        integrity: Option<&Integrity>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let Some(integrity) = integrity else {
+            return self.fetch_unpinned(url);
+        };
+
+        let cached = self.cache_path(url).is_file();
+        let mut bytes = self.fetch(url)?;
+
+        // The cached contents may be stale. Try downloading the url again before failing
+        if !integrity.matches(&bytes) && cached && !self.offline {
+            bytes = download(url, self.user_agent)?;
+            self.store(&self.cache_path(url), &bytes)?;
+        }
+
+        if !integrity.matches(&bytes) {
+            let actual = Integrity::compute(integrity.algorithm(), &bytes);
+            anyhow::bail!(
+                "Integrity check failed for {url}\nexpected: {integrity}\nactual:   {actual}\nIf the new contents are expected, update the pinned integrity to `.integrity(\"{actual}\")`"
+            );
+        }
+
+        Ok(bytes)
+    }
+
+    /// Returns the contents of an unpinned url after making sure they match the digest in the lockfile
+    fn fetch_unpinned(&self, url: &Url) -> anyhow::Result<Vec<u8>> {
+        let path = self.cache_path(url);
+        let cached = path.is_file();
+        let revalidate = self.revalidate
+            && !self.offline
+            && cached
+            && !self
+                .revalidated
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .contains(url);
+        if !revalidate {
+            let bytes = self.fetch(url)?;
+            self.check_lockfile(url, &bytes)?;
+            // If the url wasn't cached, it was just downloaded
+            if !cached {
+                self.mark_revalidated(url);
+            }
+            return Ok(bytes);
+        }
+
+        let bytes = match download(url, self.user_agent) {
+            Ok(bytes) => bytes,
+            // The network may be flaky or unavailable. The cached copy is still good if it matches the lockfile
+            Err(err) => {
+                tracing::warn!("{err:#}. Using the cached copy of {url}");
+                let bytes = self.fetch(url)?;
+                self.check_lockfile(url, &bytes)?;
+                return Ok(bytes);
+            }
+        };
+        self.check_lockfile(url, &bytes)?;
+        self.store(&path, &bytes)?;
+        self.mark_revalidated(url);
+        Ok(bytes)
+    }
+
+    fn mark_revalidated(&self, url: &Url) {
+        self.revalidated
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(url.clone());
+    }
+
+    /// Record the digest of a url the first time it is downloaded and make sure it hasn't changed since
+    fn check_lockfile(&self, url: &Url, bytes: &[u8]) -> anyhow::Result<()> {
+        let Some(lockfile) = &self.lockfile else {
+            return Ok(());
+        };
+
+        // The lockfile may be shared between threads and concurrent builds that are processing assets
+        static LOCKFILE: Mutex<()> = Mutex::new(());
+        let _guard = LOCKFILE.lock().unwrap_or_else(|err| err.into_inner());
+        let _file_guard = lock_lockfile(lockfile)?;
+
+        let mut recorded: BTreeMap<String, Integrity> = match std::fs::read(lockfile) {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .with_context(|| format!("Failed to parse lockfile at {}", lockfile.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to read lockfile at {}", lockfile.display()))
+            }
+        };
+
+        match recorded.get(url.as_str()) {
+            Some(expected) if expected.matches(bytes) => Ok(()),
+            Some(expected) => {
+                let actual = Integrity::compute(expected.algorithm(), bytes);
+                anyhow::bail!(
+                    "The contents of {url} changed since it was first downloaded\nrecorded: {expected}\nactual:   {actual}\nIf the change is expected, pin the asset with `.integrity(\"{actual}\")` or remove it from {}",
+                    lockfile.display()
+                )
+            }
+            None => {
+                let integrity = Integrity::compute(IntegrityAlgorithm::Sha256, bytes);
+                recorded.insert(url.to_string(), integrity);
+                write_atomically(
+                    lockfile,
+                    serde_json::to_string_pretty(&recorded)?.as_bytes(),
+                )
+                .with_context(|| format!("Failed to write lockfile to {}", lockfile.display()))
+            }
+        }
+    }
+
+    /// Write the downloaded bytes to the cache
+    fn store(&self, path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.directory).with_context(|| {
+            format!(
+                "Failed to create download cache at location: {}",
+                self.directory.display()
+            )
+        })?;
+        write_atomically(path, bytes).with_context(|| {
+            format!(
+                "Failed to write downloaded asset to location: {}",
+                path.display()
+            )
+        })
+    }
+}
+
+/// Takes an exclusive lock that other processes sharing the lockfile wait on while they read and update it. The lockfile itself is
+/// replaced on every write, so the lock is held on a separate file next to it. The lock is released when the returned file is dropped
+fn lock_lockfile(lockfile: &Path) -> anyhow::Result<std::fs::File> {
+    let mut path = lockfile.to_path_buf().into_os_string();
+    path.push(".guard");
+    let path = PathBuf::from(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open lockfile guard at {}", path.display()))?;
+    FileExt::lock_exclusive(&file).with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
+/// Files in the cache may be read from multiple threads and processes, so we write to a temporary file first and then move it
+/// into place. Readers either see the old or the new contents, never a partially written file
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    static TEMP_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let id = TEMP_FILE_ID.fetch_add(1, Ordering::Relaxed);
+    let mut temp_path = path.to_path_buf().into_os_string();
+    temp_path.push(format!(".{}-{id}.tmp", std::process::id()));
+    let temp_path = PathBuf::from(temp_path);
+    std::fs::write(&temp_path, bytes)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
+/// The user agent of a modern browser. Some servers change the response based on the user agent
+pub(crate) const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+fn download(url: &Url, user_agent: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    tracing::info!("Downloading {}", url);
+    let mut client = reqwest::blocking::Client::builder();
+    if let Some(user_agent) = user_agent {
+        client = client.user_agent(user_agent);
+    }
+    let response = client
+        .build()
+        .and_then(|client| client.get(url.clone()).send())
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {url}"))?;
+    let bytes = response
+        .bytes()
+        .with_context(|| format!("Failed to read the response from {url}"))?;
+    Ok(bytes.to_vec())
+}
+
+/// Reads the asset source to bytes, downloading remote assets through the cache and verifying them with [`RemoteAssetCache::fetch_verified`]
+pub(crate) fn read_to_bytes(
+    source: &AssetSource,
+    cache: &RemoteAssetCache,
+) -> anyhow::Result<Vec<u8>> {
+    match source {
+        AssetSource::Local(_) => source.read_to_bytes(),
+        AssetSource::Remote(url) => cache.fetch_verified(url, cache.integrity.as_ref()),
+    }
+}
+
+/// Reads the asset source to a string, downloading remote assets through the cache and verifying them with [`RemoteAssetCache::fetch_verified`]
+pub(crate) fn read_to_string(
+    source: &AssetSource,
+    cache: &RemoteAssetCache,
+) -> anyhow::Result<String> {
+    match source {
+        AssetSource::Local(_) => source.read_to_string(),
+        AssetSource::Remote(url) => {
+            let bytes = cache.fetch_verified(url, cache.integrity.as_ref())?;
+            String::from_utf8(bytes).with_context(|| format!("{url} is not valid utf-8"))
+        }
+    }
+}
@@ -0,0 +1,727 @@
+use anyhow::{bail, Context};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{Read, Write},
+};
+
+type Tag = [u8; 4];
+
+/// The magic number the checksums of the whole font should add up to
+const CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
+
+/// A TrueType or OpenType font split into its tables
+pub(crate) struct Sfnt {
+    /// The version of the font. This is `0x00010000` for TrueType outlines and `OTTO` for CFF outlines
+    flavor: u32,
+    tables: BTreeMap<Tag, Vec<u8>>,
+}
+
+impl Sfnt {
+    /// Read a TrueType or OpenType font
+    pub fn parse(bytes: &[u8]) -> anyhow::Result<Self> {
+        let flavor = read_u32(bytes, 0)?;
+        let num_tables = read_u16(bytes, 4)? as usize;
+        let mut tables = BTreeMap::new();
+        for index in 0..num_tables {
+            let record = 12 + index * 16;
+            let tag = read_tag(bytes, record)?;
+            let offset = read_u32(bytes, record + 8)? as usize;
+            let length = read_u32(bytes, record + 12)? as usize;
+            let table = bytes
+                .get(offset..offset + length)
+                .with_context(|| format!("table `{}` is truncated", tag_name(&tag)))?;
+            tables.insert(tag, table.to_vec());
+        }
+        Ok(Self { flavor, tables })
+    }
+
+    /// Read a WOFF font
+    pub fn parse_woff(bytes: &[u8]) -> anyhow::Result<Self> {
+        let flavor = read_u32(bytes, 4)?;
+        let num_tables = read_u16(bytes, 12)? as usize;
+        let mut tables = BTreeMap::new();
+        for index in 0..num_tables {
+            let entry = 44 + index * 20;
+            let tag = read_tag(bytes, entry)?;
+            let offset = read_u32(bytes, entry + 4)? as usize;
+            let compressed_length = read_u32(bytes, entry + 8)? as usize;
+            let length = read_u32(bytes, entry + 12)? as usize;
+            let stored = bytes
+                .get(offset..offset + compressed_length)
+                .with_context(|| format!("table `{}` is truncated", tag_name(&tag)))?;
+            // Tables are only compressed if that makes them smaller
+            let table = if compressed_length < length {
+                let mut table = Vec::with_capacity(length);
+                ZlibDecoder::new(stored)
+                    .read_to_end(&mut table)
+                    .with_context(|| format!("table `{}` is corrupt", tag_name(&tag)))?;
+                if table.len() != length {
+                    bail!("table `{}` has the wrong length", tag_name(&tag));
+                }
+                table
+            } else {
+                stored.to_vec()
+            };
+            tables.insert(tag, table);
+        }
+        Ok(Self { flavor, tables })
+    }
+
+    /// Returns true if the glyphs in the font are stored as TrueType outlines
+    pub fn has_truetype_outlines(&self) -> bool {
+        self.tables.contains_key(b"glyf") && self.tables.contains_key(b"loca")
+    }
+
+    fn table(&self, tag: &Tag) -> anyhow::Result<&[u8]> {
+        self.tables
+            .get(tag)
+            .map(Vec::as_slice)
+            .with_context(|| format!("font is missing the `{}` table", tag_name(tag)))
+    }
+
+    /// Removes the outlines of every glyph that is not needed to render `text`
+    ///
+    /// Glyphs keep their ids so tables that refer to glyphs (hmtx, GPOS, kern, ...) stay valid. The outlines of unused
+    /// glyphs are removed and the character map only includes the characters in the text which is where almost all of the
+    /// size of a font is.
+    pub fn subset(&mut self, text: &str) -> anyhow::Result<()> {
+        let long_loca = read_u16(self.table(b"head")?, 50)? != 0;
+        let num_glyphs = read_u16(self.table(b"maxp")?, 4)? as usize;
+        let glyf = self.table(b"glyf")?;
+        let loca = parse_loca(self.table(b"loca")?, num_glyphs, long_loca, glyf.len())?;
+        let glyph = |glyph: u16| &glyf[loca[glyph as usize]..loca[glyph as usize + 1]];
+
+        let cmap = CharacterMap::parse(self.table(b"cmap")?)?;
+        let mut characters = BTreeMap::new();
+        for character in text.chars() {
+            if let Some(glyph) = cmap.lookup(character as u32)? {
+                if glyph != 0 && (glyph as usize) < num_glyphs {
+                    characters.insert(character as u32, glyph);
+                }
+            }
+        }
+
+        // The .notdef glyph is always kept because it is drawn for missing characters
+        let mut glyphs: BTreeSet<u16> = [0]
+            .into_iter()
+            .chain(characters.values().copied())
+            .collect();
+        // Ligatures and alternates of the characters may be drawn instead of the characters themselves
+        if let Some(gsub) = self.tables.get(b"GSUB") {
+            substitution_closure(gsub, &mut glyphs)?;
+            glyphs.retain(|glyph| (*glyph as usize) < num_glyphs);
+        }
+        // Composite glyphs are drawn from other glyphs
+        let mut pending: Vec<u16> = glyphs.iter().copied().collect();
+        while let Some(composite) = pending.pop() {
+            for component in composite_components(glyph(composite))? {
+                if (component as usize) < num_glyphs && glyphs.insert(component) {
+                    pending.push(component);
+                }
+            }
+        }
+
+        let mut new_glyf = Vec::new();
+        let mut new_loca = Vec::with_capacity(num_glyphs + 1);
+        for id in 0..num_glyphs as u16 {
+            new_loca.push(new_glyf.len());
+            if glyphs.contains(&id) {
+                new_glyf.extend_from_slice(glyph(id));
+                pad_to_four(&mut new_glyf);
+            }
+        }
+        new_loca.push(new_glyf.len());
+
+        // Short offsets store half of the offset in 16 bits
+        let long_loca = new_glyf.len() > u16::MAX as usize * 2;
+        let mut loca = Vec::new();
+        for offset in new_loca {
+            if long_loca {
+                loca.extend_from_slice(&(offset as u32).to_be_bytes());
+            } else {
+                loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+            }
+        }
+        let cmap = write_cmap(&characters)?;
+
+        let head = self.tables.get_mut(b"head").unwrap();
+        head[50..52].copy_from_slice(&(long_loca as u16).to_be_bytes());
+        self.tables.insert(*b"glyf", new_glyf);
+        self.tables.insert(*b"loca", loca);
+        self.tables.insert(*b"cmap", cmap);
+        // The signature no longer matches the font
+        self.tables.remove(b"DSIG");
+
+        Ok(())
+    }
+
+    /// Write the font as a TrueType or OpenType font
+    pub fn into_sfnt(mut self) -> Vec<u8> {
+        self.update_checksum_adjustment();
+        self.write_sfnt()
+    }
+
+    /// Write the font as a [WOFF](https://www.w3.org/TR/WOFF/) font. Each table is compressed with zlib
+    pub fn into_woff(mut self) -> anyhow::Result<Vec<u8>> {
+        self.update_checksum_adjustment();
+
+        let num_tables = self.tables.len();
+        let header_len = 44 + 20 * num_tables;
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        let mut sfnt_size = 12 + 16 * num_tables;
+        for (tag, table) in &self.tables {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(table)?;
+            let compressed = encoder.finish()?;
+            let stored = if compressed.len() < table.len() {
+                &compressed
+            } else {
+                table
+            };
+
+            directory.extend_from_slice(tag);
+            directory.extend_from_slice(&((header_len + data.len()) as u32).to_be_bytes());
+            directory.extend_from_slice(&(stored.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&table_checksum(tag, table).to_be_bytes());
+            data.extend_from_slice(stored);
+            pad_to_four(&mut data);
+            sfnt_size += table.len().next_multiple_of(4);
+        }
+
+        let mut woff = Vec::with_capacity(header_len + data.len());
+        woff.extend_from_slice(b"wOFF");
+        woff.extend_from_slice(&self.flavor.to_be_bytes());
+        woff.extend_from_slice(&((header_len + data.len()) as u32).to_be_bytes());
+        woff.extend_from_slice(&(num_tables as u16).to_be_bytes());
+        woff.extend_from_slice(&[0; 2]);
+        woff.extend_from_slice(&(sfnt_size as u32).to_be_bytes());
+        woff.extend_from_slice(&self.version());
+        // There is no metadata or private data block
+        woff.extend_from_slice(&[0; 20]);
+        woff.extend_from_slice(&directory);
+        woff.extend_from_slice(&data);
+        Ok(woff)
+    }
+
+    /// Write the font as a [WOFF2](https://www.w3.org/TR/WOFF2/) font. All tables are compressed together with brotli
+    pub fn into_woff2(mut self) -> anyhow::Result<Vec<u8>> {
+        self.update_checksum_adjustment();
+
+        // The loca table must directly follow the glyf table
+        let mut order: Vec<&Tag> = self.tables.keys().filter(|tag| *tag != b"loca").collect();
+        if let Some(glyf) = order.iter().position(|tag| *tag == b"glyf") {
+            order.insert(glyf + 1, b"loca");
+        }
+
+        let num_tables = self.tables.len();
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        let mut sfnt_size = 12 + 16 * num_tables;
+        for tag in order {
+            let table = &self.tables[tag];
+            // Every table is stored with the null transform. The tag is always written out instead of using the index
+            // of the well known tags. For glyf and loca, transform version 3 is the null transform
+            let flags = match tag {
+                b"glyf" | b"loca" => 0xFF,
+                _ => 0x3F,
+            };
+            directory.push(flags);
+            directory.extend_from_slice(tag);
+            write_base128(&mut directory, table.len() as u32);
+            data.extend_from_slice(table);
+            sfnt_size += table.len().next_multiple_of(4);
+        }
+
+        let params = brotli::enc::BrotliEncoderParams {
+            mode: brotli::enc::backward_references::BrotliEncoderMode::BROTLI_MODE_FONT,
+            quality: 11,
+            size_hint: data.len(),
+            ..Default::default()
+        };
+        let mut compressed = Vec::new();
+        brotli::BrotliCompress(&mut data.as_slice(), &mut compressed, &params)?;
+
+        let header_len = 48 + directory.len();
+        let length = (header_len + compressed.len()).next_multiple_of(4);
+        let mut woff2 = Vec::with_capacity(length);
+        woff2.extend_from_slice(b"wOF2");
+        woff2.extend_from_slice(&self.flavor.to_be_bytes());
+        woff2.extend_from_slice(&(length as u32).to_be_bytes());
+        woff2.extend_from_slice(&(num_tables as u16).to_be_bytes());
+        woff2.extend_from_slice(&[0; 2]);
+        woff2.extend_from_slice(&(sfnt_size as u32).to_be_bytes());
+        woff2.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        woff2.extend_from_slice(&self.version());
+        // There is no metadata or private data block
+        woff2.extend_from_slice(&[0; 20]);
+        woff2.extend_from_slice(&directory);
+        woff2.extend_from_slice(&compressed);
+        woff2.resize(length, 0);
+        Ok(woff2)
+    }
+
+    /// The major and minor version of the font from the `head` table
+    fn version(&self) -> [u8; 4] {
+        self.tables
+            .get(b"head")
+            .and_then(|head| head.get(4..8))
+            .map(|version| version.try_into().unwrap())
+            .unwrap_or_default()
+    }
+
+    fn write_sfnt(&self) -> Vec<u8> {
+        let num_tables = self.tables.len() as u16;
+        let entry_selector = num_tables.max(1).ilog2() as u16;
+        let search_range: u16 = (1 << entry_selector) * 16;
+
+        let mut sfnt = Vec::new();
+        sfnt.extend_from_slice(&self.flavor.to_be_bytes());
+        sfnt.extend_from_slice(&num_tables.to_be_bytes());
+        sfnt.extend_from_slice(&search_range.to_be_bytes());
+        sfnt.extend_from_slice(&entry_selector.to_be_bytes());
+        sfnt.extend_from_slice(&(num_tables * 16).saturating_sub(search_range).to_be_bytes());
+
+        let mut offset = 12 + 16 * self.tables.len();
+        for (tag, table) in &self.tables {
+            sfnt.extend_from_slice(tag);
+            sfnt.extend_from_slice(&table_checksum(tag, table).to_be_bytes());
+            sfnt.extend_from_slice(&(offset as u32).to_be_bytes());
+            sfnt.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += table.len().next_multiple_of(4);
+        }
+        for table in self.tables.values() {
+            sfnt.extend_from_slice(table);
+            pad_to_four(&mut sfnt);
+        }
+        sfnt
+    }
+
+    /// Updates the checksum adjustment in the `head` table to match the rest of the font
+    fn update_checksum_adjustment(&mut self) {
+        match self.tables.get_mut(b"head") {
+            Some(head) if head.len() >= 12 => head[8..12].fill(0),
+            _ => return,
+        }
+        let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&self.write_sfnt()));
+        self.tables.get_mut(b"head").unwrap()[8..12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+}
+
+/// The checksum of a table. The checksum of the `head` table is calculated as if the checksum adjustment was zero
+fn table_checksum(tag: &Tag, table: &[u8]) -> u32 {
+    let checksum = checksum(table);
+    match (tag, table.get(8..12)) {
+        (b"head", Some(adjustment)) => {
+            checksum.wrapping_sub(u32::from_be_bytes(adjustment.try_into().unwrap()))
+        }
+        _ => checksum,
+    }
+}
+
+/// The sum of the data as big endian 32 bit integers
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad_to_four(data: &mut Vec<u8>) {
+    data.resize(data.len().next_multiple_of(4), 0);
+}
+
+/// Writes a variable length integer in the format used by WOFF2 to store table lengths
+fn write_base128(output: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    value >>= 7;
+    while value > 0 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    output.extend(bytes.iter().rev());
+}
+
+fn tag_name(tag: &Tag) -> String {
+    String::from_utf8_lossy(tag).into_owned()
+}
+
+fn read_tag(data: &[u8], offset: usize) -> anyhow::Result<Tag> {
+    data.get(offset..offset + 4)
+        .map(|tag| tag.try_into().unwrap())
+        .context("font is truncated")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> anyhow::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+        .context("font is truncated")
+}
+
+fn read_u32(data: &[u8], offset: usize) -> anyhow::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .context("font is truncated")
+}
+
+/// Reads the offset of each glyph in the glyf table. The last offset is the end of the last glyph
+fn parse_loca(
+    loca: &[u8],
+    num_glyphs: usize,
+    long: bool,
+    glyf_len: usize,
+) -> anyhow::Result<Vec<usize>> {
+    let offsets = (0..=num_glyphs)
+        .map(|glyph| match long {
+            true => read_u32(loca, glyph * 4).map(|offset| offset as usize),
+            false => read_u16(loca, glyph * 2).map(|offset| offset as usize * 2),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if offsets.windows(2).any(|range| range[0] > range[1]) || offsets[num_glyphs] > glyf_len {
+        bail!("glyph locations are out of order");
+    }
+    Ok(offsets)
+}
+
+/// Returns the glyphs a composite glyph is built from
+fn composite_components(glyph: &[u8]) -> anyhow::Result<Vec<u16>> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+    let mut components = Vec::new();
+    // Simple glyphs have a positive number of contours
+    if glyph.len() < 10 || (read_u16(glyph, 0)? as i16) >= 0 {
+        return Ok(components);
+    }
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Ok(components);
+        }
+    }
+}
+
+/// The subtable of the `cmap` table used to map characters to glyphs
+struct CharacterMap<'a> {
+    format: u16,
+    subtable: &'a [u8],
+}
+
+impl<'a> CharacterMap<'a> {
+    fn parse(cmap: &'a [u8]) -> anyhow::Result<Self> {
+        let num_tables = read_u16(cmap, 2)? as usize;
+        let mut best: Option<(u8, Self)> = None;
+        for index in 0..num_tables {
+            let record = 4 + index * 8;
+            let platform = read_u16(cmap, record)?;
+            let encoding = read_u16(cmap, record + 2)?;
+            let subtable = cmap
+                .get(read_u32(cmap, record + 4)? as usize..)
+                .context("character map is truncated")?;
+            let format = read_u16(subtable, 0)?;
+            // Prefer full unicode tables over tables that only cover the basic multilingual plane
+            let priority = match (platform, encoding, format) {
+                (3, 10, 12) | (0, 4 | 6, 12) => 2,
+                (3, 1, 4) | (0, _, 4) => 1,
+                _ => continue,
+            };
+            if best.as_ref().is_none_or(|(best, _)| priority > *best) {
+                best = Some((priority, Self { format, subtable }));
+            }
+        }
+        best.map(|(_, cmap)| cmap)
+            .context("font does not have a unicode character map")
+    }
+
+    fn lookup(&self, character: u32) -> anyhow::Result<Option<u16>> {
+        let subtable = self.subtable;
+        if self.format == 12 {
+            let groups = read_u32(subtable, 12)? as usize;
+            for group in 0..groups {
+                let group = 16 + group * 12;
+                let start = read_u32(subtable, group)?;
+                let end = read_u32(subtable, group + 4)?;
+                if (start..=end).contains(&character) {
+                    let glyph = read_u32(subtable, group + 8)? + (character - start);
+                    return Ok(u16::try_from(glyph).ok());
+                }
+            }
+            return Ok(None);
+        }
+
+        let Ok(character) = u16::try_from(character) else {
+            return Ok(None);
+        };
+        let segments = read_u16(subtable, 6)? as usize / 2;
+        let end_codes = 14;
+        let start_codes = end_codes + segments * 2 + 2;
+        let id_deltas = start_codes + segments * 2;
+        let id_range_offsets = id_deltas + segments * 2;
+        for segment in 0..segments {
+            if character > read_u16(subtable, end_codes + segment * 2)? {
+                continue;
+            }
+            let start = read_u16(subtable, start_codes + segment * 2)?;
+            if character < start {
+                return Ok(None);
+            }
+            let delta = read_u16(subtable, id_deltas + segment * 2)?;
+            let range_offset = read_u16(subtable, id_range_offsets + segment * 2)? as usize;
+            if range_offset == 0 {
+                return Ok(Some(character.wrapping_add(delta)));
+            }
+            // The range offset is relative to its own location in the table
+            let glyph = read_u16(
+                subtable,
+                id_range_offsets + segment * 2 + range_offset + (character - start) as usize * 2,
+            )?;
+            return Ok((glyph != 0).then(|| glyph.wrapping_add(delta)));
+        }
+        Ok(None)
+    }
+}
+
+/// Writes a `cmap` table that maps the characters to their glyphs
+fn write_cmap(characters: &BTreeMap<u32, u16>) -> anyhow::Result<Vec<u8>> {
+    // Group characters that map to consecutive glyphs
+    let mut runs: Vec<(u32, u32, u16)> = Vec::new();
+    for (&character, &glyph) in characters {
+        match runs.last_mut() {
+            Some((start, end, start_glyph))
+                if *end + 1 == character
+                    && (*start_glyph as u32 + character - *start) == glyph as u32 =>
+            {
+                *end = character
+            }
+            _ => runs.push((character, character, glyph)),
+        }
+    }
+
+    let mut subtables: Vec<(u16, Vec<u8>)> = Vec::new();
+    let bmp_runs: Vec<_> = runs
+        .iter()
+        .filter(|(_, end, _)| *end <= 0xFFFF)
+        .copied()
+        .collect();
+    let format_4_len = 16 + 8 * (bmp_runs.len() + 1);
+    let has_format_4 = format_4_len <= u16::MAX as usize;
+    if has_format_4 {
+        // The last segment must map 0xFFFF to the missing glyph
+        let segments: Vec<(u16, u16, u16)> = bmp_runs
+            .iter()
+            .map(|&(start, end, glyph)| {
+                (start as u16, end as u16, glyph.wrapping_sub(start as u16))
+            })
+            .chain([(0xFFFF, 0xFFFF, 1)])
+            .collect();
+        let segment_count = segments.len() as u16;
+        let entry_selector = segment_count.ilog2() as u16;
+        let search_range: u16 = (1 << entry_selector) * 2;
+
+        let mut subtable = Vec::with_capacity(format_4_len);
+        for value in [
+            4,
+            format_4_len as u16,
+            0,
+            segment_count * 2,
+            search_range,
+            entry_selector,
+            segment_count * 2 - search_range,
+        ] {
+            subtable.extend_from_slice(&value.to_be_bytes());
+        }
+        for (_, end, _) in &segments {
+            subtable.extend_from_slice(&end.to_be_bytes());
+        }
+        subtable.extend_from_slice(&[0; 2]);
+        for (start, _, _) in &segments {
+            subtable.extend_from_slice(&start.to_be_bytes());
+        }
+        for (_, _, delta) in &segments {
+            subtable.extend_from_slice(&delta.to_be_bytes());
+        }
+        subtable.resize(format_4_len, 0);
+        subtables.push((1, subtable));
+    }
+
+    // Characters outside of the basic multilingual plane need a format 12 table
+    if !has_format_4 || bmp_runs.len() != runs.len() {
+        let mut subtable = Vec::new();
+        subtable.extend_from_slice(&12u16.to_be_bytes());
+        subtable.extend_from_slice(&[0; 2]);
+        subtable.extend_from_slice(&((16 + 12 * runs.len()) as u32).to_be_bytes());
+        subtable.extend_from_slice(&[0; 4]);
+        subtable.extend_from_slice(&(runs.len() as u32).to_be_bytes());
+        for (start, end, glyph) in &runs {
+            subtable.extend_from_slice(&start.to_be_bytes());
+            subtable.extend_from_slice(&end.to_be_bytes());
+            subtable.extend_from_slice(&(*glyph as u32).to_be_bytes());
+        }
+        subtables.push((10, subtable));
+    }
+
+    let mut cmap = Vec::new();
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    cmap.extend_from_slice(&(subtables.len() as u16).to_be_bytes());
+    let mut offset = 4 + 8 * subtables.len();
+    for (encoding, subtable) in &subtables {
+        cmap.extend_from_slice(&3u16.to_be_bytes());
+        cmap.extend_from_slice(&encoding.to_be_bytes());
+        cmap.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += subtable.len();
+    }
+    for (_, subtable) in subtables {
+        cmap.extend_from_slice(&subtable);
+    }
+    Ok(cmap)
+}
+
+/// Adds every glyph the `GSUB` table can substitute for the glyphs in the set until no new glyphs are found
+fn substitution_closure(gsub: &[u8], glyphs: &mut BTreeSet<u16>) -> anyhow::Result<()> {
+    let lookup_list = read_u16(gsub, 8)? as usize;
+    let mut subtables = Vec::new();
+    for lookup in 0..read_u16(gsub, lookup_list)? as usize {
+        let lookup = lookup_list + read_u16(gsub, lookup_list + 2 + lookup * 2)? as usize;
+        let lookup_type = read_u16(gsub, lookup)?;
+        for subtable in 0..read_u16(gsub, lookup + 4)? as usize {
+            let subtable = lookup + read_u16(gsub, lookup + 6 + subtable * 2)? as usize;
+            // Extension subtables point to a subtable of another type with a 32 bit offset
+            if lookup_type == 7 {
+                subtables.push((
+                    read_u16(gsub, subtable + 2)?,
+                    subtable + read_u32(gsub, subtable + 4)? as usize,
+                ));
+            } else {
+                subtables.push((lookup_type, subtable));
+            }
+        }
+    }
+
+    loop {
+        let mut added = Vec::new();
+        for &(lookup_type, subtable) in &subtables {
+            added.extend(substitutions(gsub, lookup_type, subtable, glyphs)?);
+        }
+        let before = glyphs.len();
+        glyphs.extend(added);
+        if glyphs.len() == before {
+            return Ok(());
+        }
+    }
+}
+
+/// Returns the glyphs a substitution subtable can replace the glyphs in the set with
+///
+/// Contextual lookups only apply other lookups, which are already included, so only the lookups that replace glyphs are read
+fn substitutions(
+    gsub: &[u8],
+    lookup_type: u16,
+    subtable: usize,
+    glyphs: &BTreeSet<u16>,
+) -> anyhow::Result<Vec<u16>> {
+    let covered = || -> anyhow::Result<Vec<(u16, usize)>> {
+        let coverage = coverage(gsub, subtable + read_u16(gsub, subtable + 2)? as usize)?;
+        Ok(coverage
+            .into_iter()
+            .filter(|(glyph, _)| glyphs.contains(glyph))
+            .collect())
+    };
+    let mut added = Vec::new();
+    match lookup_type {
+        // Single substitution
+        1 => {
+            let format = read_u16(gsub, subtable)?;
+            for (glyph, index) in covered()? {
+                added.push(match format {
+                    1 => glyph.wrapping_add(read_u16(gsub, subtable + 4)?),
+                    _ => read_u16(gsub, subtable + 6 + index * 2)?,
+                });
+            }
+        }
+        // Multiple and alternate substitution
+        2 | 3 => {
+            for (_, index) in covered()? {
+                let sequence = subtable + read_u16(gsub, subtable + 6 + index * 2)? as usize;
+                for glyph in 0..read_u16(gsub, sequence)? as usize {
+                    added.push(read_u16(gsub, sequence + 2 + glyph * 2)?);
+                }
+            }
+        }
+        // Ligature substitution
+        4 => {
+            for (_, index) in covered()? {
+                let set = subtable + read_u16(gsub, subtable + 6 + index * 2)? as usize;
+                for ligature in 0..read_u16(gsub, set)? as usize {
+                    let ligature = set + read_u16(gsub, set + 2 + ligature * 2)? as usize;
+                    let components = read_u16(gsub, ligature + 2)?.saturating_sub(1) as usize;
+                    let mut uses_glyphs = true;
+                    for component in 0..components {
+                        uses_glyphs &=
+                            glyphs.contains(&read_u16(gsub, ligature + 4 + component * 2)?);
+                    }
+                    if uses_glyphs {
+                        added.push(read_u16(gsub, ligature)?);
+                    }
+                }
+            }
+        }
+        // Reverse chaining single substitution
+        8 => {
+            let backtrack = subtable + 4;
+            let lookahead = backtrack + 2 + read_u16(gsub, backtrack)? as usize * 2;
+            let substitutes = lookahead + 2 + read_u16(gsub, lookahead)? as usize * 2;
+            for (_, index) in covered()? {
+                added.push(read_u16(gsub, substitutes + 2 + index * 2)?);
+            }
+        }
+        _ => {}
+    }
+    Ok(added)
+}
+
+/// Returns the glyphs in a coverage table with their coverage index
+fn coverage(gsub: &[u8], coverage: usize) -> anyhow::Result<Vec<(u16, usize)>> {
+    let count = read_u16(gsub, coverage + 2)? as usize;
+    let mut glyphs = Vec::new();
+    match read_u16(gsub, coverage)? {
+        1 => {
+            for index in 0..count {
+                glyphs.push((read_u16(gsub, coverage + 4 + index * 2)?, index));
+            }
+        }
+        2 => {
+            for range in 0..count {
+                let range = coverage + 4 + range * 6;
+                let start = read_u16(gsub, range)?;
+                let end = read_u16(gsub, range + 2)?;
+                let start_index = read_u16(gsub, range + 4)? as usize;
+                for glyph in start..=end {
+                    glyphs.push((glyph, start_index + (glyph - start) as usize));
+                }
+            }
+        }
+        format => bail!("unknown coverage format {format}"),
+    }
+    Ok(glyphs)
+}
@@ -0,0 +1,639 @@
+use image::{DynamicImage, RgbaImage};
+use manganis_common::{AssetSource, ImageOptions, SvgOptions};
+use roxmltree::{Document, Node, NodeType, ParsingOptions};
+use std::path::Path;
+use svgtypes::{PathParser, PathSegment};
+
+use crate::{
+    file::{minify_css, write_output, Process, ProcessStage, StageError, WithStage},
+    remote::{read_to_string, RemoteAssetCache},
+};
+
+impl Process for SvgOptions {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let svg = read_to_string(source, cache).stage(ProcessStage::Read)?;
+
+        let svg = if self.minify() {
+            match minify_svg(&svg, self.precision()) {
+                Ok(minified) => minified,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to minify svg {}: {}. The svg will be copied unchanged",
+                        source,
+                        err
+                    );
+                    svg
+                }
+            }
+        } else {
+            svg
+        };
+
+        write_output(output_path, svg, "svg")
+    }
+}
+
+/// Draws the svg at the smallest scale that covers the size of the image in the options. The image is cropped and
+/// resized to the exact size afterwards the same way raster images are
+pub(crate) fn rasterize_svg(svg: &[u8], options: &ImageOptions) -> anyhow::Result<DynamicImage> {
+    let tree = resvg::usvg::Tree::from_data(svg, &resvg::usvg::Options::default())?;
+    let size = tree.size();
+    let original = size.to_int_size();
+    let target = options.rasterized_size((original.width(), original.height()));
+    let scale = (target.0 as f32 / size.width()).max(target.1 as f32 / size.height());
+    let width = ((size.width() * scale).round() as u32).max(1);
+    let height = ((size.height() * scale).round() as u32).max(1);
+
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| anyhow::anyhow!("The svg is too large to rasterize at {width}x{height}"))?;
+    let transform = resvg::tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    // Tiny skia stores premultiplied colors, but images expect straight alpha
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    let image = RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("The rasterized svg has the wrong number of pixels"))?;
+    Ok(DynamicImage::ImageRgba8(image))
+}
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// Namespaces that only store data for the editor the svg was made with. Browsers ignore them
+const EDITOR_NAMESPACES: [&str; 10] = [
+    "http://www.inkscape.org/namespaces/inkscape",
+    "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+    "http://www.bohemiancoding.com/sketch/ns",
+    "http://www.figma.com/figma/ns",
+    "http://www.serif.com/",
+    "http://ns.adobe.com/",
+    "http://purl.org/dc/elements/1.1/",
+    "http://creativecommons.org/ns#",
+    "http://web.resource.org/cc/",
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+];
+
+/// Elements where whitespace is part of the content
+const TEXT_ELEMENTS: [&str; 6] = ["text", "tspan", "textPath", "title", "desc", "script"];
+
+/// Attributes that only contain numbers, lengths or lists of them
+const NUMERIC_ATTRIBUTES: [&str; 23] = [
+    "x",
+    "y",
+    "dx",
+    "dy",
+    "width",
+    "height",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "fx",
+    "fy",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "stroke-width",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-miterlimit",
+    "font-size",
+    "viewBox",
+];
+
+/// Attributes that contain fractions from 0 to 1. Rounding them to the precision of the coordinates could hide a shape or a gradient stop
+const FRACTION_ATTRIBUTES: [&str; 5] = [
+    "offset",
+    "opacity",
+    "fill-opacity",
+    "stroke-opacity",
+    "stop-opacity",
+];
+
+/// The fewest decimal places fractions are rounded to
+const MIN_FRACTION_PRECISION: u8 = 3;
+
+/// Attributes that contain numbers mixed with function names
+const TRANSFORM_ATTRIBUTES: [&str; 4] = [
+    "transform",
+    "gradientTransform",
+    "patternTransform",
+    "points",
+];
+
+fn is_editor_namespace(uri: &str) -> bool {
+    EDITOR_NAMESPACES.iter().any(|namespace| {
+        uri == *namespace || (namespace.ends_with('/') && uri.starts_with(namespace))
+    })
+}
+
+/// Removes comments, editor metadata, whitespace and groups that do nothing from the svg and rounds numbers to the precision
+pub(crate) fn minify_svg(svg: &str, precision: u8) -> anyhow::Result<String> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = Document::parse_with_options(svg, options)?;
+    let root = document.root_element();
+    anyhow::ensure!(
+        root.tag_name().name() == "svg",
+        "The root element is <{}> instead of <svg>",
+        root.tag_name().name()
+    );
+
+    let mut minifier = SvgMinifier {
+        output: String::with_capacity(svg.len()),
+        precision,
+    };
+    // The root always declares every namespace it uses
+    minifier.write_element(root, None, false);
+    Ok(minifier.output)
+}
+
+struct SvgMinifier {
+    output: String,
+    precision: u8,
+}
+
+impl SvgMinifier {
+    fn write_element(&mut self, node: Node, parent: Option<Node>, preserve_space: bool) {
+        if is_removed(node) {
+            return;
+        }
+        let name = node.tag_name();
+
+        let preserve_space = match node.attribute((roxmltree::NS_XML_URI, "space")) {
+            Some(space) => space == "preserve",
+            None => preserve_space,
+        };
+
+        // Groups without attributes don't change how their children are drawn
+        let is_group = name.namespace() == Some(SVG_NAMESPACE) && name.name() == "g";
+        let has_attributes = node
+            .attributes()
+            .any(|attribute| !attribute.namespace().is_some_and(is_editor_namespace));
+        if is_group && !has_attributes && parent.is_some() {
+            self.write_children(node, parent, preserve_space);
+            return;
+        }
+        // Empty groups don't draw anything, but they may be referenced by id
+        if is_group && node.attribute("id").is_none() && !has_content(node) {
+            return;
+        }
+
+        let tag = qualified_name(node, name.namespace(), name.name(), false);
+        self.output.push('<');
+        self.output.push_str(&tag);
+
+        // Only declare the namespaces the parent doesn't already have
+        for namespace in node.namespaces() {
+            if namespace.uri() == roxmltree::NS_XML_URI || is_editor_namespace(namespace.uri()) {
+                continue;
+            }
+            let declared_in_parent = parent.is_some_and(|parent| {
+                parent
+                    .namespaces()
+                    .any(|other| other.name() == namespace.name() && other.uri() == namespace.uri())
+            });
+            if declared_in_parent {
+                continue;
+            }
+            match namespace.name() {
+                Some(prefix) => self.output.push_str(&format!(" xmlns:{prefix}=\"")),
+                None => self.output.push_str(" xmlns=\""),
+            }
+            push_escaped(&mut self.output, namespace.uri(), true);
+            self.output.push('"');
+        }
+
+        for attribute in node.attributes() {
+            if attribute.namespace().is_some_and(is_editor_namespace) {
+                continue;
+            }
+            let name = qualified_name(node, attribute.namespace(), attribute.name(), true);
+            let value = if attribute.namespace().is_some() {
+                attribute.value().to_string()
+            } else {
+                self.minify_attribute(attribute.name(), attribute.value())
+            };
+            self.output.push(' ');
+            self.output.push_str(&name);
+            self.output.push_str("=\"");
+            push_escaped(&mut self.output, &value, true);
+            self.output.push('"');
+        }
+
+        if !has_content(node) {
+            self.output.push_str("/>");
+            return;
+        }
+        self.output.push('>');
+        if name.name() == "style" {
+            let css: String = node.children().filter_map(|child| child.text()).collect();
+            let css = minify_css(&css).unwrap_or(css);
+            push_escaped(&mut self.output, &css, false);
+        } else {
+            let preserve_space = preserve_space || TEXT_ELEMENTS.contains(&name.name());
+            self.write_children(node, Some(node), preserve_space);
+        }
+        self.output.push_str("</");
+        self.output.push_str(&tag);
+        self.output.push('>');
+    }
+
+    /// Writes the children of the node. The parent is the closest element that is written to the output
+    fn write_children(&mut self, node: Node, parent: Option<Node>, preserve_space: bool) {
+        for child in node.children() {
+            match child.node_type() {
+                NodeType::Element => self.write_element(child, parent, preserve_space),
+                NodeType::Text => {
+                    let text = child.text().unwrap_or_default();
+                    if preserve_space || !text.trim().is_empty() {
+                        push_escaped(&mut self.output, text, false);
+                    }
+                }
+                // Comments and processing instructions are removed
+                _ => {}
+            }
+        }
+    }
+
+    fn minify_attribute(&self, name: &str, value: &str) -> String {
+        if name == "d" {
+            minify_path(value, self.precision).unwrap_or_else(|| value.to_string())
+        } else if NUMERIC_ATTRIBUTES.contains(&name) || TRANSFORM_ATTRIBUTES.contains(&name) {
+            round_numbers(value, self.precision)
+        } else if FRACTION_ATTRIBUTES.contains(&name) {
+            round_numbers(value, self.precision.max(MIN_FRACTION_PRECISION))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+/// Returns whether the element only contains metadata that is removed from the output
+fn is_removed(node: Node) -> bool {
+    let name = node.tag_name();
+    name.namespace().is_some_and(is_editor_namespace)
+        || (name.namespace() == Some(SVG_NAMESPACE) && name.name() == "metadata")
+}
+
+/// Returns whether the element has children or text that is written to the output
+fn has_content(node: Node) -> bool {
+    node.children().any(|child| match child.node_type() {
+        NodeType::Element => !is_removed(child),
+        NodeType::Text => !child.text().unwrap_or_default().is_empty(),
+        _ => false,
+    })
+}
+
+/// Returns the name with the prefix of its namespace. Attributes without a prefix never have a namespace
+fn qualified_name(node: Node, namespace: Option<&str>, name: &str, attribute: bool) -> String {
+    let Some(uri) = namespace else {
+        return name.to_string();
+    };
+    if uri == roxmltree::NS_XML_URI {
+        return format!("xml:{name}");
+    }
+    let prefix = node
+        .namespaces()
+        .filter(|namespace| namespace.uri() == uri)
+        .map(|namespace| namespace.name())
+        // Prefer the default namespace for elements, but attributes always need a prefix
+        .min_by_key(|prefix| prefix.is_some() != attribute);
+    match prefix.flatten() {
+        Some(prefix) => format!("{prefix}:{name}"),
+        None => name.to_string(),
+    }
+}
+
+fn push_escaped(output: &mut String, text: &str, attribute: bool) {
+    for char in text.chars() {
+        match char {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' if !attribute => output.push_str("&gt;"),
+            '"' if attribute => output.push_str("&quot;"),
+            char => output.push(char),
+        }
+    }
+}
+
+/// Formats a number with at most `precision` decimal places and without any characters that aren't needed
+fn format_number(number: f64, precision: u8) -> String {
+    let formatted = format!("{:.*}", precision as usize, number);
+    let mut formatted = if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    };
+    if formatted == "-0" {
+        formatted = "0".to_string();
+    }
+    if let Some(fraction) = formatted.strip_prefix("0.") {
+        formatted = format!(".{fraction}");
+    } else if let Some(fraction) = formatted.strip_prefix("-0.") {
+        formatted = format!("-.{fraction}");
+    }
+    formatted
+}
+
+/// Rounds every number in the value and keeps everything else like units, separators and function names
+fn round_numbers(value: &str, precision: u8) -> String {
+    let bytes = value.as_bytes();
+    let mut output = String::with_capacity(value.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        // Names like `matrix` or `px` are copied as is
+        if byte.is_ascii_alphabetic() {
+            let start = index;
+            while index < bytes.len() && bytes[index].is_ascii_alphanumeric() {
+                index += 1;
+            }
+            output.push_str(&value[start..index]);
+            continue;
+        }
+        let number_len = number_length(&bytes[index..]);
+        if number_len == 0 {
+            output.push(value[index..].chars().next().unwrap_or_default());
+            index += value[index..]
+                .chars()
+                .next()
+                .map_or(1, |char| char.len_utf8());
+            continue;
+        }
+        let number = &value[index..index + number_len];
+        match number.parse::<f64>() {
+            Ok(parsed) => output.push_str(&format_number(parsed, precision)),
+            Err(_) => output.push_str(number),
+        }
+        index += number_len;
+    }
+    output
+}
+
+/// Returns the length of the number at the start of the bytes or 0 if it doesn't start with a number
+fn number_length(bytes: &[u8]) -> usize {
+    let digits = |start: usize| {
+        bytes[start.min(bytes.len())..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+    let mut length = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        length += 1;
+    }
+    let integer = digits(length);
+    length += integer;
+    let mut fraction = 0;
+    if bytes.get(length) == Some(&b'.') {
+        fraction = digits(length + 1);
+        if fraction > 0 {
+            length += 1 + fraction;
+        }
+    }
+    if integer == 0 && fraction == 0 {
+        return 0;
+    }
+    // The exponent needs digits after it so units like `em` are not part of the number
+    if matches!(bytes.get(length), Some(b'e' | b'E')) {
+        let mut exponent = length + 1;
+        if matches!(bytes.get(exponent), Some(b'+' | b'-')) {
+            exponent += 1;
+        }
+        let exponent_digits = digits(exponent);
+        if exponent_digits > 0 {
+            length = exponent + exponent_digits;
+        }
+    }
+    length
+}
+
+/// Writes the numbers of path data with as few separators as possible
+struct PathWriter {
+    output: String,
+    precision: u8,
+    last_command: Option<char>,
+    /// Whether the last number has a decimal point, so a number starting with `.` can follow it without a separator
+    last_has_point: bool,
+}
+
+impl PathWriter {
+    fn command(&mut self, command: char) {
+        // Repeated commands can be left out, except for move which turns into a line when it is repeated
+        if self.last_command != Some(command) || command.eq_ignore_ascii_case(&'m') {
+            self.output.push(command);
+            self.last_command = Some(command);
+            self.last_has_point = false;
+        }
+    }
+
+    fn number(&mut self, number: f64) {
+        let formatted = format_number(number, self.precision);
+        let needs_separator = match self.output.chars().last() {
+            Some(last) if last.is_ascii_digit() || last == '.' => {
+                // A sign or a second decimal point starts a new number
+                let starts_number = formatted.starts_with('-')
+                    || (formatted.starts_with('.') && self.last_has_point);
+                !starts_number
+            }
+            _ => false,
+        };
+        if needs_separator {
+            self.output.push(' ');
+        }
+        self.last_has_point = formatted.contains('.');
+        self.output.push_str(&formatted);
+    }
+
+    fn flag(&mut self, flag: bool) {
+        self.number(if flag { 1. } else { 0. });
+    }
+}
+
+/// Rewrites path data with rounded coordinates. Relative coordinates are rounded against the rounded position so
+/// the rounding errors don't add up along the path. Returns `None` if the path data is invalid
+fn minify_path(path: &str, precision: u8) -> Option<String> {
+    let round = |number: f64| {
+        format_number(number, precision)
+            .parse::<f64>()
+            .unwrap_or(number)
+    };
+    let mut writer = PathWriter {
+        output: String::with_capacity(path.len()),
+        precision,
+        last_command: None,
+        last_has_point: false,
+    };
+    // The exact position and the position of the rounded path
+    let mut current = (0., 0.);
+    let mut rounded = (0., 0.);
+    let mut start = (0., 0.);
+    let mut rounded_start = (0., 0.);
+
+    for segment in PathParser::from(path) {
+        let segment = segment.ok()?;
+        let abs = match segment {
+            PathSegment::MoveTo { abs, .. }
+            | PathSegment::LineTo { abs, .. }
+            | PathSegment::HorizontalLineTo { abs, .. }
+            | PathSegment::VerticalLineTo { abs, .. }
+            | PathSegment::CurveTo { abs, .. }
+            | PathSegment::SmoothCurveTo { abs, .. }
+            | PathSegment::Quadratic { abs, .. }
+            | PathSegment::SmoothQuadratic { abs, .. }
+            | PathSegment::EllipticalArc { abs, .. }
+            | PathSegment::ClosePath { abs } => abs,
+        };
+        // Converts a point of the segment to an absolute point
+        let absolute = |(x, y): (f64, f64)| {
+            if abs {
+                (x, y)
+            } else {
+                (current.0 + x, current.1 + y)
+            }
+        };
+        let letter = |command: char| {
+            if abs {
+                command
+            } else {
+                command.to_ascii_lowercase()
+            }
+        };
+
+        // Writes a point and returns where the rounded path ends up
+        let point = |writer: &mut PathWriter, point: (f64, f64)| {
+            let (x, y) = absolute(point);
+            if abs {
+                let (x, y) = (round(x), round(y));
+                writer.number(x);
+                writer.number(y);
+                (x, y)
+            } else {
+                let (dx, dy) = (round(x - rounded.0), round(y - rounded.1));
+                writer.number(dx);
+                writer.number(dy);
+                (rounded.0 + dx, rounded.1 + dy)
+            }
+        };
+
+        let (end, rounded_end) = match segment {
+            PathSegment::MoveTo { x, y, .. } => {
+                writer.command(letter('M'));
+                let rounded_end = point(&mut writer, (x, y));
+                start = absolute((x, y));
+                rounded_start = rounded_end;
+                (start, rounded_end)
+            }
+            PathSegment::LineTo { x, y, .. } => {
+                writer.command(letter('L'));
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::HorizontalLineTo { x, .. } => {
+                writer.command(letter('H'));
+                let end = if abs { x } else { current.0 + x };
+                let rounded_x = if abs {
+                    let x = round(x);
+                    writer.number(x);
+                    x
+                } else {
+                    let dx = round(end - rounded.0);
+                    writer.number(dx);
+                    rounded.0 + dx
+                };
+                ((end, current.1), (rounded_x, rounded.1))
+            }
+            PathSegment::VerticalLineTo { y, .. } => {
+                writer.command(letter('V'));
+                let end = if abs { y } else { current.1 + y };
+                let rounded_y = if abs {
+                    let y = round(y);
+                    writer.number(y);
+                    y
+                } else {
+                    let dy = round(end - rounded.1);
+                    writer.number(dy);
+                    rounded.1 + dy
+                };
+                ((current.0, end), (rounded.0, rounded_y))
+            }
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+                ..
+            } => {
+                writer.command(letter('C'));
+                point(&mut writer, (x1, y1));
+                point(&mut writer, (x2, y2));
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::SmoothCurveTo { x2, y2, x, y, .. } => {
+                writer.command(letter('S'));
+                point(&mut writer, (x2, y2));
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::Quadratic { x1, y1, x, y, .. } => {
+                writer.command(letter('Q'));
+                point(&mut writer, (x1, y1));
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::SmoothQuadratic { x, y, .. } => {
+                writer.command(letter('T'));
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::EllipticalArc {
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+                ..
+            } => {
+                writer.command(letter('A'));
+                writer.number(round(rx));
+                writer.number(round(ry));
+                writer.number(round(x_axis_rotation));
+                writer.flag(large_arc);
+                writer.flag(sweep);
+                (absolute((x, y)), point(&mut writer, (x, y)))
+            }
+            PathSegment::ClosePath { .. } => {
+                // Closing the path always goes back to the start, so the command is never left out
+                writer.output.push(letter('Z'));
+                writer.last_command = None;
+                (start, rounded_start)
+            }
+        };
+        current = end;
+        rounded = rounded_end;
+    }
+
+    Some(writer.output)
+}
@@ -0,0 +1,277 @@
+use anyhow::Context;
+use manganis_common::{AssetSource, VideoOptions, VideoType};
+use std::{ops::Range, path::Path};
+
+use crate::{
+    file::{write_output, Process, ProcessStage, StageError, WithStage},
+    remote::{read_to_bytes, RemoteAssetCache},
+};
+
+impl Process for VideoOptions {
+    fn process(
+        &self,
+        source: &AssetSource,
+        output_path: &Path,
+        cache: &RemoteAssetCache,
+    ) -> Result<(), StageError> {
+        let bytes = read_to_bytes(source, cache).stage(ProcessStage::Read)?;
+
+        let video = match self.ty() {
+            // QuickTime videos are made of the same boxes as mp4 videos, so the media data can be moved the same way
+            VideoType::MP4 | VideoType::MOV => match optimize_mp4(&bytes, self.compress()) {
+                Ok(Some(optimized)) => optimized,
+                Ok(None) => bytes,
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to optimize mp4 {}: {}. The video will be copied unchanged",
+                        source,
+                        err
+                    );
+                    bytes
+                }
+            },
+            // Webm and gif videos are copied as-is
+            VideoType::Webm | VideoType::GIF => bytes,
+        };
+
+        write_output(output_path, video, "video")
+    }
+}
+
+/// Boxes that only contain other boxes. We need to look inside them to find the chunk offset tables
+const CONTAINER_BOXES: [&[u8; 4]; 8] = [
+    b"moov", b"trak", b"mdia", b"minf", b"stbl", b"edts", b"dinf", b"mvex",
+];
+
+/// Boxes that don't affect playback and are removed when the video is compressed
+const METADATA_BOXES: [&[u8; 4]; 4] = [b"free", b"skip", b"udta", b"meta"];
+
+/// Moves the `moov` box in front of the media data so browsers can start playing the video before it is fully downloaded
+/// and, if `compress` is set, removes metadata boxes that are not needed for playback.
+///
+/// Returns `None` if the video can't be optimized and should be copied as-is.
+fn optimize_mp4(bytes: &[u8], compress: bool) -> anyhow::Result<Option<Vec<u8>>> {
+    let boxes = parse_boxes(bytes, 0)?;
+
+    // Fragmented videos are already streamable and their offsets are relative to each fragment
+    if boxes.iter().any(|mp4_box| &mp4_box.kind == b"moof") {
+        return Ok(None);
+    }
+    let Some(moov) = boxes.iter().find(|mp4_box| &mp4_box.kind == b"moov") else {
+        return Ok(None);
+    };
+    let mut moov = Mp4Box::parse(bytes, moov, compress)?;
+
+    // Put the moov box before the first media data box, keeping every other box in the same order
+    let first_mdat = boxes
+        .iter()
+        .position(|mp4_box| &mp4_box.kind == b"mdat")
+        .unwrap_or(boxes.len());
+    let mut layout: Vec<Option<&RawBox>> = Vec::new();
+    for (index, mp4_box) in boxes.iter().enumerate() {
+        if index == first_mdat {
+            layout.push(None);
+        }
+        if &mp4_box.kind == b"moov" || (compress && METADATA_BOXES.contains(&&mp4_box.kind)) {
+            continue;
+        }
+        layout.push(Some(mp4_box));
+    }
+    if first_mdat == boxes.len() {
+        layout.push(None);
+    }
+
+    // Find where each box will end up so we can move the chunk offsets with the media data
+    let moov_len = moov.len();
+    let mut moved = Vec::new();
+    let mut position = 0;
+    for mp4_box in &layout {
+        match mp4_box {
+            Some(mp4_box) => {
+                moved.push((mp4_box.range.clone(), position));
+                position += mp4_box.range.len() as u64;
+            }
+            None => position += moov_len,
+        }
+    }
+    moov.relocate_chunk_offsets(&|offset| {
+        moved
+            .iter()
+            .find(|(range, _)| range.contains(&(offset as usize)))
+            .map(|(range, new_start)| offset - range.start as u64 + new_start)
+    })?;
+
+    let mut output = Vec::with_capacity(bytes.len());
+    for mp4_box in layout {
+        match mp4_box {
+            Some(mp4_box) => output.extend_from_slice(&bytes[mp4_box.range.clone()]),
+            None => moov.write(&mut output),
+        }
+    }
+
+    Ok(Some(output))
+}
+
+/// The location of a box in the original file
+struct RawBox {
+    kind: [u8; 4],
+    /// The range of the whole box, including the header
+    range: Range<usize>,
+    /// The range of the contents of the box
+    content: Range<usize>,
+}
+
+/// Splits a range of the file into boxes
+fn parse_boxes(bytes: &[u8], start: usize) -> anyhow::Result<Vec<RawBox>> {
+    let mut boxes = Vec::new();
+    let mut offset = start;
+    while offset < bytes.len() {
+        let header = bytes
+            .get(offset..offset + 8)
+            .context("box header is truncated")?;
+        let kind: [u8; 4] = header[4..8].try_into().unwrap();
+        let (size, header_len) = match u32::from_be_bytes(header[0..4].try_into().unwrap()) {
+            // The box extends to the end of the file
+            0 => ((bytes.len() - offset) as u64, 8),
+            // The size is stored as a 64 bit integer after the type
+            1 => {
+                let size = bytes
+                    .get(offset + 8..offset + 16)
+                    .context("box header is truncated")?;
+                (u64::from_be_bytes(size.try_into().unwrap()), 16)
+            }
+            size => (size as u64, 8),
+        };
+        let end = usize::try_from(size)
+            .ok()
+            .and_then(|size| offset.checked_add(size))
+            .filter(|end| *end <= bytes.len() && size >= header_len as u64)
+            .with_context(|| {
+                format!(
+                    "box `{}` has an invalid size",
+                    String::from_utf8_lossy(&kind)
+                )
+            })?;
+        boxes.push(RawBox {
+            kind,
+            range: offset..end,
+            content: offset + header_len..end,
+        });
+        offset = end;
+    }
+    Ok(boxes)
+}
+
+/// A box inside the `moov` box that may be rewritten
+struct Mp4Box {
+    kind: [u8; 4],
+    contents: Mp4BoxContents,
+}
+
+enum Mp4BoxContents {
+    Children(Vec<Mp4Box>),
+    Data(Vec<u8>),
+}
+
+impl Mp4Box {
+    fn parse(bytes: &[u8], raw: &RawBox, compress: bool) -> anyhow::Result<Self> {
+        let contents = if CONTAINER_BOXES.contains(&&raw.kind) {
+            let children = parse_boxes(&bytes[..raw.content.end], raw.content.start)?
+                .iter()
+                .filter(|child| !(compress && METADATA_BOXES.contains(&&child.kind)))
+                .map(|child| Self::parse(bytes, child, compress))
+                .collect::<anyhow::Result<_>>()?;
+            Mp4BoxContents::Children(children)
+        } else {
+            Mp4BoxContents::Data(bytes[raw.content.clone()].to_vec())
+        };
+        Ok(Self {
+            kind: raw.kind,
+            contents,
+        })
+    }
+
+    fn content_len(&self) -> u64 {
+        match &self.contents {
+            Mp4BoxContents::Children(children) => children.iter().map(Self::len).sum(),
+            Mp4BoxContents::Data(data) => data.len() as u64,
+        }
+    }
+
+    /// Boxes that are too large for a 32 bit size store a 64 bit size after the type
+    fn is_large(&self) -> bool {
+        self.content_len() + 8 > u32::MAX as u64
+    }
+
+    /// The length of the box including the header
+    fn len(&self) -> u64 {
+        let header_len = if self.is_large() { 16 } else { 8 };
+        self.content_len() + header_len
+    }
+
+    fn write(&self, output: &mut Vec<u8>) {
+        if self.is_large() {
+            output.extend_from_slice(&1u32.to_be_bytes());
+            output.extend_from_slice(&self.kind);
+            output.extend_from_slice(&self.len().to_be_bytes());
+        } else {
+            output.extend_from_slice(&(self.len() as u32).to_be_bytes());
+            output.extend_from_slice(&self.kind);
+        }
+        match &self.contents {
+            Mp4BoxContents::Children(children) => {
+                for child in children {
+                    child.write(output);
+                }
+            }
+            Mp4BoxContents::Data(data) => output.extend_from_slice(data),
+        }
+    }
+
+    /// Rewrite every chunk offset in the `stco` and `co64` tables
+    fn relocate_chunk_offsets(
+        &mut self,
+        relocate: &impl Fn(u64) -> Option<u64>,
+    ) -> anyhow::Result<()> {
+        let data = match &mut self.contents {
+            Mp4BoxContents::Children(children) => {
+                for child in children {
+                    child.relocate_chunk_offsets(relocate)?;
+                }
+                return Ok(());
+            }
+            Mp4BoxContents::Data(data) => data,
+        };
+
+        let entry_size = match &self.kind {
+            b"stco" => 4,
+            b"co64" => 8,
+            _ => return Ok(()),
+        };
+        // Skip the version and flags
+        let count = data
+            .get(4..8)
+            .map(|count| u32::from_be_bytes(count.try_into().unwrap()) as usize)
+            .context("chunk offset table is truncated")?;
+        let entries = data
+            .get_mut(8..8 + count * entry_size)
+            .context("chunk offset table is truncated")?;
+        for entry in entries.chunks_exact_mut(entry_size) {
+            let offset = match entry_size {
+                4 => u32::from_be_bytes(entry.try_into().unwrap()) as u64,
+                _ => u64::from_be_bytes(entry.try_into().unwrap()),
+            };
+            let relocated = relocate(offset)
+                .with_context(|| format!("chunk offset {offset} is outside of the file"))?;
+            match entry_size {
+                4 => entry.copy_from_slice(
+                    &u32::try_from(relocated)
+                        .context("chunk offset no longer fits in a 32 bit table")?
+                        .to_be_bytes(),
+                ),
+                _ => entry.copy_from_slice(&relocated.to_be_bytes()),
+            }
+        }
+        Ok(())
+    }
+}
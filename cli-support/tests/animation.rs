@@ -0,0 +1,65 @@
+mod common;
+
+use common::process_asset;
+use image::codecs::gif::GifEncoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, Delay, Frame, Rgba, RgbaImage};
+use manganis_common::{FileOptions, ImageOptions, ImageType};
+
+const DELAYS_MS: [u32; 3] = [100, 200, 300];
+
+/// Creates a 40x20 gif with a frame of a different color for each delay
+fn animated_gif(dir: &std::path::Path) -> std::path::PathBuf {
+    let path = dir.join("spinner.gif");
+    let mut gif = Vec::new();
+    let frames = DELAYS_MS.iter().enumerate().map(|(index, delay)| {
+        let mut color = [0, 0, 0, 255];
+        color[index] = 255;
+        Frame::from_parts(
+            RgbaImage::from_pixel(40, 20, Rgba(color)),
+            0,
+            0,
+            Delay::from_numer_denom_ms(*delay, 1),
+        )
+    });
+    GifEncoder::new(&mut gif).encode_frames(frames).unwrap();
+    std::fs::write(&path, gif).unwrap();
+    path
+}
+
+fn process(path: std::path::PathBuf, options: ImageOptions) -> Vec<u8> {
+    process_asset(&path, FileOptions::Image(options))
+}
+
+#[test]
+fn animated_gifs_are_converted_to_animated_webp() {
+    let source = tempfile::tempdir().unwrap();
+    let gif = animated_gif(source.path());
+
+    let webp = process(gif, ImageOptions::new(ImageType::Webp, Some((20, 10))));
+    let frames = WebPDecoder::new(std::io::Cursor::new(webp))
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+
+    // Every frame keeps its timing and is resized
+    assert_eq!(frames.len(), DELAYS_MS.len());
+    for (index, (frame, delay)) in frames.iter().zip(DELAYS_MS).enumerate() {
+        assert_eq!(frame.delay(), Delay::from_numer_denom_ms(delay, 1));
+        assert_eq!(frame.buffer().dimensions(), (20, 10));
+        let pixel = frame.buffer().get_pixel(10, 5);
+        assert!(pixel[index] > 200, "frame {index} is {pixel:?}");
+    }
+}
+
+#[test]
+fn other_formats_keep_the_first_frame() {
+    let source = tempfile::tempdir().unwrap();
+    let gif = animated_gif(source.path());
+
+    let png = process(gif, ImageOptions::new(ImageType::Png, None));
+    let image = image::load_from_memory(&png).unwrap().into_rgba8();
+    assert_eq!(image.dimensions(), (40, 20));
+    assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use common::process;
+use manganis_cli_support::{select_image_format, ProcessStage, RemoteAssetCache};
+use manganis_common::{
+    AssetSource, FileAsset, FileOptions, ImageOptions, ImageType, Integrity, IntegrityAlgorithm,
+};
+
+const ALL_FORMATS: [ImageType; 4] = [
+    ImageType::Avif,
+    ImageType::Webp,
+    ImageType::Jpg,
+    ImageType::Png,
+];
+
+/// Creates a 48x48 png with a gradient. The image is transparent if `alpha` is less than 255
+fn gradient_image(dir: &std::path::Path, alpha: u8) -> FileAsset {
+    let path = dir.join(format!("gradient-{alpha}.png"));
+    image::RgbaImage::from_fn(48, 48, |x, y| {
+        image::Rgba([(x * 5) as u8, (y * 5) as u8, 128, alpha])
+    })
+    .save(&path)
+    .unwrap();
+    image_asset(path, 80)
+}
+
+/// Creates a 64x64 png with a lot of detail that is hard to compress without losing quality
+fn noisy_image(dir: &std::path::Path) -> FileAsset {
+    let path = dir.join("noise.png");
+    let mut seed = 1u32;
+    image::RgbaImage::from_fn(64, 64, |x, y| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        let noise = (seed >> 16) as u8 / 4;
+        image::Rgba([(x * 4) as u8 ^ noise, (y * 4) as u8, noise, 255])
+    })
+    .save(&path)
+    .unwrap();
+    image_asset(path, 10)
+}
+
+fn image_asset(path: std::path::PathBuf, quality: u8) -> FileAsset {
+    let mut options = ImageOptions::new(ImageType::Png, None);
+    options.set_quality(Some(quality));
+    FileAsset::new(AssetSource::Local(path))
+        .unwrap()
+        .with_options(FileOptions::Image(options))
+}
+
+fn with_quality(file: &FileAsset, quality: u8) -> FileAsset {
+    let mut file = file.clone();
+    file.with_options_mut(|options| {
+        if let FileOptions::Image(options) = options {
+            options.set_quality(Some(quality));
+        }
+    });
+    file
+}
+
+fn pick(
+    file: &FileAsset,
+    candidates: &[ImageType],
+    cache_folder: &std::path::Path,
+) -> Result<ImageType, manganis_cli_support::ProcessError> {
+    pick_at(file, candidates, 80, cache_folder)
+}
+
+fn pick_at(
+    file: &FileAsset,
+    candidates: &[ImageType],
+    min_quality: u8,
+    cache_folder: &std::path::Path,
+) -> Result<ImageType, manganis_cli_support::ProcessError> {
+    select_image_format(
+        file,
+        candidates,
+        min_quality,
+        cache_folder,
+        &RemoteAssetCache::default(),
+    )
+}
+
+fn with_format(file: &FileAsset, ty: ImageType) -> FileAsset {
+    let mut file = file.clone();
+    file.with_options_mut(|options| {
+        if let FileOptions::Image(options) = options {
+            options.set_ty(ty);
+        }
+    });
+    file
+}
+
+#[test]
+fn the_smallest_format_is_picked_if_every_format_meets_the_quality() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let file = gradient_image(source.path(), 255);
+
+    let picked = pick_at(&file, &ALL_FORMATS, 1, cache.path()).unwrap();
+
+    // Processing the image in the picked format gives the smallest output
+    let size = |ty| process(&with_format(&file, ty)).len();
+    let picked_size = size(picked);
+    for ty in ALL_FORMATS {
+        assert!(picked_size <= size(ty), "{picked:?} is larger than {ty:?}");
+    }
+}
+
+#[test]
+fn formats_that_look_worse_than_the_quality_are_skipped() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    // At a low encoder quality, webp is smaller than avif but blurs the gradient much more
+    let file = with_quality(&gradient_image(source.path(), 255), 10);
+    let size = |ty| process(&with_format(&file, ty)).len();
+    assert!(size(ImageType::Webp) < size(ImageType::Avif));
+
+    let candidates = [ImageType::Webp, ImageType::Avif];
+    assert_eq!(
+        pick_at(&file, &candidates, 70, cache.path()).unwrap(),
+        ImageType::Avif
+    );
+    // Webp is picked once it is good enough
+    assert_eq!(
+        pick_at(&file, &candidates, 1, cache.path()).unwrap(),
+        ImageType::Webp
+    );
+}
+
+#[test]
+fn the_closest_format_is_picked_if_none_meet_the_quality() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let file = noisy_image(source.path());
+    let size = |ty| process(&with_format(&file, ty)).len();
+    assert!(size(ImageType::Webp) < size(ImageType::Png));
+
+    let picked = pick_at(&file, &[ImageType::Webp, ImageType::Png], 100, cache.path());
+    assert_eq!(picked.unwrap(), ImageType::Png);
+}
+
+#[test]
+fn transparent_images_are_never_jpg() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let file = gradient_image(source.path(), 128);
+
+    let picked = pick(&file, &[ImageType::Jpg, ImageType::Png], cache.path());
+    assert_eq!(picked.unwrap(), ImageType::Png);
+
+    let error = pick(&file, &[ImageType::Jpg], cache.path()).unwrap_err();
+    assert_eq!(error.stage(), ProcessStage::Encode);
+
+    // The alpha of formats that can store it is measured with the color
+    let picked = pick(&file, &[ImageType::Avif, ImageType::Webp], cache.path());
+    assert!(picked.is_ok(), "{picked:?}");
+}
+
+#[test]
+fn the_choice_is_cached() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let file = gradient_image(source.path(), 255);
+
+    let picked = pick(&file, &ALL_FORMATS, cache.path()).unwrap();
+    let entries: Vec<_> = std::fs::read_dir(cache.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&entries[0]).unwrap(),
+        picked.extension()
+    );
+
+    // The cached format is used instead of encoding the image again
+    let other = ALL_FORMATS.into_iter().find(|ty| *ty != picked).unwrap();
+    std::fs::write(&entries[0], other.extension()).unwrap();
+    assert_eq!(pick(&file, &ALL_FORMATS, cache.path()).unwrap(), other);
+
+    // The format the image starts with doesn't change the choice
+    assert_eq!(
+        pick(
+            &with_format(&file, ImageType::Webp),
+            &ALL_FORMATS,
+            cache.path()
+        )
+        .unwrap(),
+        other
+    );
+
+    // Changing the options, the candidates or the minimum quality picks the format again
+    pick(&with_quality(&file, 40), &ALL_FORMATS, cache.path()).unwrap();
+    pick(&file, &[ImageType::Png], cache.path()).unwrap();
+    pick_at(&file, &ALL_FORMATS, 50, cache.path()).unwrap();
+    assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 4);
+}
+
+#[test]
+fn remote_images_are_read_through_the_cache_and_verified() {
+    let source = tempfile::tempdir().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let downloads = tempfile::tempdir().unwrap();
+    let local = gradient_image(source.path(), 255);
+    let AssetSource::Local(path) = local.location().source() else {
+        unreachable!()
+    };
+    let server = common::TestServer::start(vec![("/image.png", std::fs::read(path).unwrap())]);
+    let url = server.url("/image.png");
+    let remote = |integrity| {
+        let mut file = FileAsset::new(AssetSource::Remote(url.clone()))
+            .unwrap()
+            .with_options(local.options().clone());
+        file.set_integrity(integrity);
+        file
+    };
+    let downloads = RemoteAssetCache::new(downloads.path());
+
+    select_image_format(&remote(None), &ALL_FORMATS, 80, cache.path(), &downloads).unwrap();
+    assert!(downloads.cache_path(&url).is_file());
+
+    // The cached download doesn't match the pinned integrity and the server returns the same contents
+    let wrong = Integrity::compute(IntegrityAlgorithm::Sha256, b"something else");
+    let error = select_image_format(
+        &remote(Some(wrong)),
+        &ALL_FORMATS,
+        80,
+        cache.path(),
+        &downloads,
+    )
+    .unwrap_err();
+    assert_eq!(error.stage(), ProcessStage::Read);
+}
@@ -1,4 +1,4 @@
-use manganis_cli_support::{AssetManifestExt, ManganisSupportGuard};
+use manganis_cli_support::{enable_manganis_support, AssetManifestExt};
 use manganis_common::{AssetManifest, AssetType, Config};
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
@@ -7,17 +7,6 @@ use std::process::{Command, Stdio};
 fn collects_assets() {
     tracing_subscriber::fmt::init();
 
-    // This is the location where the assets will be served from
-    let assets_serve_location = "/";
-
-    // First set any settings you need for the build
-    Config::default()
-        .with_assets_serve_location(assets_serve_location)
-        .save();
-
-    // Next, tell manganis that you support assets
-    let _guard = ManganisSupportGuard::default();
-
     // Get args and default to "build"
     let args: Vec<String> = std::env::args().collect();
     let command = match args.get(1) {
@@ -43,9 +32,14 @@ fn build() {
 
     println!("running the CLI from {test_package_dir:?}");
 
+    // Set any settings you need for the build. The assets will be served from the root
+    let config = Config::default().with_assets_serve_location("/");
+
     // Then build your application
     let args = ["--target", "wasm32-unknown-unknown", "--release"];
-    Command::new("cargo")
+    let mut build = Command::new("cargo");
+    config
+        .apply_to_command(enable_manganis_support(&mut build))
         .arg("build")
         .args(args)
         .current_dir(&test_package_dir)
@@ -60,7 +54,17 @@ fn build() {
     // Call the helper function to intercept the Rust linker.
     // We will pass the current working directory as it may get lost.
     let link_args = vec![format!("{}", test_package_dir.display())];
-    manganis_cli_support::start_linker_intercept("link", args, Some(link_args)).unwrap();
+    let (mut intercept, script) =
+        manganis_cli_support::linker_intercept_command("link", args, Some(link_args)).unwrap();
+    config
+        .apply_to_command(&mut intercept)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+        .wait()
+        .unwrap();
+    manganis_cli_support::delete_linker_script(&script).unwrap();
 }
 
 fn link() {
@@ -71,7 +75,7 @@ fn link() {
     let working_dir = PathBuf::from(link_args.first().unwrap());
 
     // Then collect the assets
-    let assets = AssetManifest::load_from_objects(object_files);
+    let mut assets = AssetManifest::load_from_objects(object_files);
 
     let all_assets = assets.assets();
     println!("{:#?}", all_assets);
@@ -85,11 +89,12 @@ fn link() {
         .collect::<Vec<_>>();
 
     // Make sure the right number of assets were collected
-    assert_eq!(locations.len(), 16);
+    assert_eq!(locations.len(), 21);
 
     // Then copy the assets to a temporary directory and run the application
     let assets_dir = PathBuf::from("./assets");
-    assets.copy_static_assets_to(assets_dir).unwrap();
+    let config = Config::default().with_assets_serve_location("/");
+    assets.copy_static_assets_to(assets_dir, &config).unwrap();
 
     // Then run the application
     let status = Command::new("cargo")
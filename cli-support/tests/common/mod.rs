@@ -0,0 +1,155 @@
+use manganis_cli_support::process_file;
+use manganis_common::{AssetSource, FileAsset, FileOptions};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+/// Creates an asset for a local file with the options
+#[allow(unused)]
+pub fn file_asset(path: &Path, options: FileOptions) -> FileAsset {
+    FileAsset::new(AssetSource::Local(path.to_path_buf()))
+        .unwrap()
+        .with_options(options)
+}
+
+/// Processes a local file with the options and returns the processed file
+#[allow(unused)]
+pub fn process_asset(path: &Path, options: FileOptions) -> Vec<u8> {
+    process(&file_asset(path, options))
+}
+
+/// Processes the asset into a temporary folder and returns the processed file
+#[allow(unused)]
+pub fn process(asset: &FileAsset) -> Vec<u8> {
+    let output = tempfile::tempdir().unwrap();
+    process_file(asset, output.path()).unwrap();
+    let unique_name = asset.location().unique_name();
+    if let Some(extension) = asset.options().extension() {
+        assert!(unique_name.ends_with(extension), "{unique_name}");
+    }
+    std::fs::read(output.path().join(unique_name)).unwrap()
+}
+
+/// Writes the contents to a file with the name, processes it with the options and returns the processed file
+#[allow(unused)]
+pub fn process_contents(name: &str, contents: impl AsRef<[u8]>, options: FileOptions) -> Vec<u8> {
+    let source = tempfile::tempdir().unwrap();
+    let path = source.path().join(name);
+    std::fs::write(&path, contents).unwrap();
+    process_asset(&path, options)
+}
+
+/// A minimal HTTP server that serves canned responses for tests
+#[allow(unused)]
+pub struct TestServer {
+    address: String,
+    routes: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    requests: Arc<Mutex<HashMap<String, usize>>>,
+    user_agents: Arc<Mutex<HashMap<String, String>>>,
+}
+
+#[allow(unused)]
+impl TestServer {
+    /// Start a server that responds to each path with the given body
+    pub fn start(routes: Vec<(&str, Vec<u8>)>) -> Self {
+        let routes: HashMap<String, Vec<u8>> = routes
+            .into_iter()
+            .map(|(path, body)| (path.to_string(), body))
+            .collect();
+        let routes = Arc::new(Mutex::new(routes));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(HashMap::new()));
+        let user_agents = Arc::new(Mutex::new(HashMap::new()));
+
+        let request_counts = requests.clone();
+        let request_user_agents = user_agents.clone();
+        let served_routes = routes.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                let path = request_line
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+
+                // Skip the headers, except for the user agent
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok() && line != "\r\n" && !line.is_empty() {
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("user-agent") {
+                            request_user_agents
+                                .lock()
+                                .unwrap()
+                                .insert(path.clone(), value.trim().to_string());
+                        }
+                    }
+                    line.clear();
+                }
+
+                *request_counts
+                    .lock()
+                    .unwrap()
+                    .entry(path.clone())
+                    .or_insert(0) += 1;
+
+                let (status, body) = match served_routes.lock().unwrap().get(&path) {
+                    Some(body) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", b"not found".to_vec()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        Self {
+            address,
+            routes,
+            requests,
+            user_agents,
+        }
+    }
+
+    /// Change the body the server responds to a path with
+    pub fn set(&self, path: &str, body: Vec<u8>) {
+        self.routes.lock().unwrap().insert(path.to_string(), body);
+    }
+
+    /// Stop serving a path, so requests to it fail
+    pub fn remove(&self, path: &str) {
+        self.routes.lock().unwrap().remove(path);
+    }
+
+    /// Returns the url of a path on the server
+    pub fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("{}{path}", self.address)).unwrap()
+    }
+
+    /// Returns the number of times a path was requested
+    pub fn requests(&self, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the user agent of the last request to a path
+    pub fn user_agent(&self, path: &str) -> Option<String> {
+        self.user_agents.lock().unwrap().get(path).cloned()
+    }
+}
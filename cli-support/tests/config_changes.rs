@@ -0,0 +1,51 @@
+use manganis_cli_support::enable_manganis_support;
+use manganis_common::Config;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Builds the test package with the config and returns the path to the executable
+fn build_test_package(test_package_dir: &Path, config: &Config) -> PathBuf {
+    let mut build = Command::new("cargo");
+    let output = config
+        .apply_to_command(enable_manganis_support(&mut build))
+        .args(["build", "--message-format=json-render-diagnostics"])
+        .current_dir(test_package_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["target"]["name"] == "test-package")
+        .find_map(|message| message["executable"].as_str().map(PathBuf::from))
+        .unwrap()
+}
+
+fn contains(haystack: &[u8], needle: &str) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle.as_bytes())
+}
+
+#[test]
+fn changing_the_config_rebuilds_the_assets() {
+    let test_package_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("test-package");
+
+    // Cargo only expands the macros again if it knows they depend on the config
+    let first = Config::default().with_assets_serve_location("/first-location/");
+    let executable = build_test_package(&test_package_dir, &first);
+    let binary = std::fs::read(&executable).unwrap();
+    assert!(contains(&binary, "/first-location/"));
+
+    let second = Config::default().with_assets_serve_location("/second-location/");
+    let executable = build_test_package(&test_package_dir, &second);
+    let binary = std::fs::read(&executable).unwrap();
+    assert!(contains(&binary, "/second-location/"));
+    assert!(!contains(&binary, "/first-location/"));
+}
@@ -0,0 +1,337 @@
+use manganis_cli_support::{process_file, AssetManifestExt};
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, FileAsset, FileOptions, FontFace, FontType,
+    MANIFEST_FILE_NAME,
+};
+use std::path::Path;
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+const NOTDEF: u16 = 0;
+const A: u16 = 1;
+const B: u16 = 2;
+/// A composite glyph drawn with the outline of `A`
+const C: u16 = 3;
+const F: u16 = 4;
+const I: u16 = 5;
+/// The ligature GSUB replaces `f` `i` with
+const FI: u16 = 6;
+
+fn be16(values: &[u16]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_be_bytes())
+        .collect()
+}
+
+fn triangle() -> Vec<u8> {
+    let mut glyph = be16(&[1, 0, 0, 500, 700, 2, 0]);
+    glyph.extend_from_slice(&[1, 1, 1]);
+    glyph.extend(be16(&[0, 250, 250]));
+    glyph.extend(be16(&[0, 700, (-700i16) as u16]));
+    glyph
+}
+
+fn composite(component: u16) -> Vec<u8> {
+    let (min, max) = (0, 500);
+    // ARG_1_AND_2_ARE_WORDS | ARGS_ARE_XY_VALUES
+    be16(&[(-1i16) as u16, min, min, max, max, 0x0003, component, 0, 0])
+}
+
+fn table_directory(tables: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut font = be16(&[1, 0, tables.len() as u16, 0, 0, 0]);
+    let mut offset = 12 + 16 * tables.len();
+    let mut data = Vec::new();
+    for (tag, table) in tables {
+        font.extend_from_slice(*tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        data.extend_from_slice(table);
+        data.resize(data.len().next_multiple_of(4), 0);
+        offset = 12 + 16 * tables.len() + data.len();
+    }
+    font.extend(data);
+    font
+}
+
+/// Builds a small TrueType font with the glyphs `A`, `B`, `C`, `f`, `i` and a `fi` ligature
+fn test_font() -> Vec<u8> {
+    let glyphs = [
+        triangle(),
+        triangle(),
+        triangle(),
+        composite(A),
+        triangle(),
+        triangle(),
+        triangle(),
+    ];
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+    for glyph in &glyphs {
+        loca.extend((glyf.len() as u32).to_be_bytes());
+        glyf.extend_from_slice(glyph);
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    loca.extend((glyf.len() as u32).to_be_bytes());
+
+    let mut head = be16(&[1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+    head.extend_from_slice(&[0; 16]);
+    head.extend(be16(&[0, 0, 500, 700, 0, 8, 2, 1, 0]));
+
+    let mut hhea = be16(&[1, 0, 800, (-200i16) as u16, 0, 500]);
+    hhea.extend_from_slice(&[0; 22]);
+    hhea.extend(be16(&[glyphs.len() as u16]));
+    let hmtx = be16(&[500, 0].repeat(glyphs.len()));
+    let maxp = be16(&[0, 0x5000, glyphs.len() as u16]);
+
+    let mapping = [('A', A), ('B', B), ('C', C), ('f', F), ('i', I)];
+    let mut cmap_subtable = be16(&[12, 0]);
+    cmap_subtable.extend(((16 + 12 * mapping.len()) as u32).to_be_bytes());
+    cmap_subtable.extend(0u32.to_be_bytes());
+    cmap_subtable.extend((mapping.len() as u32).to_be_bytes());
+    for (character, glyph) in mapping {
+        cmap_subtable.extend((character as u32).to_be_bytes());
+        cmap_subtable.extend((character as u32).to_be_bytes());
+        cmap_subtable.extend((glyph as u32).to_be_bytes());
+    }
+    let mut cmap = be16(&[0, 1, 3, 10, 0, 12]);
+    cmap.extend(cmap_subtable);
+
+    // A single ligature lookup that replaces `f` `i` with `fi`
+    let gsub = be16(&[
+        1, 0, 10, 10, 12, // header with an empty script and feature list
+        0,  // empty script and feature list
+        1, 4, // lookup list
+        4, 0, 1, 8, // ligature lookup
+        1, 8, 1, 14, // ligature substitution
+        1, 1, F, // coverage
+        1, 4, // ligature set
+        FI, 2, I, // ligature
+    ]);
+
+    table_directory(&[
+        (b"GSUB", gsub),
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ])
+}
+
+struct PointCounter(usize);
+
+impl OutlineBuilder for PointCounter {
+    fn move_to(&mut self, _: f32, _: f32) {
+        self.0 += 1;
+    }
+    fn line_to(&mut self, _: f32, _: f32) {
+        self.0 += 1;
+    }
+    fn quad_to(&mut self, _: f32, _: f32, _: f32, _: f32) {
+        self.0 += 1;
+    }
+    fn curve_to(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32, _: f32) {
+        self.0 += 1;
+    }
+    fn close(&mut self) {}
+}
+
+/// Returns the glyphs in the font that still have an outline
+fn glyphs_with_outlines(font: &[u8]) -> Vec<u16> {
+    let face = Face::parse(font, 0).unwrap();
+    (0..face.number_of_glyphs())
+        .filter(|glyph| {
+            face.outline_glyph(GlyphId(*glyph), &mut PointCounter(0))
+                .is_some()
+        })
+        .collect()
+}
+
+fn mapped_characters(font: &[u8]) -> String {
+    let face = Face::parse(font, 0).unwrap();
+    "ABCfi"
+        .chars()
+        .filter(|character| face.glyph_index(*character).is_some())
+        .collect()
+}
+
+/// Reads the tables out of a WOFF2 font that doesn't transform any tables
+fn decode_woff2(woff2: &[u8]) -> Vec<u8> {
+    let u32_at = |offset: usize| u32::from_be_bytes(woff2[offset..offset + 4].try_into().unwrap());
+    assert_eq!(&woff2[..4], b"wOF2");
+    assert_eq!(u32_at(8) as usize, woff2.len());
+    let num_tables = u16::from_be_bytes([woff2[12], woff2[13]]);
+
+    let mut offset = 48;
+    let mut entries = Vec::new();
+    for _ in 0..num_tables {
+        assert_eq!(woff2[offset] & 0x3F, 0x3F);
+        let tag: [u8; 4] = woff2[offset + 1..offset + 5].try_into().unwrap();
+        offset += 5;
+        let mut length = 0;
+        loop {
+            let byte = woff2[offset];
+            offset += 1;
+            length = (length << 7) | (byte & 0x7F) as usize;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        entries.push((tag, length));
+    }
+
+    let compressed = &woff2[offset..offset + u32_at(20) as usize];
+    let mut data = Vec::new();
+    brotli::BrotliDecompress(&mut &*compressed, &mut data).unwrap();
+    let mut tables = Vec::new();
+    let mut start = 0;
+    for (tag, length) in &entries {
+        tables.push((tag, data[start..start + length].to_vec()));
+        start += length;
+    }
+    assert_eq!(start, data.len());
+    table_directory(&tables)
+}
+
+fn process_font(
+    path: &Path,
+    ty: FontType,
+    text: Option<&str>,
+    font_face: Option<FontFace>,
+) -> (Vec<u8>, FileAsset, tempfile::TempDir) {
+    let output = tempfile::tempdir().unwrap();
+    let mut asset = FileAsset::new(AssetSource::Local(path.to_path_buf())).unwrap();
+    asset.with_options_mut(|options| {
+        if let FileOptions::Font(options) = options {
+            options.set_ty(ty);
+            options.set_text(text.map(String::from));
+            options.set_font_face(font_face);
+        }
+    });
+    process_file(&asset, output.path()).unwrap();
+    let font = std::fs::read(output.path().join(asset.location().unique_name())).unwrap();
+    (font, asset, output)
+}
+
+#[test]
+fn subsets_fonts_to_the_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+    assert_eq!(
+        glyphs_with_outlines(&test_font()),
+        [NOTDEF, A, B, C, F, I, FI]
+    );
+
+    let (font, _, _) = process_font(&path, FontType::TTF, Some("C fi"), None);
+    // The component of `C` and the ligature of `f` `i` are kept even though they are not in the text
+    assert_eq!(glyphs_with_outlines(&font), [NOTDEF, A, C, F, I, FI]);
+    assert_eq!(mapped_characters(&font), "Cfi");
+
+    // The checksum of the whole font must add up to the magic number
+    let checksum = font.chunks(4).fold(0u32, |sum, word| {
+        sum.wrapping_add(u32::from_be_bytes(word.try_into().unwrap()))
+    });
+    assert_eq!(checksum, 0xB1B0AFBA);
+}
+
+#[test]
+fn ligatures_are_removed_without_all_components() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+
+    let (font, _, _) = process_font(&path, FontType::TTF, Some("Bf"), None);
+    assert_eq!(glyphs_with_outlines(&font), [NOTDEF, B, F]);
+    assert_eq!(mapped_characters(&font), "Bf");
+}
+
+#[test]
+fn converts_fonts_to_woff2_with_a_font_face() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+
+    let mut font_face = FontFace::new("Test \"Sans\"");
+    font_face.set_display(Some("swap".to_string()));
+    let (font, asset, output) = process_font(&path, FontType::WOFF2, Some("AB"), Some(font_face));
+    let ttf = decode_woff2(&font);
+    assert_eq!(glyphs_with_outlines(&ttf), [NOTDEF, A, B]);
+    assert_eq!(mapped_characters(&ttf), "AB");
+
+    let unique_name = asset.location().unique_name().to_string();
+    assert!(unique_name.ends_with(".woff2"));
+    let css =
+        std::fs::read_to_string(output.path().join(FontFace::css_file_name(&unique_name))).unwrap();
+    assert_eq!(
+        css,
+        format!(
+            "@font-face {{\n  font-family: \"Test \\\"Sans\\\"\";\n  src: url(\"./{unique_name}\") format(\"woff2\");\n  font-display: swap;\n}}\n"
+        )
+    );
+}
+
+#[test]
+fn font_face_stylesheets_are_added_to_the_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+    let (_, font, _) = process_font(&path, FontType::WOFF, None, Some(FontFace::new("Test")));
+    let stylesheet = font.font_face_stylesheet().unwrap();
+    assert_eq!(
+        stylesheet.location().unique_name(),
+        FontFace::css_file_name(font.location().unique_name())
+    );
+
+    let output = tempfile::tempdir().unwrap();
+    let config = Config::default();
+    let mut manifest = AssetManifest::new(vec![AssetType::File(font.clone())]);
+    manifest
+        .copy_static_assets_to(output.path(), &config)
+        .unwrap();
+    let manifest_path = output.path().join(MANIFEST_FILE_NAME);
+    let written = AssetManifest::read_from(&manifest_path).unwrap();
+    assert_eq!(
+        written.assets(),
+        &[AssetType::File(font), AssetType::File(stylesheet.clone())]
+    );
+    let unique_name = stylesheet.location().unique_name();
+    assert!(output.path().join(unique_name).is_file());
+    assert!(written.integrity(unique_name).is_some());
+
+    // Copying the manifest again writes the stylesheet with the font instead of reading the font as css
+    let mut written = written;
+    written
+        .copy_static_assets_to(output.path(), &config)
+        .unwrap();
+    assert_eq!(written.assets().len(), 2);
+}
+
+#[test]
+fn converts_woff_fonts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+
+    let (woff, _, _) = process_font(&path, FontType::WOFF, None, None);
+    assert_eq!(&woff[..4], b"wOFF");
+    let woff_path = dir.path().join("font.woff");
+    std::fs::write(&woff_path, &woff).unwrap();
+
+    let (ttf, _, _) = process_font(&woff_path, FontType::TTF, Some("i"), None);
+    assert_eq!(glyphs_with_outlines(&ttf), [NOTDEF, I]);
+    assert_eq!(mapped_characters(&ttf), "i");
+}
+
+#[test]
+fn fonts_are_copied_without_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("font.ttf");
+    std::fs::write(&path, test_font()).unwrap();
+
+    let (font, _, _) = process_font(&path, FontType::TTF, None, None);
+    assert_eq!(font, test_font());
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use common::TestServer;
+use manganis_cli_support::{process_file_with_cache, AssetManifestExt, RemoteAssetCache};
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, CssOptions, FileAsset, FileOptions, Integrity,
+    IntegrityAlgorithm, MANIFEST_FILE_NAME,
+};
+
+/// The stylesheet google fonts responds with, linking to fonts on the test server
+fn css2_response(server: &TestServer) -> String {
+    format!(
+        r#"/* latin-ext */
+@font-face {{
+  font-family: 'Roboto';
+  font-style: normal;
+  font-weight: 400;
+  src: url({}) format('woff2');
+  unicode-range: U+0100-02AF;
+}}
+/* latin */
+@font-face {{
+  font-family: 'Roboto';
+  font-style: normal;
+  font-weight: 400;
+  src: url("/s/roboto/v30/latin.woff2") format('woff2');
+  unicode-range: U+0000-00FF;
+}}
+"#,
+        server.url("/s/roboto/v30/latin-ext.woff2")
+    )
+}
+
+fn stylesheet(url: url::Url) -> FileAsset {
+    let mut options = CssOptions::default();
+    options.set_minify(false);
+    options.set_self_host(true);
+    FileAsset::new(AssetSource::Remote(url))
+        .unwrap()
+        .with_options(FileOptions::Css(options))
+}
+
+/// Processes the asset with a download cache that is only used by the test
+fn process(
+    asset: &FileAsset,
+    output: &std::path::Path,
+) -> Result<(), manganis_cli_support::ProcessError> {
+    let cache_dir = tempfile::tempdir().unwrap();
+    process_file_with_cache(asset, output, &RemoteAssetCache::new(cache_dir.path()))
+}
+
+/// The asset a file linked from a self-hosted stylesheet is downloaded as
+fn linked_file(url: url::Url, extension: &str) -> FileAsset {
+    FileAsset::new(AssetSource::Remote(url))
+        .unwrap()
+        .with_options(FileOptions::default_for_extension(Some(extension)))
+}
+
+#[test]
+fn downloads_fonts_linked_from_the_stylesheet() {
+    let server = TestServer::start(vec![
+        ("/s/roboto/v30/latin-ext.woff2", b"latin-ext font".to_vec()),
+        ("/s/roboto/v30/latin.woff2", b"latin font".to_vec()),
+    ]);
+    server.set("/css2?family=Roboto", css2_response(&server).into_bytes());
+    let output = tempfile::tempdir().unwrap();
+
+    let asset = stylesheet(server.url("/css2?family=Roboto"));
+    process(&asset, output.path()).unwrap();
+
+    let css = std::fs::read_to_string(output.path().join(asset.location().unique_name())).unwrap();
+    for (path, contents) in [
+        ("/s/roboto/v30/latin-ext.woff2", "latin-ext font"),
+        ("/s/roboto/v30/latin.woff2", "latin font"),
+    ] {
+        let font = linked_file(server.url(path), "woff2");
+        let unique_name = font.location().unique_name();
+        assert!(unique_name.ends_with(".woff2"));
+        assert!(css.contains(&format!("url(\"./{unique_name}\")")), "{css}");
+        assert_eq!(
+            std::fs::read_to_string(output.path().join(unique_name)).unwrap(),
+            contents
+        );
+    }
+    assert!(!css.contains("127.0.0.1"));
+
+    // Google fonts only serves woff2 fonts to browsers that support them. The fonts don't need to be requested as a browser
+    let user_agent = server.user_agent("/css2?family=Roboto").unwrap();
+    assert!(user_agent.contains("Chrome"));
+    let user_agent = server.user_agent("/s/roboto/v30/latin.woff2");
+    assert!(!user_agent.is_some_and(|user_agent| user_agent.contains("Chrome")));
+}
+
+#[test]
+fn keeps_data_urls() {
+    let server = TestServer::start(vec![
+        (
+            "/style.css",
+            b"a { background: url(data:image/png;base64,AAAA) } b { background: url('/image.svg') }"
+                .to_vec(),
+        ),
+        ("/image.svg", b"<svg></svg>".to_vec()),
+    ]);
+    let output = tempfile::tempdir().unwrap();
+
+    let asset = stylesheet(server.url("/style.css"));
+    process(&asset, output.path()).unwrap();
+
+    let css = std::fs::read_to_string(output.path().join(asset.location().unique_name())).unwrap();
+    let image = linked_file(server.url("/image.svg"), "svg");
+    assert!(css.contains("url(\"data:image/png;base64,AAAA\")"), "{css}");
+    assert!(
+        css.contains(&format!("url(\"./{}\")", image.location().unique_name())),
+        "{css}"
+    );
+}
+
+#[test]
+fn only_links_in_the_css_are_downloaded() {
+    let server = TestServer::start(vec![
+        (
+            "/style.css",
+            br#"/* a { background: url(/comment.svg) } */
+a::before { content: "url(/string.svg)" }
+b { background: url("/image(1).svg") }"#
+                .to_vec(),
+        ),
+        ("/image(1).svg", b"<svg></svg>".to_vec()),
+    ]);
+    let output = tempfile::tempdir().unwrap();
+
+    let asset = stylesheet(server.url("/style.css"));
+    process(&asset, output.path()).unwrap();
+
+    let css = std::fs::read_to_string(output.path().join(asset.location().unique_name())).unwrap();
+    let image = linked_file(server.url("/image(1).svg"), "svg");
+    assert!(
+        css.contains(&format!("url(\"./{}\")", image.location().unique_name())),
+        "{css}"
+    );
+    assert!(css.contains("\"url(/string.svg)\""), "{css}");
+    assert_eq!(server.requests("/comment.svg"), 0);
+    assert_eq!(server.requests("/string.svg"), 0);
+}
+
+#[test]
+fn linked_fonts_are_added_to_the_manifest() {
+    let server = TestServer::start(vec![
+        ("/s/roboto/v30/latin-ext.woff2", b"latin-ext font".to_vec()),
+        ("/s/roboto/v30/latin.woff2", b"latin font".to_vec()),
+    ]);
+    server.set("/css2?family=Roboto", css2_response(&server).into_bytes());
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    let output = tempfile::tempdir().unwrap();
+
+    let stylesheet = stylesheet(server.url("/css2?family=Roboto"));
+    let mut manifest = AssetManifest::new(vec![AssetType::File(stylesheet)]);
+    manifest
+        .copy_static_assets_to_with_cache(output.path(), &Config::default(), &cache)
+        .unwrap();
+
+    assert_eq!(manifest.assets().len(), 3);
+    let file = AssetManifest::read_from(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+    assert_eq!(file, manifest);
+    for (path, contents) in [
+        ("/s/roboto/v30/latin-ext.woff2", "latin-ext font"),
+        ("/s/roboto/v30/latin.woff2", "latin font"),
+    ] {
+        let font = linked_file(server.url(path), "woff2");
+        assert!(manifest.assets().contains(&AssetType::File(font.clone())));
+        assert_eq!(
+            manifest.integrity(font.location().unique_name()),
+            Some(&Integrity::compute(
+                IntegrityAlgorithm::Sha384,
+                contents.as_bytes()
+            ))
+        );
+    }
+}
+
+#[test]
+fn missing_fonts_fail_the_stylesheet() {
+    let server = TestServer::start(vec![]);
+    server.set("/css2?family=Roboto", css2_response(&server).into_bytes());
+    let output = tempfile::tempdir().unwrap();
+
+    let asset = stylesheet(server.url("/css2?family=Roboto"));
+    assert!(process(&asset, output.path()).is_err());
+}
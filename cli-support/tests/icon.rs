@@ -0,0 +1,80 @@
+mod common;
+
+use common::process_asset;
+use manganis_common::{FileOptions, IconOptions, IconPurpose};
+
+/// Processes every icon in the icon set and returns the options and output of each icon
+fn process_icon_set(source: &std::path::Path) -> Vec<(IconOptions, Vec<u8>)> {
+    IconOptions::icon_set()
+        .into_iter()
+        .map(|options| {
+            let output = process_asset(source, FileOptions::Icon(options.clone()));
+            (options, output)
+        })
+        .collect()
+}
+
+/// Reads the size of each image in the directory of an ico file
+fn ico_sizes(ico: &[u8]) -> Vec<u32> {
+    assert_eq!(&ico[..4], [0, 0, 1, 0]);
+    let count = u16::from_le_bytes([ico[4], ico[5]]) as usize;
+    (0..count)
+        .map(|index| match ico[6 + index * 16] {
+            0 => 256,
+            size => size as u32,
+        })
+        .collect()
+}
+
+#[test]
+fn generates_the_icon_set_from_an_svg() {
+    let source = tempfile::tempdir().unwrap();
+    let path = source.path().join("logo.svg");
+    std::fs::write(
+        &path,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><circle cx="5" cy="5" r="5" fill="green"/></svg>"#,
+    )
+    .unwrap();
+
+    let icons = process_icon_set(&path);
+    assert_eq!(icons.len(), 4);
+    for (options, output) in icons {
+        match options.purpose() {
+            IconPurpose::Favicon => {
+                assert_eq!(ico_sizes(&output), IconOptions::FAVICON_SIZES);
+                // The ico decoder reads the largest image
+                let favicon = image::load_from_memory(&output).unwrap();
+                assert_eq!((favicon.width(), favicon.height()), (48, 48));
+            }
+            IconPurpose::AppleTouchIcon | IconPurpose::Icon => {
+                let size = options.sizes()[0];
+                let icon = image::load_from_memory(&output).unwrap().into_rgba8();
+                assert_eq!(icon.dimensions(), (size, size));
+                let center = icon.get_pixel(size / 2, size / 2);
+                assert!(center[1] > 100 && center[3] == 255, "{center:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn icons_that_are_not_square_are_centered() {
+    let source = tempfile::tempdir().unwrap();
+    let path = source.path().join("wide.png");
+    image::RgbaImage::from_pixel(60, 30, image::Rgba([255, 0, 0, 255]))
+        .save(&path)
+        .unwrap();
+
+    let (_, apple_touch_icon) = process_icon_set(&path)
+        .into_iter()
+        .find(|(options, _)| options.purpose() == IconPurpose::AppleTouchIcon)
+        .unwrap();
+    let icon = image::load_from_memory(&apple_touch_icon)
+        .unwrap()
+        .into_rgba8();
+    assert_eq!(icon.dimensions(), (180, 180));
+    // The image fills the width and is padded with transparent rows above and below it
+    assert_eq!(icon.get_pixel(90, 10)[3], 0);
+    assert_eq!(*icon.get_pixel(90, 90), image::Rgba([255, 0, 0, 255]));
+    assert_eq!(icon.get_pixel(90, 170)[3], 0);
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::process_asset;
+use manganis_common::{FileOptions, ImageFit, ImageOptions, ImageType};
+
+const RED: image::Rgba<u8> = image::Rgba([255, 0, 0, 255]);
+const BLUE: image::Rgba<u8> = image::Rgba([0, 0, 255, 255]);
+
+/// Creates a 200x100 png that is red on the left half and blue on the right half
+fn two_color_image(dir: &std::path::Path) -> std::path::PathBuf {
+    let path = dir.join("image.png");
+    image::RgbaImage::from_fn(200, 100, |x, _| if x < 100 { RED } else { BLUE })
+        .save(&path)
+        .unwrap();
+    path
+}
+
+/// Processes the image with the options and returns the output image
+fn process(path: &std::path::Path, options: ImageOptions) -> image::RgbaImage {
+    let output = process_asset(path, FileOptions::Image(options.clone()));
+    let image = image::load_from_memory(&output).unwrap().into_rgba8();
+
+    // The size known at compile time must match the processed image
+    let original = std::fs::read(path).unwrap();
+    assert_eq!(options.output_size(&original), Some(image.dimensions()));
+    image
+}
+
+fn options(size: Option<(u32, u32)>, fit: ImageFit) -> ImageOptions {
+    // Png output is lossless, so the colors can be compared exactly
+    let mut options = ImageOptions::new(ImageType::Png, size);
+    options.set_fit(fit);
+    options
+}
+
+/// Returns whether most of the pixels in the image are the color
+fn mostly(image: &image::RgbaImage, color: image::Rgba<u8>) -> bool {
+    image.pixels().filter(|pixel| **pixel == color).count() * 10 > image.pixels().count() * 9
+}
+
+#[test]
+fn fill_stretches_the_image() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = two_color_image(dir.path());
+
+    let image = process(&path, options(Some((50, 50)), ImageFit::Fill));
+    assert_eq!(image.dimensions(), (50, 50));
+    // Both halves are still visible, but squished
+    assert_eq!(*image.get_pixel(0, 25), RED);
+    assert_eq!(*image.get_pixel(49, 25), BLUE);
+}
+
+#[test]
+fn contain_keeps_the_aspect_ratio() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = two_color_image(dir.path());
+
+    let image = process(&path, options(Some((50, 50)), ImageFit::Contain));
+    assert_eq!(image.dimensions(), (50, 25));
+
+    let image = process(&path, options(Some((400, 400)), ImageFit::Contain));
+    assert_eq!(image.dimensions(), (400, 200));
+}
+
+#[test]
+fn cover_crops_around_the_focal_point() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = two_color_image(dir.path());
+
+    let image = process(&path, options(Some((50, 50)), ImageFit::COVER));
+    assert_eq!(image.dimensions(), (50, 50));
+    assert_eq!(*image.get_pixel(0, 25), RED);
+    assert_eq!(*image.get_pixel(49, 25), BLUE);
+
+    let left = ImageFit::Cover {
+        focal_point: (0, 50),
+    };
+    let image = process(&path, options(Some((50, 50)), left));
+    assert_eq!(image.dimensions(), (50, 50));
+    assert!(mostly(&image, RED));
+
+    let right = ImageFit::Cover {
+        focal_point: (100, 50),
+    };
+    let image = process(&path, options(Some((50, 50)), right));
+    assert_eq!(image.dimensions(), (50, 50));
+    assert!(mostly(&image, BLUE));
+}
+
+#[test]
+fn max_size_never_scales_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = two_color_image(dir.path());
+
+    let mut bounded = options(None, ImageFit::Fill);
+    bounded.set_max_width(Some(100));
+    let image = process(&path, bounded.clone());
+    assert_eq!(image.dimensions(), (100, 50));
+
+    bounded.set_max_width(None);
+    bounded.set_max_height(Some(20));
+    let image = process(&path, bounded.clone());
+    assert_eq!(image.dimensions(), (40, 20));
+
+    // Both bounds apply at the same time
+    bounded.set_max_width(Some(30));
+    let image = process(&path, bounded.clone());
+    assert_eq!(image.dimensions(), (30, 15));
+
+    // Images that are already smaller than the bounds are kept as they are
+    bounded.set_max_width(Some(1000));
+    bounded.set_max_height(Some(1000));
+    let image = process(&path, bounded);
+    assert_eq!(image.dimensions(), (200, 100));
+}
+
+#[test]
+fn max_size_applies_after_the_fit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = two_color_image(dir.path());
+
+    let mut options = options(Some((400, 400)), ImageFit::COVER);
+    options.set_max_width(Some(100));
+    let image = process(&path, options);
+    assert_eq!(image.dimensions(), (100, 100));
+}
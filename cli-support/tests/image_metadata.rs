@@ -0,0 +1,141 @@
+mod common;
+
+use common::process_contents;
+use std::io::{Cursor, Write};
+
+use image::{ImageDecoder, ImageReader};
+use manganis_common::{FileOptions, ImageOptions, ImageType};
+
+/// EXIF data with the orientation tag and a GPS directory pointer
+fn exif(orientation: u16) -> Vec<u8> {
+    let mut exif = b"II*\0".to_vec();
+    exif.extend(8u32.to_le_bytes());
+    exif.extend(2u16.to_le_bytes());
+    // The orientation is a single short
+    exif.extend(0x0112u16.to_le_bytes());
+    exif.extend(3u16.to_le_bytes());
+    exif.extend(1u32.to_le_bytes());
+    exif.extend(orientation.to_le_bytes());
+    exif.extend([0, 0]);
+    // The GPS directory is never read, so it can point anywhere
+    exif.extend(0x8825u16.to_le_bytes());
+    exif.extend(4u16.to_le_bytes());
+    exif.extend(1u32.to_le_bytes());
+    exif.extend(0u32.to_le_bytes());
+    exif.extend([0, 0, 0, 0]);
+    exif
+}
+
+/// Inserts a chunk after the header of a png
+fn insert_png_chunk(png: &mut Vec<u8>, ty: &[u8; 4], data: &[u8]) {
+    let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+    chunk.extend(ty);
+    chunk.extend(data);
+    chunk.extend(crc32fast::hash(&chunk[4..]).to_be_bytes());
+    png.splice(33..33, chunk);
+}
+
+/// Creates a 4x2 png where every pixel has a different color
+fn image() -> (Vec<u8>, image::RgbaImage) {
+    let image = image::RgbaImage::from_fn(4, 2, |x, y| {
+        image::Rgba([x as u8 * 60, y as u8 * 200, 0, 255])
+    });
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    (png, image)
+}
+
+/// Processes the image and returns the encoded bytes
+fn process(source: &[u8], extension: &str, options: ImageOptions) -> Vec<u8> {
+    process_contents(
+        &format!("image.{extension}"),
+        source,
+        FileOptions::Image(options),
+    )
+}
+
+fn icc_profile(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut decoder = ImageReader::new(Cursor::new(encoded))
+        .with_guessed_format()
+        .unwrap()
+        .into_decoder()
+        .unwrap();
+    decoder.icc_profile().unwrap()
+}
+
+#[test]
+fn images_are_rotated_upright_before_they_are_resized() {
+    let (mut png, original) = image();
+    // Rotate 90 degrees clockwise
+    insert_png_chunk(&mut png, b"eXIf", &exif(6));
+
+    let mut options = ImageOptions::new(ImageType::Png, None);
+    options.set_lossless(true);
+    let rotated = process(&png, "png", options);
+    let rotated = image::load_from_memory(&rotated).unwrap().into_rgba8();
+    assert_eq!(rotated.dimensions(), (2, 4));
+    assert_eq!(rotated, image::imageops::rotate90(&original));
+
+    // The width of the upright image is resized
+    let mut options = ImageOptions::new(ImageType::Png, None);
+    options.set_width(Some(1));
+    let resized = process(&png, "png", options);
+    let resized = image::load_from_memory(&resized).unwrap();
+    assert_eq!((resized.width(), resized.height()), (1, 2));
+}
+
+#[test]
+fn metadata_is_removed() {
+    let (png, _) = image();
+    let jpg = process(&png, "png", ImageOptions::new(ImageType::Jpg, None));
+    // Add an EXIF segment with a GPS location after the start of the jpg
+    let exif = exif(1);
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend(((exif.len() + 8) as u16).to_be_bytes());
+    segment.extend(b"Exif\0\0");
+    segment.extend(&exif);
+    let mut photo = jpg.clone();
+    photo.splice(2..2, segment);
+
+    // Photos from cameras are usually saved with an uppercase extension
+    for extension in ["jpg", "JPG"] {
+        let options = FileOptions::default_for_extension(Some(extension));
+        let FileOptions::Image(options) = options else {
+            panic!("{extension} files should be processed as images");
+        };
+        let processed = process(&photo, extension, options);
+        assert!(
+            !processed.windows(4).any(|window| window == b"Exif"),
+            "The EXIF data of the {extension} file should be removed"
+        );
+    }
+}
+
+#[test]
+fn icc_profiles_are_only_kept_when_requested() {
+    let profile = b"not a real color profile, but decoders don't check".repeat(4);
+    let (mut png, _) = image();
+    let mut compressed = b"ICC Profile\0\0".to_vec();
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&profile).unwrap();
+    compressed.extend(encoder.finish().unwrap());
+    insert_png_chunk(&mut png, b"iCCP", &compressed);
+
+    for ty in [ImageType::Png, ImageType::Jpg, ImageType::Webp] {
+        for lossless in [false, true] {
+            let mut options = ImageOptions::new(ty, None);
+            options.set_lossless(lossless);
+            let stripped = process(&png, "png", options.clone());
+            assert_eq!(icc_profile(&stripped), None, "{ty:?}");
+
+            options.set_preserve_icc_profile(true);
+            let preserved = process(&png, "png", options);
+            assert_eq!(icc_profile(&preserved).as_ref(), Some(&profile), "{ty:?}");
+            // The image can still be decoded with the profile
+            let decoded = image::load_from_memory(&preserved).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (4, 2), "{ty:?}");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::process_asset;
+use manganis_common::{AssetSource, FileAsset, FileOptions, ImageOptions, ImageType};
+
+/// Creates a 64x64 png with a gradient and some noise so the encoders have detail to throw away
+fn detailed_image(dir: &std::path::Path) -> (std::path::PathBuf, image::RgbaImage) {
+    let path = dir.join("image.png");
+    let image = image::RgbaImage::from_fn(64, 64, |x, y| {
+        let noise = (x * 7919 + y * 104729) % 16;
+        image::Rgba([(x * 4) as u8, (y * 4) as u8, (noise * 4) as u8, 255])
+    });
+    image.save(&path).unwrap();
+    (path, image)
+}
+
+fn options(ty: ImageType, configure: impl FnOnce(&mut ImageOptions)) -> ImageOptions {
+    let mut options = ImageOptions::new(ty, None);
+    configure(&mut options);
+    options
+}
+
+/// Processes the image with the options and returns the encoded bytes
+fn process(path: &std::path::Path, options: ImageOptions) -> Vec<u8> {
+    process_asset(path, FileOptions::Image(options))
+}
+
+#[test]
+fn lower_quality_makes_smaller_images() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, _) = detailed_image(dir.path());
+
+    for ty in [
+        ImageType::Jpg,
+        ImageType::Png,
+        ImageType::Webp,
+        ImageType::Avif,
+    ] {
+        let low = process(&path, options(ty, |options| options.set_quality(Some(10))));
+        let high = process(&path, options(ty, |options| options.set_quality(Some(95))));
+        assert!(
+            low.len() < high.len(),
+            "{ty:?} at quality 10 should be smaller than at quality 95 ({} >= {})",
+            low.len(),
+            high.len()
+        );
+    }
+}
+
+#[test]
+fn lossless_images_keep_every_pixel() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, original) = detailed_image(dir.path());
+
+    for ty in [ImageType::Png, ImageType::Webp] {
+        let lossless = process(&path, options(ty, |options| options.set_lossless(true)));
+        let decoded = image::load_from_memory(&lossless).unwrap().into_rgba8();
+        assert_eq!(decoded, original, "{ty:?} should be lossless");
+    }
+
+    // Without lossless, png images are quantized to a palette
+    let quantized = process(&path, options(ImageType::Png, |_| {}));
+    let decoded = image::load_from_memory(&quantized).unwrap().into_rgba8();
+    assert_ne!(decoded, original);
+}
+
+#[test]
+fn effort_is_honored_by_every_encoder() {
+    let dir = tempfile::tempdir().unwrap();
+    let (path, _) = detailed_image(dir.path());
+
+    for ty in [
+        ImageType::Jpg,
+        ImageType::Png,
+        ImageType::Webp,
+        ImageType::Avif,
+    ] {
+        for effort in [1, 10] {
+            let encoded = process(
+                &path,
+                options(ty, |options| options.set_effort(Some(effort))),
+            );
+            if ty == ImageType::Avif {
+                // The image crate can't decode avif images, so only check the header
+                assert_eq!(&encoded[4..12], b"ftypavif");
+            } else {
+                let decoded = image::load_from_memory(&encoded).unwrap();
+                assert_eq!((decoded.width(), decoded.height()), (64, 64));
+            }
+        }
+    }
+}
+
+#[test]
+fn encoder_options_change_the_unique_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("image.png");
+    std::fs::write(&path, b"not really a png").unwrap();
+    let unique_name = |options: ImageOptions| {
+        FileAsset::new(AssetSource::Local(path.clone()))
+            .unwrap()
+            .with_options(FileOptions::Image(options))
+            .location()
+            .unique_name()
+            .to_string()
+    };
+
+    let mut names = vec![
+        unique_name(options(ImageType::Webp, |_| {})),
+        unique_name(options(ImageType::Webp, |options| {
+            options.set_quality(Some(50))
+        })),
+        unique_name(options(ImageType::Webp, |options| {
+            options.set_quality(Some(90))
+        })),
+        unique_name(options(ImageType::Webp, |options| {
+            options.set_lossless(true)
+        })),
+        unique_name(options(ImageType::Webp, |options| {
+            options.set_effort(Some(9))
+        })),
+    ];
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), 5);
+}
@@ -0,0 +1,53 @@
+use manganis_cli_support::AssetManifestExt;
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, FileAsset, FileOptions, Integrity,
+    IntegrityAlgorithm, MANIFEST_FILE_NAME,
+};
+
+fn css_asset(dir: &std::path::Path) -> FileAsset {
+    let path = dir.join("style.css");
+    std::fs::write(&path, "body { color: red; }").unwrap();
+    let mut asset = FileAsset::new(AssetSource::Local(path)).unwrap();
+    asset.with_options_mut(|options| {
+        if let FileOptions::Css(options) = options {
+            options.set_preload(true);
+        }
+    });
+    asset
+}
+
+#[test]
+fn computes_the_integrity_of_processed_files() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let asset = css_asset(source.path());
+    let unique_name = asset.location().unique_name().to_string();
+    let mut manifest = AssetManifest::new(vec![AssetType::File(asset)]);
+
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    let processed = std::fs::read(output.path().join(&unique_name)).unwrap();
+    let integrity = Integrity::compute(IntegrityAlgorithm::Sha384, &processed);
+    assert_eq!(manifest.integrity(&unique_name), Some(&integrity));
+
+    // The manifest file is written with the integrity
+    let file = AssetManifest::read_from(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+    assert_eq!(file.integrity(&unique_name), Some(&integrity));
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn head_includes_integrity_attributes() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let mut manifest = AssetManifest::new(vec![AssetType::File(css_asset(source.path()))]);
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    let head = manifest.head(&Config::default());
+    assert!(head.contains("integrity=\"sha384-"), "{head}");
+    assert!(head.contains("crossorigin=\"anonymous\""), "{head}");
+}
@@ -0,0 +1,106 @@
+use manganis_cli_support::AssetManifestExt;
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, FileAsset, ManifestError, ManifestFile,
+    MANIFEST_FILE_NAME,
+};
+
+fn manifest(dir: &std::path::Path) -> AssetManifest {
+    let css = dir.join("style.css");
+    std::fs::write(&css, "body { color: red; }").unwrap();
+    let js = dir.join("script.js");
+    std::fs::write(&js, "console.log('hello')").unwrap();
+    AssetManifest::new(vec![
+        AssetType::File(FileAsset::new(AssetSource::Local(css)).unwrap()),
+        AssetType::File(FileAsset::new(AssetSource::Local(js)).unwrap()),
+    ])
+}
+
+#[test]
+fn manifest_file_round_trips() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let mut manifest = manifest(source.path());
+
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+    let path = output.path().join(MANIFEST_FILE_NAME);
+    assert_eq!(AssetManifest::read_from(&path).unwrap(), manifest);
+}
+
+#[test]
+fn manifest_file_records_processed_assets() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let mut manifest = manifest(source.path());
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    let json = std::fs::read_to_string(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+    let file = ManifestFile::parse(&json).unwrap();
+    assert_eq!(file.assets().len(), 2);
+    for entry in file.assets() {
+        let AssetType::File(asset) = entry.asset() else {
+            panic!("expected a file asset");
+        };
+        let processed = output.path().join(asset.location().unique_name());
+        assert_eq!(
+            entry.output_size(),
+            Some(processed.metadata().unwrap().len())
+        );
+        assert!(entry.served_location().is_some());
+        assert!(entry.mime_type().is_some());
+    }
+}
+
+#[test]
+fn builds_with_different_configs_record_their_own_served_locations() {
+    let source = tempfile::tempdir().unwrap();
+    let manifest = manifest(source.path());
+
+    // Each build collects the same assets into its own folder at the same time
+    let builds: Vec<_> = ["/first/", "/second/"]
+        .into_iter()
+        .map(|serve_location| {
+            let config = Config::default().with_assets_serve_location(serve_location);
+            (tempfile::tempdir().unwrap(), config)
+        })
+        .collect();
+    std::thread::scope(|scope| {
+        for (output, config) in &builds {
+            let mut manifest = manifest.clone();
+            scope.spawn(move || {
+                manifest
+                    .copy_static_assets_to(output.path(), config)
+                    .unwrap()
+            });
+        }
+    });
+
+    for (output, config) in &builds {
+        let json = std::fs::read_to_string(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+        let file = ManifestFile::parse(&json).unwrap();
+        for entry in file.assets() {
+            let AssetType::File(asset) = entry.asset() else {
+                panic!("expected a file asset");
+            };
+            let expected = format!(
+                "{}{}",
+                config.assets_serve_location(),
+                asset.location().unique_name()
+            );
+            assert_eq!(entry.served_location(), Some(expected.as_str()));
+        }
+    }
+}
+
+#[test]
+fn other_manifest_versions_are_rejected() {
+    let error = ManifestFile::parse(r#"{ "version": 0, "assets": [] }"#).unwrap_err();
+    assert!(matches!(error, ManifestError::UnsupportedVersion(0)));
+    assert!(
+        error.to_string().contains("Collect the assets again"),
+        "{error}"
+    );
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::file_asset;
+use manganis_cli_support::{process_file, AssetManifestExt, ProcessError, ProcessStage};
+use manganis_common::{
+    AssetManifest, AssetType, Config, FileAsset, FileOptions, ImageOptions, ImageType,
+};
+
+fn image_asset(path: &std::path::Path, ty: ImageType) -> FileAsset {
+    file_asset(path, FileOptions::Image(ImageOptions::new(ty, None)))
+}
+
+#[test]
+fn undecodable_images_fail_to_decode() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let path = source.path().join("broken.png");
+    std::fs::write(&path, b"this is not an image").unwrap();
+
+    for ty in [
+        ImageType::Png,
+        ImageType::Jpg,
+        ImageType::Avif,
+        ImageType::Webp,
+    ] {
+        let asset = image_asset(&path, ty);
+        let error = process_file(&asset, output.path()).unwrap_err();
+        assert_eq!(error.stage(), ProcessStage::Decode);
+        assert_eq!(error.asset(), &asset);
+        assert!(error.to_string().contains("broken"), "{error}");
+        // Nothing is written for the manifest to point at
+        assert!(!output.path().join(asset.location().unique_name()).exists());
+    }
+}
+
+#[test]
+fn missing_files_fail_to_read() {
+    let output = tempfile::tempdir().unwrap();
+    // The source is checked when the asset is created, so delete it afterwards
+    let source = tempfile::tempdir().unwrap();
+    let path = source.path().join("missing.png");
+    image::RgbaImage::new(1, 1).save(&path).unwrap();
+    let asset = image_asset(&path, ImageType::Png);
+    std::fs::remove_file(&path).unwrap();
+
+    let error = process_file(&asset, output.path()).unwrap_err();
+    assert_eq!(error.stage(), ProcessStage::Read);
+}
+
+#[test]
+fn copying_assets_reports_the_asset_and_stage_that_failed() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let valid = source.path().join("valid.png");
+    image::RgbaImage::new(4, 4).save(&valid).unwrap();
+    let broken = source.path().join("broken.png");
+    std::fs::write(&broken, b"this is not an image").unwrap();
+
+    let broken = image_asset(&broken, ImageType::Webp);
+    let mut manifest = AssetManifest::new(vec![
+        AssetType::File(image_asset(&valid, ImageType::Webp)),
+        AssetType::File(broken.clone()),
+    ]);
+    let error = manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap_err();
+    let error = error.downcast_ref::<ProcessError>().unwrap();
+    assert_eq!(error.asset(), &broken);
+    assert_eq!(error.stage(), ProcessStage::Decode);
+    let message = error.to_string();
+    assert!(message.starts_with("failed to decode"), "{message}");
+    assert!(message.contains("broken"), "{message}");
+}
@@ -0,0 +1,291 @@
+mod common;
+
+use common::TestServer;
+use manganis_cli_support::{
+    process_file_with_cache, AssetManifest, AssetManifestExt, AssetSource, AssetType, Config,
+    FileAsset, Integrity, IntegrityAlgorithm, RemoteAssetCache, LOCKFILE_NAME,
+};
+
+#[test]
+fn downloads_are_cached() {
+    let server = TestServer::start(vec![("/style.css", b"body { color: red; }".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    let url = server.url("/style.css");
+
+    assert_eq!(cache.fetch(&url).unwrap(), b"body { color: red; }");
+    assert!(cache.cache_path(&url).is_file());
+
+    // The second fetch is served from the cache
+    assert_eq!(cache.fetch(&url).unwrap(), b"body { color: red; }");
+    assert_eq!(server.requests("/style.css"), 1);
+}
+
+#[test]
+fn offline_mode_only_reads_the_cache() {
+    let server = TestServer::start(vec![
+        ("/cached.txt", b"cached".to_vec()),
+        ("/uncached.txt", b"uncached".to_vec()),
+    ]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cached = server.url("/cached.txt");
+    let uncached = server.url("/uncached.txt");
+
+    RemoteAssetCache::new(cache_dir.path())
+        .fetch(&cached)
+        .unwrap();
+
+    let offline = RemoteAssetCache::new(cache_dir.path()).with_offline(true);
+    assert_eq!(offline.fetch(&cached).unwrap(), b"cached");
+    assert!(offline.fetch(&uncached).is_err());
+    assert_eq!(server.requests("/cached.txt"), 1);
+    assert_eq!(server.requests("/uncached.txt"), 0);
+}
+
+#[test]
+fn failed_downloads_are_not_cached() {
+    let server = TestServer::start(vec![]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    let url = server.url("/missing.png");
+
+    assert!(cache.fetch(&url).is_err());
+    assert!(!cache.cache_path(&url).exists());
+}
+
+#[test]
+fn pinned_downloads_must_match() {
+    let server = TestServer::start(vec![("/script.js", b"console.log(1)".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    let url = server.url("/script.js");
+
+    let expected = Integrity::compute(IntegrityAlgorithm::Sha256, b"console.log(1)");
+    assert_eq!(
+        cache.fetch_verified(&url, Some(&expected)).unwrap(),
+        b"console.log(1)"
+    );
+
+    // If the contents change, the error includes the new digest so it can be pinned
+    server.set("/script.js", b"console.log(2)".to_vec());
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    let actual = Integrity::compute(IntegrityAlgorithm::Sha256, b"console.log(2)");
+    let error = cache
+        .fetch_verified(&url, Some(&expected))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&actual.to_string()), "{error}");
+}
+
+#[test]
+fn unpinned_downloads_are_recorded_in_the_lockfile() {
+    let server = TestServer::start(vec![("/data.json", b"{}".to_vec())]);
+    let lock_dir = tempfile::tempdir().unwrap();
+    let lockfile = lock_dir.path().join("integrity.lock");
+    let url = server.url("/data.json");
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+    cache.fetch_verified(&url, None).unwrap();
+    let recorded = std::fs::read_to_string(&lockfile).unwrap();
+    let first_seen = Integrity::compute(IntegrityAlgorithm::Sha256, b"{}");
+    assert!(recorded.contains(&first_seen.to_string()), "{recorded}");
+
+    // Downloading the same contents again is fine
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+    cache.fetch_verified(&url, None).unwrap();
+
+    // But changed contents are detected
+    server.set("/data.json", b"[]".to_vec());
+    let cache_dir = tempfile::tempdir().unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+    assert!(cache.fetch_verified(&url, None).is_err());
+}
+
+#[test]
+fn unpinned_cached_assets_are_downloaded_again() {
+    let server = TestServer::start(vec![("/data.json", b"{}".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let lock_dir = tempfile::tempdir().unwrap();
+    let lockfile = lock_dir.path().join("integrity.lock");
+    let url = server.url("/data.json");
+
+    // Each cache only downloads the url again once
+    let revalidating = |cache_dir: &std::path::Path| {
+        RemoteAssetCache::new(cache_dir)
+            .with_lockfile(&lockfile)
+            .with_revalidate(true)
+    };
+    let cache = revalidating(cache_dir.path());
+    cache.fetch_verified(&url, None).unwrap();
+    cache.fetch_verified(&url, None).unwrap();
+    assert_eq!(server.requests("/data.json"), 1);
+
+    // A later build notices the change even though the old contents are cached
+    server.set("/data.json", b"[]".to_vec());
+    let cache = revalidating(cache_dir.path());
+    let error = cache.fetch_verified(&url, None).unwrap_err().to_string();
+    assert!(
+        error.contains("changed since it was first downloaded"),
+        "{error}"
+    );
+    assert_eq!(std::fs::read(cache.cache_path(&url)).unwrap(), b"{}");
+
+    // Offline builds can only compare the cached contents
+    let offline = RemoteAssetCache::new(cache_dir.path())
+        .with_lockfile(&lockfile)
+        .with_offline(true);
+    assert_eq!(offline.fetch_verified(&url, None).unwrap(), b"{}");
+    assert_eq!(server.requests("/data.json"), 2);
+}
+
+#[test]
+fn unpinned_cached_assets_are_only_downloaded_again_when_revalidating() {
+    let server = TestServer::start(vec![("/data.json", b"{}".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let lock_dir = tempfile::tempdir().unwrap();
+    let lockfile = lock_dir.path().join("integrity.lock");
+    let url = server.url("/data.json");
+
+    // Caches that don't revalidate keep using the persistent download cache
+    for _ in 0..2 {
+        let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+        assert_eq!(cache.fetch_verified(&url, None).unwrap(), b"{}");
+    }
+    assert_eq!(server.requests("/data.json"), 1);
+
+    // If the url can't be downloaded again, the cached copy is used because it still matches the lockfile
+    server.remove("/data.json");
+    let cache = RemoteAssetCache::new(cache_dir.path())
+        .with_lockfile(&lockfile)
+        .with_revalidate(true);
+    assert_eq!(cache.fetch_verified(&url, None).unwrap(), b"{}");
+    assert_eq!(server.requests("/data.json"), 2);
+
+    // But a cached copy that doesn't match the lockfile is still rejected
+    std::fs::write(cache.cache_path(&url), b"[]").unwrap();
+    let cache = RemoteAssetCache::new(cache_dir.path())
+        .with_lockfile(&lockfile)
+        .with_revalidate(true);
+    assert!(cache.fetch_verified(&url, None).is_err());
+}
+
+#[test]
+fn unreadable_lockfiles_are_not_overwritten() {
+    let server = TestServer::start(vec![("/data.json", b"{}".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let lock_dir = tempfile::tempdir().unwrap();
+    // A directory can't be read as a lockfile
+    let lockfile = lock_dir.path().join("integrity.lock");
+    std::fs::create_dir(&lockfile).unwrap();
+
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+    assert!(cache
+        .fetch_verified(&server.url("/data.json"), None)
+        .is_err());
+    assert!(lockfile.is_dir());
+}
+
+#[test]
+fn copying_assets_records_unpinned_urls_next_to_the_manifest() {
+    let server = TestServer::start(vec![("/data.json", br#"{ "a": 1 }"#.to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let url = server.url("/data.json");
+
+    let mut manifest = AssetManifest::new(vec![AssetType::File(
+        FileAsset::new(AssetSource::Remote(url.clone())).unwrap(),
+    )]);
+    manifest
+        .copy_static_assets_to_with_cache(
+            output.path(),
+            &Config::default(),
+            &RemoteAssetCache::new(cache_dir.path()),
+        )
+        .unwrap();
+
+    let recorded = std::fs::read_to_string(output.path().join(LOCKFILE_NAME)).unwrap();
+    assert!(recorded.contains(url.as_str()), "{recorded}");
+}
+
+#[test]
+fn processing_uses_the_cache_and_lockfile_it_is_given() {
+    let server = TestServer::start(vec![("/data.json", br#"{ "a": 1 }"#.to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let lockfile = project.path().join("assets.lock");
+    let url = server.url("/data.json");
+
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(&lockfile);
+    let file = FileAsset::new(AssetSource::Remote(url.clone())).unwrap();
+    process_file_with_cache(&file, output.path(), &cache).unwrap();
+
+    assert!(cache.cache_path(&url).is_file());
+    let recorded = std::fs::read_to_string(&lockfile).unwrap();
+    assert!(recorded.contains(url.as_str()), "{recorded}");
+    assert!(output.path().join(file.location().unique_name()).is_file());
+}
+
+#[test]
+fn concurrent_builds_record_every_url_in_the_lockfile() {
+    let paths: Vec<_> = (0..8).map(|i| format!("/{i}.json")).collect();
+    let server = TestServer::start(
+        paths
+            .iter()
+            .map(|path| (path.as_str(), path.as_bytes().to_vec()))
+            .collect(),
+    );
+    let lock_dir = tempfile::tempdir().unwrap();
+    let lockfile = lock_dir.path().join("integrity.lock");
+
+    // Each build has its own cache, but they all share the lockfile
+    std::thread::scope(|s| {
+        for path in &paths {
+            let (server, lockfile) = (&server, &lockfile);
+            s.spawn(move || {
+                let cache_dir = tempfile::tempdir().unwrap();
+                let cache = RemoteAssetCache::new(cache_dir.path()).with_lockfile(lockfile);
+                cache.fetch_verified(&server.url(path), None).unwrap();
+            });
+        }
+    });
+
+    let recorded = std::fs::read_to_string(&lockfile).unwrap();
+    for path in &paths {
+        assert!(recorded.contains(server.url(path).as_str()), "{recorded}");
+    }
+    // The lockfile is written through a temporary file that is moved into place
+    let leftovers: Vec<_> = std::fs::read_dir(lock_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+}
+
+#[test]
+fn processing_checks_the_cached_source_against_the_integrity() {
+    let server = TestServer::start(vec![("/script.js", b"console.log(1)".to_vec())]);
+    let cache_dir = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let url = server.url("/script.js");
+    let integrity = Integrity::compute(IntegrityAlgorithm::Sha256, b"console.log(1)");
+    let mut file = FileAsset::new(AssetSource::Remote(url.clone())).unwrap();
+    file.set_integrity(Some(integrity));
+
+    // A tampered cache entry can't be replaced while offline, so processing fails instead of using it
+    let cache = RemoteAssetCache::new(cache_dir.path()).with_offline(true);
+    std::fs::create_dir_all(cache.directory()).unwrap();
+    std::fs::write(cache.cache_path(&url), b"console.log(2)").unwrap();
+    assert!(process_file_with_cache(&file, output.path(), &cache).is_err());
+    assert!(!output.path().join(file.location().unique_name()).exists());
+
+    // Online builds download the source again
+    let cache = RemoteAssetCache::new(cache_dir.path());
+    process_file_with_cache(&file, output.path(), &cache).unwrap();
+    let processed = std::fs::read(output.path().join(file.location().unique_name())).unwrap();
+    assert!(!String::from_utf8_lossy(&processed).contains('2'));
+}
@@ -0,0 +1,122 @@
+use manganis_cli_support::AssetManifestExt;
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, FileAsset, FileOptions, ImageOptions, ImageType,
+    ManifestFile, MANIFEST_FILE_NAME,
+};
+
+fn image_with_width(path: &std::path::Path, width: Option<u32>) -> FileAsset {
+    let mut options = ImageOptions::new(ImageType::Png, None);
+    options.set_width(width);
+    FileAsset::new(AssetSource::Local(path.to_path_buf()))
+        .unwrap()
+        .with_options(FileOptions::Image(options))
+}
+
+#[test]
+fn widths_are_processed_into_separate_images() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let path = source.path().join("hero.png");
+    image::RgbaImage::from_pixel(100, 50, image::Rgba([255, 0, 0, 255]))
+        .save(&path)
+        .unwrap();
+
+    let original = image_with_width(&path, None);
+    let small = image_with_width(&path, Some(40));
+    let large = image_with_width(&path, Some(80));
+    // Images are never scaled up past their original width
+    let too_large = image_with_width(&path, Some(200));
+    let mut manifest = AssetManifest::new(
+        [&original, &small, &large, &too_large]
+            .into_iter()
+            .map(|file| AssetType::File(file.clone()))
+            .collect(),
+    );
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    for (file, size) in [
+        (&original, (100, 50)),
+        (&small, (40, 20)),
+        (&large, (80, 40)),
+        (&too_large, (100, 50)),
+    ] {
+        let processed = output.path().join(file.location().unique_name());
+        assert_eq!(image::image_dimensions(processed).unwrap(), size);
+    }
+
+    // Every width is recorded in the manifest with its own location
+    let json = std::fs::read_to_string(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+    let file = ManifestFile::parse(&json).unwrap();
+    assert_eq!(file.assets().len(), 4);
+    let mut unique_names: Vec<_> = [&original, &small, &large, &too_large]
+        .iter()
+        .map(|file| file.location().unique_name())
+        .collect();
+    unique_names.sort_unstable();
+    unique_names.dedup();
+    assert_eq!(unique_names.len(), 4);
+}
+
+#[test]
+fn duplicate_assets_are_processed_once() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let path = source.path().join("hero.png");
+    image::RgbaImage::from_pixel(64, 64, image::Rgba([0, 0, 255, 255]))
+        .save(&path)
+        .unwrap();
+
+    let small = image_with_width(&path, Some(32));
+    let mut manifest = AssetManifest::new(vec![
+        AssetType::File(small.clone()),
+        AssetType::File(small.clone()),
+    ]);
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    let processed = output.path().join(small.location().unique_name());
+    assert_eq!(image::image_dimensions(processed).unwrap(), (32, 32));
+    // The manifest still lists every collected asset
+    let json = std::fs::read_to_string(output.path().join(MANIFEST_FILE_NAME)).unwrap();
+    assert_eq!(ManifestFile::parse(&json).unwrap().assets().len(), 2);
+}
+
+#[test]
+fn densities_are_processed_at_scaled_sizes() {
+    let source = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let path = source.path().join("logo.png");
+    image::RgbaImage::from_pixel(64, 32, image::Rgba([0, 255, 0, 255]))
+        .save(&path)
+        .unwrap();
+
+    // The size is the 1x size and every density multiplies it
+    let files: Vec<(FileAsset, (u32, u32))> = [1, 2, 3]
+        .into_iter()
+        .map(|density| {
+            let mut options = ImageOptions::new(ImageType::Png, Some((16, 8)));
+            options.scale(density);
+            let file = FileAsset::new(AssetSource::Local(path.clone()))
+                .unwrap()
+                .with_options(FileOptions::Image(options));
+            (file, (16 * density, 8 * density))
+        })
+        .collect();
+    let mut manifest = AssetManifest::new(
+        files
+            .iter()
+            .map(|(file, _)| AssetType::File(file.clone()))
+            .collect(),
+    );
+    manifest
+        .copy_static_assets_to(output.path(), &Config::default())
+        .unwrap();
+
+    for (file, size) in &files {
+        let processed = output.path().join(file.location().unique_name());
+        assert_eq!(image::image_dimensions(processed).unwrap(), *size);
+    }
+}
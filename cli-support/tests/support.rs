@@ -0,0 +1,44 @@
+use manganis_cli_support::{delete_linker_script, linker_intercept_command, SUPPORT_ENV_VAR};
+use std::ffi::OsStr;
+
+#[test]
+fn support_is_only_passed_to_the_command() {
+    let (command, script) =
+        linker_intercept_command("link", ["--release"], None::<Vec<String>>).unwrap();
+    delete_linker_script(&script).unwrap();
+
+    let support = command
+        .get_envs()
+        .find(|(key, _)| *key == SUPPORT_ENV_VAR)
+        .and_then(|(_, value)| value);
+    assert_eq!(support, Some(OsStr::new("true")));
+    assert!(std::env::var(SUPPORT_ENV_VAR).is_err());
+}
+
+#[test]
+fn concurrent_builds_get_their_own_linker_script() {
+    let scripts: Vec<_> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                s.spawn(|| {
+                    linker_intercept_command("link", ["--release"], None::<Vec<String>>)
+                        .unwrap()
+                        .1
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    for (i, script) in scripts.iter().enumerate() {
+        assert!(scripts[i + 1..].iter().all(|other| other != script));
+    }
+
+    // Deleting one build's script leaves the others in place
+    delete_linker_script(&scripts[0]).unwrap();
+    assert!(!scripts[0].exists());
+    for script in &scripts[1..] {
+        assert!(script.exists());
+        delete_linker_script(script).unwrap();
+    }
+}
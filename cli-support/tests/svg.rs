@@ -0,0 +1,185 @@
+mod common;
+
+use common::process_contents;
+use manganis_common::{FileOptions, ImageFit, ImageOptions, ImageType, SvgOptions};
+
+const INKSCAPE_SVG: &str = r##"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!-- Created with Inkscape (http://www.inkscape.org/) -->
+<svg
+   xmlns="http://www.w3.org/2000/svg"
+   xmlns:xlink="http://www.w3.org/1999/xlink"
+   xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape"
+   xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd"
+   xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+   width="24.000000"
+   height="24.000000"
+   viewBox="0 0 24.000000 24.000000"
+   sodipodi:docname="icon.svg"
+   inkscape:version="1.3">
+  <metadata>
+    <rdf:RDF />
+  </metadata>
+  <sodipodi:namedview pagecolor="#ffffff" />
+  <defs>
+    <style>
+      .label { fill : currentColor ; }
+    </style>
+  </defs>
+  <g>
+    <g inkscape:label="Layer 1">
+      <path
+         d="M 12.123456,2.000000 L 22.000000,20.000000 L 2.000000,20.000000 Z"
+         fill="currentColor" />
+      <path d="m 1.0004,1.0004 l 1.0004,0 l 1.0004,0 l 1.0004,0" />
+    </g>
+    <g transform="translate(0.500000, 1.250000)">
+      <use xlink:href="#dot" />
+    </g>
+    <g />
+  </g>
+  <text x="2" y="22" class="label">Hello   world</text>
+</svg>
+"##;
+
+fn process_svg(svg: &str, options: SvgOptions) -> String {
+    String::from_utf8(process_contents("icon.svg", svg, FileOptions::Svg(options))).unwrap()
+}
+
+#[test]
+fn editor_data_is_removed() {
+    let minified = process_svg(INKSCAPE_SVG, SvgOptions::new());
+    for removed in [
+        "<?xml", "<!--", "metadata", "rdf", "inkscape", "sodipodi", "<g>", "<g/>",
+    ] {
+        assert!(!minified.contains(removed), "{removed} in {minified}");
+    }
+    assert!(minified.starts_with(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="24" height="24" viewBox="0 0 24 24">"#
+    ), "{minified}");
+    assert!(minified.len() < INKSCAPE_SVG.len() / 2, "{minified}");
+
+    // Everything that is drawn is kept
+    assert!(minified.contains(r#"fill="currentColor""#), "{minified}");
+    assert!(
+        minified.contains(r##"<use xlink:href="#dot"/>"##),
+        "{minified}"
+    );
+    assert!(minified.contains(">Hello   world</text>"), "{minified}");
+    assert!(minified.contains(".label{fill:currentColor}"), "{minified}");
+    assert!(
+        minified.contains(r#"<g transform="translate(.5, 1.25)">"#),
+        "{minified}"
+    );
+
+    // The minified svg is still valid xml
+    roxmltree::Document::parse(&minified).unwrap();
+}
+
+#[test]
+fn paths_are_rounded_to_the_precision() {
+    let minified = process_svg(INKSCAPE_SVG, SvgOptions::new());
+    // Repeated commands are left out
+    assert!(
+        minified.contains(r#"d="M12.123 2L22 20 2 20Z""#),
+        "{minified}"
+    );
+
+    let mut options = SvgOptions::new();
+    options.set_precision(1);
+    let minified = process_svg(INKSCAPE_SVG, options);
+    assert!(
+        minified.contains(r#"d="M12.1 2L22 20 2 20Z""#),
+        "{minified}"
+    );
+    // Rounding relative coordinates doesn't move the end of the path
+    assert!(minified.contains(r#"d="m1 1l1 0 1 0 1 0""#), "{minified}");
+}
+
+#[test]
+fn relative_paths_do_not_drift() {
+    let mut options = SvgOptions::new();
+    options.set_precision(0);
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="m0.4 0 l0.4 0 l0.4 0 l0.4 0 l-0.6 -0.6"/></svg>"#;
+    let minified = process_svg(svg, options);
+    // The exact x positions are 0.4, 0.8, 1.2 and 1.6, so the rounded path moves 0, 1, 0 and 1
+    assert!(
+        minified.contains(r#"d="m0 0l1 0 0 0 1 0-1-1""#),
+        "{minified}"
+    );
+}
+
+#[test]
+fn opacities_are_not_rounded_away() {
+    let mut options = SvgOptions::new();
+    options.set_precision(0);
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><linearGradient id="fade"><stop offset="0.25" stop-opacity="0.123456"/></linearGradient><rect x="0.4" width="10.6" height="10" opacity="0.4" fill-opacity="0.5" stroke-opacity="0.05"/></svg>"#;
+    let minified = process_svg(svg, options);
+    // Coordinates are rounded to the precision, but fractions keep enough decimal places to stay visible
+    assert!(
+        minified.contains(
+            r#"<rect x="0" width="11" height="10" opacity=".4" fill-opacity=".5" stroke-opacity=".05"/>"#
+        ),
+        "{minified}"
+    );
+    assert!(
+        minified.contains(r#"<stop offset=".25" stop-opacity=".123"/>"#),
+        "{minified}"
+    );
+}
+
+#[test]
+fn unminified_svgs_are_copied() {
+    let mut options = SvgOptions::new();
+    options.set_minify(false);
+    assert_eq!(process_svg(INKSCAPE_SVG, options), INKSCAPE_SVG);
+}
+
+#[test]
+fn svgs_without_the_svg_builder_are_copied() {
+    let options = FileOptions::default_for_extension(Some("svg"));
+    let copied = process_contents("icon.svg", INKSCAPE_SVG, options);
+    assert_eq!(String::from_utf8(copied).unwrap(), INKSCAPE_SVG);
+}
+
+#[test]
+fn invalid_svgs_are_copied() {
+    let svg = "<svg><path></svg>";
+    assert_eq!(process_svg(svg, SvgOptions::new()), svg);
+}
+
+fn rasterize(svg: &str, options: ImageOptions) -> image::DynamicImage {
+    // The output keeps the extension of the image type, so it is a raster image instead of an svg
+    let output = process_contents("logo.svg", svg, FileOptions::Image(options));
+    image::load_from_memory(&output).unwrap()
+}
+
+/// A red square in the left half of a transparent 24x24 svg
+const LOGO_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><rect width="12" height="24" fill="red"/></svg>"#;
+
+#[test]
+fn svgs_are_rasterized_at_the_output_size() {
+    for ty in [ImageType::Png, ImageType::Webp] {
+        let mut options = ImageOptions::new(ty, Some((512, 512)));
+        options.set_lossless(true);
+        let image = rasterize(LOGO_SVG, options).into_rgba8();
+        assert_eq!(image.dimensions(), (512, 512), "{ty:?}");
+
+        // The edges are drawn at the output size instead of being scaled up, so they stay sharp
+        let red = image.get_pixel(250, 256);
+        assert!(red[0] > 200 && red[3] > 200, "{ty:?} {red:?}");
+        let transparent = image.get_pixel(262, 256);
+        assert!(transparent[3] < 50, "{ty:?} {transparent:?}");
+    }
+}
+
+#[test]
+fn svgs_keep_their_aspect_ratio() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20"><rect width="40" height="20" fill="blue"/></svg>"#;
+    let image = rasterize(svg, ImageOptions::new(ImageType::Png, None));
+    assert_eq!((image.width(), image.height()), (40, 20));
+
+    let mut options = ImageOptions::new(ImageType::Jpg, Some((100, 100)));
+    options.set_fit(ImageFit::Contain);
+    let image = rasterize(svg, options);
+    assert_eq!((image.width(), image.height()), (100, 50));
+}
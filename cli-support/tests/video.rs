@@ -0,0 +1,126 @@
+mod common;
+
+use common::process_asset;
+use manganis_cli_support::process_folder;
+use manganis_common::{AssetSource, FileOptions, FolderAsset, VideoOptions, VideoType};
+use std::path::{Path, PathBuf};
+
+fn mp4_box(kind: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+    let mut mp4_box = ((contents.len() + 8) as u32).to_be_bytes().to_vec();
+    mp4_box.extend_from_slice(kind);
+    mp4_box.extend_from_slice(contents);
+    mp4_box
+}
+
+/// Builds a small mp4 with the moov box at the end of the file, like most encoders write it
+fn mp4_with_moov_at_end() -> Vec<u8> {
+    let ftyp = mp4_box(b"ftyp", b"isom\0\0\0\0isom");
+    let free = mp4_box(b"free", &[0; 8]);
+    let mdat = mp4_box(b"mdat", b"FRAME-ONEFRAME-TWO");
+    let first_frame = (ftyp.len() + free.len() + 8) as u32;
+
+    let mut stco = vec![0, 0, 0, 0];
+    stco.extend_from_slice(&2u32.to_be_bytes());
+    stco.extend_from_slice(&first_frame.to_be_bytes());
+    stco.extend_from_slice(&(first_frame + 9).to_be_bytes());
+    let stbl = mp4_box(b"stbl", &mp4_box(b"stco", &stco));
+    let minf = mp4_box(b"minf", &stbl);
+    let mdia = mp4_box(b"mdia", &minf);
+    let trak = mp4_box(b"trak", &[mp4_box(b"tkhd", &[0; 16]), mdia].concat());
+    let udta = mp4_box(b"udta", b"encoder: test");
+    let moov = mp4_box(b"moov", &[mp4_box(b"mvhd", &[0; 16]), udta, trak].concat());
+
+    [ftyp, free, mdat, moov].concat()
+}
+
+fn top_level_boxes(bytes: &[u8]) -> Vec<String> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let size = u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        boxes.push(String::from_utf8_lossy(&bytes[offset + 4..offset + 8]).to_string());
+        offset += size;
+    }
+    boxes
+}
+
+fn frames(bytes: &[u8]) -> Vec<&[u8]> {
+    let stco = bytes
+        .windows(4)
+        .position(|window| window == b"stco")
+        .unwrap()
+        + 4;
+    let count = u32::from_be_bytes(bytes[stco + 4..stco + 8].try_into().unwrap()) as usize;
+    (0..count)
+        .map(|index| {
+            let entry = stco + 8 + index * 4;
+            let offset = u32::from_be_bytes(bytes[entry..entry + 4].try_into().unwrap()) as usize;
+            &bytes[offset..offset + 9]
+        })
+        .collect()
+}
+
+fn process_video(path: &Path, compress: bool) -> Vec<u8> {
+    let mut options = VideoOptions::new(VideoType::MP4);
+    options.set_compress(compress);
+    process_asset(path, FileOptions::Video(options))
+}
+
+#[test]
+fn moves_moov_before_media_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("video.mp4");
+    std::fs::write(&path, mp4_with_moov_at_end()).unwrap();
+
+    let processed = process_video(&path, true);
+    assert_eq!(top_level_boxes(&processed), ["ftyp", "moov", "mdat"]);
+    assert_eq!(frames(&processed), [b"FRAME-ONE", b"FRAME-TWO"]);
+    assert!(!processed.windows(4).any(|window| window == b"udta"));
+}
+
+#[test]
+fn keeps_metadata_without_compression() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("video.mp4");
+    std::fs::write(&path, mp4_with_moov_at_end()).unwrap();
+
+    let processed = process_video(&path, false);
+    assert_eq!(
+        top_level_boxes(&processed),
+        ["ftyp", "free", "moov", "mdat"]
+    );
+    assert_eq!(frames(&processed), [b"FRAME-ONE", b"FRAME-TWO"]);
+    assert!(processed.windows(4).any(|window| window == b"udta"));
+}
+
+#[test]
+fn unreadable_videos_are_copied() {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../test-package/test.mp4")
+        .canonicalize()
+        .unwrap();
+
+    let processed = process_video(&fixture, true);
+    assert_eq!(processed, std::fs::read(&fixture).unwrap());
+}
+
+#[test]
+fn folders_with_videos_and_fonts_are_copied() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("video.mp4"), mp4_with_moov_at_end()).unwrap();
+    std::fs::write(dir.path().join("video.webm"), b"webm video").unwrap();
+    std::fs::write(dir.path().join("font.ttf"), b"font").unwrap();
+
+    let output = tempfile::tempdir().unwrap();
+    let folder = FolderAsset::new(AssetSource::Local(dir.path().to_path_buf())).unwrap();
+    process_folder(&folder, output.path()).unwrap();
+
+    let output = output.path().join(folder.unique_name());
+    let video = std::fs::read(output.join("video.mp4")).unwrap();
+    assert_eq!(top_level_boxes(&video), ["ftyp", "moov", "mdat"]);
+    assert_eq!(
+        std::fs::read(output.join("video.webm")).unwrap(),
+        b"webm video"
+    );
+    assert_eq!(std::fs::read(output.join("font.ttf")).unwrap(), b"font");
+}
@@ -1,15 +1,15 @@
 use std::{
     fmt::Display,
-    hash::{DefaultHasher, Hash, Hasher},
     path::{Path, PathBuf},
 };
 
 use anyhow::Context;
 use base64::Engine;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use url::Url;
 
-use crate::{cache::manifest_dir, Config, FileOptions};
+use crate::{cache::manifest_dir, Config, CssOptions, FileOptions, FontFace, Integrity};
 
 /// The maximum length of a path segment
 const MAX_PATH_LENGTH: usize = 128;
@@ -73,7 +73,7 @@ impl AssetSource {
     pub fn last_segment(&self) -> &str {
         match self {
             Self::Local(path) => path.file_name().unwrap().to_str().unwrap(),
-            Self::Remote(url) => url.path_segments().unwrap().last().unwrap(),
+            Self::Remote(url) => url.path_segments().unwrap().next_back().unwrap(),
         }
     }
 
@@ -118,7 +118,9 @@ impl AssetSource {
             AssetSource::Local(path) => Ok(std::fs::read_to_string(path).with_context(|| {
                 format!("Failed to read file from location: {}", path.display())
             })?),
-            AssetSource::Remote(_url) => Err(anyhow::anyhow!("no more remote urls!")),
+            AssetSource::Remote(url) => Err(anyhow::anyhow!(
+                "{url} is a remote asset. Remote assets are downloaded by manganis-cli-support"
+            )),
         }
     }
 
@@ -128,7 +130,9 @@ impl AssetSource {
             AssetSource::Local(path) => Ok(std::fs::read(path).with_context(|| {
                 format!("Failed to read file from location: {}", path.display())
             })?),
-            AssetSource::Remote(_url) => Err(anyhow::anyhow!("no more remote urls!")),
+            AssetSource::Remote(url) => Err(anyhow::anyhow!(
+                "{url} is a remote asset. Remote assets are downloaded by manganis-cli-support"
+            )),
         }
     }
 }
@@ -148,6 +152,7 @@ fn _ext_of_mime(mime: &str) -> &str {
         "application/rtf" => "rtf",
         "image/svg+xml" => "svg",
         "video/mp4" => "mp4",
+        "video/quicktime" => "mov",
         "text/plain" => "txt",
         "application/xml" => "xml",
         "application/zip" => "zip",
@@ -159,7 +164,7 @@ fn _ext_of_mime(mime: &str) -> &str {
         "font/ttf" => "ttf",
         "font/woff" => "woff",
         "font/woff2" => "woff2",
-        other => other.split('/').last().unwrap_or_default(),
+        other => other.split('/').next_back().unwrap_or_default(),
     }
 }
 
@@ -203,6 +208,8 @@ pub fn get_mime_from_ext(extension: Option<&str>) -> &'static str {
         Some("rtf") => "application/rtf",
         Some("svg") => "image/svg+xml",
         Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mov") => "video/quicktime",
         Some("png") => "image/png",
         Some("jpg") => "image/jpeg",
         Some("gif") => "image/gif",
@@ -217,6 +224,43 @@ pub fn get_mime_from_ext(extension: Option<&str>) -> &'static str {
     }
 }
 
+/// Encodes the data of a file into a data URL
+///
+/// Svg files are text, so they are percent encoded which is smaller than base64. Every other file is base64 encoded
+pub fn data_url(mime: &str, data: &[u8]) -> String {
+    if mime == "image/svg+xml" {
+        if let Ok(svg) = std::str::from_utf8(data) {
+            return format!("data:{mime},{}", percent_encode_svg(svg));
+        }
+    }
+    let data = base64::engine::general_purpose::STANDARD_NO_PAD.encode(data);
+    format!("data:{mime};base64,{data}")
+}
+
+/// Percent encodes the characters in an svg that are not allowed in a URL or would end an html attribute or css url
+fn percent_encode_svg(svg: &str) -> String {
+    // Double quotes would need to be encoded, but single quotes can be used in URLs as is
+    let svg = if svg.contains('\'') {
+        std::borrow::Cow::Borrowed(svg)
+    } else {
+        std::borrow::Cow::Owned(svg.replace('"', "'"))
+    };
+    let mut encoded = String::with_capacity(svg.len());
+    for byte in svg.bytes() {
+        match byte {
+            b'%' | b'#' | b'"' | b'<' | b'>' | b'{' | b'}' | b'|' | b'\\' | b'^' | b'`' => {
+                encoded.push_str(&format!("%{byte:02X}"))
+            }
+            // Whitespace and non-ascii characters are also encoded so the URL can be used in a srcset
+            byte if byte.is_ascii_whitespace() || byte.is_ascii_control() || !byte.is_ascii() => {
+                encoded.push_str(&format!("%{byte:02X}"))
+            }
+            byte => encoded.push(byte as char),
+        }
+    }
+    encoded
+}
+
 /// The location of an asset before and after it is collected
 #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash, Eq)]
 pub struct AssetLocation {
@@ -267,6 +311,8 @@ impl Display for AssetError {
     }
 }
 
+impl std::error::Error for AssetError {}
+
 impl AssetSource {
     /// Parse a string as a file source
     pub fn parse_file(path: &str) -> Result<Self, AssetError> {
@@ -331,8 +377,8 @@ impl Display for FolderAsset {
 }
 
 impl FolderAsset {
-    /// Creates a new folder asset
-    pub fn new(source: AssetSource) -> Self {
+    /// Creates a new folder asset. This reads every file in the folder to create the unique name of the folder
+    pub fn new(source: AssetSource) -> Result<Self, AssetError> {
         let AssetSource::Local(source) = source else {
             panic!("Folder asset must be a local path");
         };
@@ -345,16 +391,25 @@ impl FolderAsset {
             },
         };
 
-        myself.regenerate_unique_name();
+        myself.regenerate_unique_name()?;
 
-        myself
+        Ok(myself)
     }
 
     /// Returns the location where the folder asset will be served from or None if the asset cannot be served
+    ///
+    /// This reads the manganis support and config the CLI passes to the build from the environment, so it is meant for the macro. CLIs should use [`FolderAsset::served_location_with_config`] instead
     pub fn served_location(&self) -> Result<String, ManganisSupportError> {
         resolve_asset_location(&self.location)
     }
 
+    /// Returns the location where the folder asset is served from in a build with the given config
+    ///
+    /// Unlike [`FolderAsset::served_location`], this does not read the config from the environment, so the CLI can use it for the build it collected the asset from
+    pub fn served_location_with_config(&self, config: &Config) -> String {
+        collected_location(&self.location, config)
+    }
+
     /// Returns the unique name of the folder asset
     pub fn unique_name(&self) -> &str {
         &self.location.unique_name
@@ -365,30 +420,41 @@ impl FolderAsset {
         &self.location
     }
 
-    /// Create a unique hash for the source folder by recursively hashing the files
-    fn hash(&self) -> u64 {
-        let mut hash = std::collections::hash_map::DefaultHasher::new();
+    /// Create a unique hash for the source folder by recursively hashing the relative paths and contents of the files
+    fn hash(&self) -> Result<u64, AssetError> {
+        let mut hash = AssetHasher::new();
         let folder = self
             .location
             .source
             .as_path()
             .expect("Folder asset must be a local path");
         let mut folders_queued = vec![folder.clone()];
-        while let Some(folder) = folders_queued.pop() {
-            // Add the folder to the hash
-            for segment in folder.iter() {
-                segment.hash(&mut hash);
-            }
+        while let Some(current) = folders_queued.pop() {
+            // Sort the entries so the hash doesn't depend on the order the file system returns them in
+            let mut entries = std::fs::read_dir(&current)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|err| AssetError::IO(current.clone(), err))?;
+            entries.sort();
+
+            for path in entries {
+                // Add the path relative to the root of the folder to the hash so the location of the folder doesn't change the hash
+                let relative: Vec<_> = path
+                    .strip_prefix(folder)
+                    .unwrap_or(&path)
+                    .iter()
+                    .map(|segment| segment.to_string_lossy())
+                    .collect();
+                hash.update(relative.join("/").as_bytes());
 
-            let files = std::fs::read_dir(folder).into_iter().flatten().flatten();
-            for file in files {
-                let path = file.path();
-                let metadata = path.metadata().unwrap();
                 // If the file is a folder, add it to the queue otherwise add it to the hash
-                if metadata.is_dir() {
+                if path.is_dir() {
                     folders_queued.push(path);
                 } else {
-                    hash_file(&AssetSource::Local(path), &mut hash);
+                    hash.update(source_digest(&AssetSource::Local(path))?.as_bytes());
                 }
             }
         }
@@ -396,15 +462,16 @@ impl FolderAsset {
         // Add the manganis version to the hash
         hash_version(&mut hash);
 
-        hash.finish()
+        Ok(hash.finish())
     }
 
     /// Regenerate the unique name of the folder asset
-    fn regenerate_unique_name(&mut self) {
-        let uuid = self.hash();
+    fn regenerate_unique_name(&mut self) -> Result<(), AssetError> {
+        let uuid = self.hash()?;
         let file_name = normalized_file_name(&self.location.source, None);
         self.location.unique_name = format!("{file_name}{uuid:x}");
         assert!(self.location.unique_name.len() <= MAX_PATH_LENGTH);
+        Ok(())
     }
 }
 
@@ -414,6 +481,11 @@ pub struct FileAsset {
     location: AssetLocation,
     options: FileOptions,
     url_encoded: bool,
+    #[serde(default)]
+    integrity: Option<Integrity>,
+    /// The digest of the contents of a local file or the url of a remote file. The file is only read once when the asset is created
+    #[serde(default)]
+    source_digest: String,
 }
 
 impl Display for FileAsset {
@@ -434,13 +506,14 @@ impl Display for FileAsset {
 }
 
 impl FileAsset {
-    /// Creates a new file asset
-    pub fn new(source: AssetSource) -> Self {
+    /// Creates a new file asset. Local files are read to create the unique name of the file
+    pub fn new(source: AssetSource) -> Result<Self, AssetError> {
         if let Some(path) = source.as_path() {
             assert!(!path.is_dir());
         }
 
         let options = FileOptions::default_for_extension(source.extension().as_deref());
+        let source_digest = source_digest(&source)?;
 
         let mut myself = Self {
             location: AssetLocation {
@@ -449,11 +522,13 @@ impl FileAsset {
             },
             options,
             url_encoded: false,
+            integrity: None,
+            source_digest,
         };
 
         myself.regenerate_unique_name();
 
-        myself
+        Ok(myself)
     }
 
     /// Set the file options
@@ -462,6 +537,8 @@ impl FileAsset {
             location: self.location,
             options,
             url_encoded: false,
+            integrity: self.integrity,
+            source_digest: self.source_digest,
         };
 
         myself.regenerate_unique_name();
@@ -479,18 +556,45 @@ impl FileAsset {
         self.url_encoded
     }
 
+    /// Returns the expected integrity of the file contents if the asset is pinned to a specific digest
+    pub fn integrity(&self) -> Option<&Integrity> {
+        self.integrity.as_ref()
+    }
+
+    /// Pin the contents of the file to a specific digest. The CLI will reject remote files that don't match the digest
+    pub fn set_integrity(&mut self, integrity: Option<Integrity>) {
+        self.integrity = integrity;
+        self.regenerate_unique_name();
+    }
+
     /// Returns the location where the file asset will be served from or None if the asset cannot be served
+    ///
+    /// This reads the manganis support and config the CLI passes to the build from the environment, so it is meant for the macro. CLIs should use [`FileAsset::served_location_with_config`] instead
     pub fn served_location(&self) -> Result<String, ManganisSupportError> {
         if self.url_encoded {
-            let data = self.location.source.read_to_bytes().unwrap();
-            let data = base64::engine::general_purpose::STANDARD_NO_PAD.encode(data);
-            let mime = self.location.source.mime_type().unwrap();
-            Ok(format!("data:{mime};base64,{data}"))
+            Ok(self.encoded_data_url())
         } else {
             resolve_asset_location(&self.location)
         }
     }
 
+    /// Returns the location where the file asset is served from in a build with the given config
+    ///
+    /// Unlike [`FileAsset::served_location`], this does not read the config from the environment, so the CLI can use it for the build it collected the asset from
+    pub fn served_location_with_config(&self, config: &Config) -> String {
+        if self.url_encoded {
+            self.encoded_data_url()
+        } else {
+            collected_location(&self.location, config)
+        }
+    }
+
+    fn encoded_data_url(&self) -> String {
+        let data = self.location.source.read_to_bytes().unwrap();
+        let mime = self.location.source.mime_type().unwrap();
+        data_url(&mime, &data)
+    }
+
     /// Returns the location of the file asset
     pub fn location(&self) -> &AssetLocation {
         &self.location
@@ -501,17 +605,44 @@ impl FileAsset {
         &self.options
     }
 
+    /// Returns the stylesheet with the `@font-face` rule that is generated next to a font asset with a [`FontFace`]
+    ///
+    /// The stylesheet is written while the font is processed, so it is served from the unique name of the font with [`FontFace::css_file_name`]
+    pub fn font_face_stylesheet(&self) -> Option<FileAsset> {
+        let FileOptions::Font(options) = &self.options else {
+            return None;
+        };
+        options.font_face()?;
+        Some(Self {
+            location: AssetLocation {
+                unique_name: FontFace::css_file_name(&self.location.unique_name),
+                source: self.location.source.clone(),
+            },
+            options: FileOptions::Css(CssOptions::new()),
+            url_encoded: false,
+            integrity: None,
+            source_digest: self.source_digest.clone(),
+        })
+    }
+
     /// Returns the options for the file asset mutably
     pub fn with_options_mut(&mut self, f: impl FnOnce(&mut FileOptions)) {
         f(&mut self.options);
         self.regenerate_unique_name();
     }
 
-    /// Hash the file asset source and options
+    /// Hash the file asset contents and options
     fn hash(&self) -> u64 {
-        let mut hash = std::collections::hash_map::DefaultHasher::new();
-        hash_file(&self.location.source, &mut hash);
-        self.options.hash(&mut hash);
+        let mut hash = AssetHasher::new();
+        match (&self.location.source, &self.integrity) {
+            // If a remote file is pinned to a digest, the digest identifies the contents of the file
+            (AssetSource::Remote(_), Some(integrity)) => {
+                hash.update(integrity.to_string().as_bytes())
+            }
+            _ => hash.update(self.source_digest.as_bytes()),
+        }
+        let options = serde_json::to_vec(&self.options).expect("Failed to serialize file options");
+        hash.update(&options);
         hash_version(&mut hash);
         hash.finish()
     }
@@ -549,22 +680,54 @@ fn to_alphanumeric_string_lossy(name: &str) -> String {
         .collect::<String>()
 }
 
-fn hash_file(location: &AssetSource, hash: &mut DefaultHasher) {
-    // Hash the last time the file was updated and the file source. If either of these change, we need to regenerate the unique name
-    let updated = location.last_updated();
-    updated.hash(hash);
-    location.hash(hash);
+/// The hasher used to create the unique names of assets.
+///
+/// Unique names end with the first 64 bits of a SHA-256 digest formatted as hex. Every field is length prefixed before it
+/// is added to the digest so the same bytes split across different fields hash differently. Only the contents of the asset,
+/// the options and the version of manganis are hashed, so the same asset will get the same unique name on every machine.
+struct AssetHasher(Sha256);
+
+impl AssetHasher {
+    fn new() -> Self {
+        Self(Sha256::new())
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update((bytes.len() as u64).to_le_bytes());
+        self.0.update(bytes);
+    }
+
+    fn finish(self) -> u64 {
+        let digest = self.0.finalize();
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
+}
+
+/// Creates a SHA-256 digest of the contents of the file. If the contents change, we need to regenerate the unique name
+fn source_digest(location: &AssetSource) -> Result<String, AssetError> {
+    let digest = match location {
+        AssetSource::Local(path) => {
+            let contents = std::fs::read(path).map_err(|err| AssetError::IO(path.clone(), err))?;
+            Sha256::digest(contents)
+        }
+        // The contents of remote files are not known until they are downloaded, so we hash the url instead
+        AssetSource::Remote(url) => Sha256::digest(url.as_str()),
+    };
+    Ok(format!("{digest:x}"))
 }
 
-fn hash_version(hash: &mut DefaultHasher) {
+fn hash_version(hash: &mut AssetHasher) {
     // Hash the current version of manganis. If this changes, we need to regenerate the unique name
-    crate::built::PKG_VERSION.hash(hash);
-    crate::built::GIT_COMMIT_HASH.hash(hash);
+    hash.update(crate::built::PKG_VERSION.as_bytes());
+    hash.update(crate::built::GIT_COMMIT_HASH.unwrap_or_default().as_bytes());
 }
 
+/// The environment variable a CLI that supports assets sets on the cargo commands it runs
+pub const SUPPORT_ENV_VAR: &str = "MANGANIS_SUPPORT";
+
 fn resolve_asset_location(location: &AssetLocation) -> Result<String, ManganisSupportError> {
     // If manganis is being used without CLI support, we will fallback to providing a local path.
-    let manganis_support = std::env::var("MANGANIS_SUPPORT");
+    let manganis_support = std::env::var(SUPPORT_ENV_VAR);
     if manganis_support.is_err() {
         match location.source() {
             AssetSource::Remote(url) => Ok(url.as_str().to_string()),
@@ -598,13 +761,17 @@ fn resolve_asset_location(location: &AssetLocation) -> Result<String, ManganisSu
             }
         }
     } else {
-        let config = Config::current();
-        let root = config.assets_serve_location();
-        let unique_name = location.unique_name();
-        Ok(format!("{root}{unique_name}"))
+        Ok(collected_location(location, &Config::current()))
     }
 }
 
+/// The location an asset collected by the CLI is served from
+fn collected_location(location: &AssetLocation, config: &Config) -> String {
+    let root = config.assets_serve_location();
+    let unique_name = location.unique_name();
+    format!("{root}{unique_name}")
+}
+
 /// An error that can occur while collecting assets without CLI support
 #[derive(Debug)]
 pub enum ManganisSupportError {
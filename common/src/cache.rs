@@ -14,6 +14,16 @@ pub fn asset_cache_dir() -> PathBuf {
     dir
 }
 
+/// The location where remote assets are cached after they are downloaded
+pub fn remote_asset_cache_dir() -> PathBuf {
+    asset_cache_dir().join("remote")
+}
+
+/// The location where the formats picked for images with `.format_auto()` are cached
+pub fn image_format_cache_dir() -> PathBuf {
+    asset_cache_dir().join("formats")
+}
+
 pub(crate) fn config_path() -> PathBuf {
     asset_cache_dir().join("config.toml")
 }
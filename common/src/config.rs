@@ -1,8 +1,11 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, process::Command};
 
 use serde::{Deserialize, Serialize};
 
-use crate::cache::config_path;
+use crate::{cache::config_path, ImageType};
+
+/// The environment variable the config for the current build is passed through. It contains the config serialized as TOML
+pub const CONFIG_ENV_VAR: &str = "MANGANIS_CONFIG";
 
 fn default_assets_serve_location() -> String {
     #[cfg(target_arch = "wasm32")]
@@ -15,11 +18,30 @@ fn default_assets_serve_location() -> String {
     }
 }
 
+fn default_auto_image_formats() -> Vec<ImageType> {
+    vec![
+        ImageType::Avif,
+        ImageType::Webp,
+        ImageType::Jpg,
+        ImageType::Png,
+    ]
+}
+
+fn default_auto_image_quality() -> u8 {
+    80
+}
+
 /// The configuration for collecting assets
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct Config {
     #[serde(default = "default_assets_serve_location")]
     assets_serve_location: String,
+    #[serde(default)]
+    require_alt: bool,
+    #[serde(default = "default_auto_image_formats")]
+    auto_image_formats: Vec<ImageType>,
+    #[serde(default = "default_auto_image_quality")]
+    auto_image_quality: u8,
 }
 
 impl Config {
@@ -27,6 +49,9 @@ impl Config {
     pub fn with_assets_serve_location(&self, assets_serve_location: impl Into<String>) -> Self {
         Self {
             assets_serve_location: assets_serve_location.into(),
+            require_alt: self.require_alt,
+            auto_image_formats: self.auto_image_formats.clone(),
+            auto_image_quality: self.auto_image_quality,
         }
     }
 
@@ -35,6 +60,53 @@ impl Config {
         &self.assets_serve_location
     }
 
+    /// Sets whether every image must have alt text. If this is enabled, collecting an image with the `image` builder without `.alt("...")` is a compile error. Decorative images can use an empty alt text. The `mg!("image.png")` shorthand only returns the path of the image, so it is not checked
+    pub fn with_require_alt(&self, require_alt: bool) -> Self {
+        Self {
+            assets_serve_location: self.assets_serve_location.clone(),
+            require_alt,
+            auto_image_formats: self.auto_image_formats.clone(),
+            auto_image_quality: self.auto_image_quality,
+        }
+    }
+
+    /// Returns whether every image must have alt text
+    pub fn require_alt(&self) -> bool {
+        self.require_alt
+    }
+
+    /// Sets the formats images with `.format_auto()` can be encoded in. The image is encoded in each format and the smallest one that meets the auto image quality is served. If two formats are the same size, the one that comes first is used
+    pub fn with_auto_image_formats(&self, formats: impl Into<Vec<ImageType>>) -> Self {
+        Self {
+            assets_serve_location: self.assets_serve_location.clone(),
+            require_alt: self.require_alt,
+            auto_image_formats: formats.into(),
+            auto_image_quality: self.auto_image_quality,
+        }
+    }
+
+    /// Returns the formats images with `.format_auto()` can be encoded in (default: avif, webp, jpg, png)
+    pub fn auto_image_formats(&self) -> &[ImageType] {
+        &self.auto_image_formats
+    }
+
+    /// Sets the quality from 1 to 100 images with `.format_auto()` that don't set their own quality are encoded with and must look at least as good as.
+    ///
+    /// Each encoder has its own quality scale, so every format is decoded again and compared with the source using SSIM. A format meets quality N if its SSIM is at least `0.9 + N / 1000`, so the default quality of 80 needs an SSIM of 0.98
+    pub fn with_auto_image_quality(&self, quality: u8) -> Self {
+        Self {
+            assets_serve_location: self.assets_serve_location.clone(),
+            require_alt: self.require_alt,
+            auto_image_formats: self.auto_image_formats.clone(),
+            auto_image_quality: quality,
+        }
+    }
+
+    /// Returns the quality images with `.format_auto()` that don't set their own quality must meet (default: 80)
+    pub fn auto_image_quality(&self) -> u8 {
+        self.auto_image_quality
+    }
+
     #[doc(hidden)]
     /// Returns the path to the config
     /// This is only used in the macro
@@ -43,16 +115,36 @@ impl Config {
     }
 
     /// Returns the current config
+    ///
+    /// The config passed to the current build through [`CONFIG_ENV_VAR`] takes precedence over the config saved globally with [`Config::save`]
     pub fn current() -> Self {
+        if let Ok(config) = std::env::var(CONFIG_ENV_VAR) {
+            match toml::from_str(&config) {
+                Ok(config) => return config,
+                Err(err) => {
+                    tracing::error!("Failed to parse the config in {CONFIG_ENV_VAR}: {err}")
+                }
+            }
+        }
+
         std::fs::read(config_path())
             .ok()
             .and_then(|config| toml::from_str(&String::from_utf8_lossy(&config)).ok())
             .unwrap_or_default()
     }
 
+    /// Passes the config to a command that builds the application you are collecting assets from.
+    ///
+    /// Unlike [`Config::save`], the config only applies to the build started by this command, so multiple builds with different configs can run at the same time.
+    pub fn apply_to_command<'a>(&self, command: &'a mut Command) -> &'a mut Command {
+        command.env(CONFIG_ENV_VAR, toml::to_string(&self).unwrap())
+    }
+
     /// Saves the config globally. This must be run before compiling the application you are collecting assets from.
     ///
     /// The assets macro will read the config from the global config file and set the assets serve location to the value in the config.
+    ///
+    /// The global config is shared between every build on the machine. Prefer [`Config::apply_to_command`] to pass the config to a single build.
     pub fn save(&self) {
         let current = Self::current();
         if current == *self {
@@ -70,6 +162,9 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             assets_serve_location: default_assets_serve_location(),
+            require_alt: false,
+            auto_image_formats: default_auto_image_formats(),
+            auto_image_quality: default_auto_image_quality(),
         }
     }
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
+use crate::{is_svg, svg_size, ImageOrientation, VideoMetadata};
+
 /// The options for a file asset
 #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
 pub enum FileOptions {
@@ -16,6 +18,10 @@ pub enum FileOptions {
     Js(JsOptions),
     /// A Json asset
     Json(JsonOptions),
+    /// An svg asset
+    Svg(SvgOptions),
+    /// An icon generated from an image
+    Icon(IconOptions),
     /// Any other asset
     Other(UnknownFileOptions),
 }
@@ -29,6 +35,8 @@ impl Display for FileOptions {
             Self::Css(options) => write!(f, "{}", options),
             Self::Js(options) => write!(f, "{}", options),
             Self::Json(options) => write!(f, "{}", options),
+            Self::Svg(options) => write!(f, "{}", options),
+            Self::Icon(options) => write!(f, "{}", options),
             Self::Other(options) => write!(f, "{}", options),
         }
     }
@@ -36,13 +44,16 @@ impl Display for FileOptions {
 
 impl FileOptions {
     /// Returns the default options for a given extension
+    ///
+    /// Svg files are copied unchanged. Use the `svg` builder to opt into the svg optimizer
     pub fn default_for_extension(extension: Option<&str>) -> Self {
         if let Some(extension) = extension {
             if extension == CssOptions::EXTENSION {
                 return Self::Css(CssOptions::default());
             } else if extension == JsonOptions::EXTENSION {
                 return Self::Json(JsonOptions::default());
-            } else if let Ok(ty) = extension.parse::<ImageType>() {
+            } else if let Ok(ty) = extension.to_ascii_lowercase().parse::<ImageType>() {
+                // Photos from cameras often have uppercase extensions like JPG. They still need their metadata removed
                 return Self::Image(ImageOptions::new(ty, None));
             } else if let Ok(ty) = extension.parse::<VideoType>() {
                 return Self::Video(VideoOptions::new(ty));
@@ -66,6 +77,8 @@ impl FileOptions {
             Self::Css(_) => Some(CssOptions::EXTENSION),
             Self::Js(js) => Some(js.ty.extension()),
             Self::Json(_) => Some(JsonOptions::EXTENSION),
+            Self::Svg(_) => Some(SvgOptions::EXTENSION),
+            Self::Icon(options) => Some(options.extension()),
             Self::Other(extension) => extension.extension.as_deref(),
         }
     }
@@ -82,6 +95,33 @@ impl Default for FileOptions {
 pub struct ImageOptions {
     compress: bool,
     size: Option<(u32, u32)>,
+    /// The width to resize the image to while keeping the aspect ratio. This is used for the variants of a responsive image
+    #[serde(default)]
+    width: Option<u32>,
+    /// How the image is fit into the size
+    #[serde(default)]
+    fit: ImageFit,
+    /// The largest width of the image. Larger images are scaled down while keeping the aspect ratio
+    #[serde(default)]
+    max_width: Option<u32>,
+    /// The largest height of the image. Larger images are scaled down while keeping the aspect ratio
+    #[serde(default)]
+    max_height: Option<u32>,
+    /// The quality of the encoded image from 1 to 100. If this is not set, the default quality of the encoder is used
+    #[serde(default)]
+    quality: Option<u8>,
+    /// Whether the image should be encoded without losing any information
+    #[serde(default)]
+    lossless: bool,
+    /// How much effort the encoder spends on making the image smaller from 1 to 10. If this is not set, the default effort of the encoder is used
+    #[serde(default)]
+    effort: Option<u8>,
+    /// Whether the ICC color profile of the image is kept. The rest of the metadata of the image is always removed
+    #[serde(default)]
+    preserve_icc_profile: bool,
+    /// The pixel density the image is scaled for. The sizes in the options are the 1x sizes
+    #[serde(default)]
+    density: Option<u32>,
     preload: bool,
     ty: ImageType,
 }
@@ -89,10 +129,33 @@ pub struct ImageOptions {
 impl Display for ImageOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some((x, y)) = self.size {
-            write!(f, "{} ({}x{})", self.ty, x, y)?;
+            write!(f, "{} ({}x{} {})", self.ty, x, y, self.fit)?;
         } else {
             write!(f, "{}", self.ty)?;
         }
+        if let Some(width) = self.width {
+            write!(f, " ({}w)", width)?;
+        }
+        if let Some(density) = self.density {
+            write!(f, " ({}x)", density)?;
+        }
+        match (self.max_width, self.max_height) {
+            (Some(width), Some(height)) => write!(f, " (max {}x{})", width, height)?,
+            (Some(width), None) => write!(f, " (max width {})", width)?,
+            (None, Some(height)) => write!(f, " (max height {})", height)?,
+            (None, None) => {}
+        }
+        if self.lossless {
+            write!(f, " (lossless)")?;
+        } else if let Some(quality) = self.quality {
+            write!(f, " (quality {})", quality)?;
+        }
+        if let Some(effort) = self.effort {
+            write!(f, " (effort {})", effort)?;
+        }
+        if self.preserve_icc_profile {
+            write!(f, " (icc profile)")?;
+        }
         if self.compress {
             write!(f, " (compressed)")?;
         }
@@ -109,6 +172,15 @@ impl ImageOptions {
         Self {
             compress: true,
             size,
+            width: None,
+            fit: ImageFit::default(),
+            max_width: None,
+            max_height: None,
+            quality: None,
+            lossless: false,
+            effort: None,
+            preserve_icc_profile: false,
+            density: None,
             ty,
             preload: false,
         }
@@ -144,19 +216,253 @@ impl ImageOptions {
         self.size = size;
     }
 
+    /// Returns the width the image is resized to while keeping the aspect ratio
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    /// Sets the width the image is resized to while keeping the aspect ratio. Images that are already narrower than the width are not scaled up
+    pub fn set_width(&mut self, width: Option<u32>) {
+        self.width = width;
+    }
+
+    /// Returns how the image is fit into the size
+    pub fn fit(&self) -> &ImageFit {
+        &self.fit
+    }
+
+    /// Sets how the image is fit into the size. This only applies if the size is set
+    pub fn set_fit(&mut self, fit: ImageFit) {
+        self.fit = fit;
+    }
+
+    /// Returns the largest width of the image
+    pub fn max_width(&self) -> Option<u32> {
+        self.max_width
+    }
+
+    /// Sets the largest width of the image. Wider images are scaled down while keeping the aspect ratio. Smaller images are never scaled up
+    pub fn set_max_width(&mut self, max_width: Option<u32>) {
+        self.max_width = max_width;
+    }
+
+    /// Returns the largest height of the image
+    pub fn max_height(&self) -> Option<u32> {
+        self.max_height
+    }
+
+    /// Sets the largest height of the image. Taller images are scaled down while keeping the aspect ratio. Smaller images are never scaled up
+    pub fn set_max_height(&mut self, max_height: Option<u32>) {
+        self.max_height = max_height;
+    }
+
+    /// Scales the size, width and largest size of the image for a screen with the given pixel density. A density of 2 makes an image with twice as many pixels in each direction
+    ///
+    /// Raster images have no more detail than the original image, so they are only scaled up to the resolution of the original image or the 1x size, whichever is larger
+    pub fn scale(&mut self, density: u32) {
+        self.density = (density != 1).then_some(density);
+    }
+
+    /// Returns the pixel density the image is scaled for
+    pub fn density(&self) -> u32 {
+        self.density.unwrap_or(1)
+    }
+
+    /// Returns the quality of the encoded image from 1 to 100
+    pub fn quality(&self) -> Option<u8> {
+        self.quality
+    }
+
+    /// Sets the quality of the encoded image from 1 to 100. Values outside of that range are clamped when the image is encoded
+    pub fn set_quality(&mut self, quality: Option<u8>) {
+        self.quality = quality;
+    }
+
+    /// Returns whether the image should be encoded without losing any information
+    pub fn lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// Sets whether the image should be encoded without losing any information. The quality is ignored for lossless images. Only png and webp images support lossless encoding, so the `mg!` macro rejects lossless jpg and avif images. If the options are built by hand, jpg and avif images are encoded with the highest quality instead
+    pub fn set_lossless(&mut self, lossless: bool) {
+        self.lossless = lossless;
+    }
+
+    /// Returns how much effort the encoder spends on making the image smaller from 1 to 10
+    pub fn effort(&self) -> Option<u8> {
+        self.effort
+    }
+
+    /// Sets how much effort the encoder spends on making the image smaller from 1 to 10. Higher values make smaller images, but take longer to encode. Values outside of that range are clamped when the image is encoded
+    pub fn set_effort(&mut self, effort: Option<u8>) {
+        self.effort = effort;
+    }
+
+    /// Returns whether the ICC color profile of the image is kept
+    pub fn preserve_icc_profile(&self) -> bool {
+        self.preserve_icc_profile
+    }
+
+    /// Sets whether the ICC color profile of the image is kept. Images with a wide gamut profile lose some of their colors without it. The EXIF, XMP and GPS metadata of the image is always removed
+    pub fn set_preserve_icc_profile(&mut self, preserve_icc_profile: bool) {
+        self.preserve_icc_profile = preserve_icc_profile;
+    }
+
+    /// Returns the size of the image after it is processed with these options. The size of the original image is read from the header of the image, so the image doesn't need to be decoded
+    ///
+    /// Images are rotated upright with their EXIF orientation before they are resized, so the size accounts for the orientation
+    pub fn output_size(&self, image: &[u8]) -> Option<(u32, u32)> {
+        // Svgs are rasterized at the size they are drawn at
+        if is_svg(image) {
+            return Some(self.rasterized_size(svg_size(image)?));
+        }
+        let original = imagesize::blob_size(image).ok()?;
+        let original = (
+            u32::try_from(original.width).ok()?,
+            u32::try_from(original.height).ok()?,
+        );
+        Some(self.resized_size(ImageOrientation::read(image).upright_size(original)))
+    }
+
+    /// Returns the size an image with the original upright size is resized to with these options
+    ///
+    /// The image is first fit into the size, then resized to the width of a responsive image and finally scaled down to the max width and height.
+    /// Density variants are then scaled up, but never past the original size or the 1x size, whichever is larger
+    pub fn resized_size(&self, original: (u32, u32)) -> (u32, u32) {
+        let base = self.unscaled_size(original);
+        let scaled = self.scaled(base);
+        let limit = (original.0.max(base.0), original.1.max(base.1));
+        if scaled.0 > limit.0 || scaled.1 > limit.1 {
+            scale_to_fit(scaled, limit)
+        } else {
+            scaled
+        }
+    }
+
+    /// Returns the size an svg with the size it is drawn at is rasterized at with these options. Svgs have detail at any size, so density variants are not limited to the original size
+    pub fn rasterized_size(&self, original: (u32, u32)) -> (u32, u32) {
+        self.scaled(self.unscaled_size(original))
+    }
+
+    /// Scales the 1x size of the image to the density of the image
+    fn scaled(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        let density = self.density();
+        (
+            width.saturating_mul(density),
+            height.saturating_mul(density),
+        )
+    }
+
+    /// Returns the 1x size of the image
+    fn unscaled_size(&self, original: (u32, u32)) -> (u32, u32) {
+        let mut size = original;
+        if let Some(target) = self.size {
+            size = match self.fit {
+                ImageFit::Fill | ImageFit::Cover { .. } => target,
+                ImageFit::Contain => scale_to_fit(size, target),
+            };
+        }
+        // Responsive images are never scaled up
+        if let Some(width) = self.width {
+            if width < size.0 {
+                size = scale_to_fit(size, (width, u32::MAX));
+            }
+        }
+        let max_size = (
+            self.max_width.unwrap_or(u32::MAX),
+            self.max_height.unwrap_or(u32::MAX),
+        );
+        if size.0 > max_size.0 || size.1 > max_size.1 {
+            size = scale_to_fit(size, max_size);
+        }
+        size
+    }
+
+    /// Returns the part of an image with the original size that is kept when the image is resized as `(x, y, width, height)`
+    ///
+    /// Only images that cover their size are cropped. The crop has the aspect ratio of the size and is centered on the focal point as close as possible
+    pub fn crop(&self, (width, height): (u32, u32)) -> Option<(u32, u32, u32, u32)> {
+        let (Some((target_width, target_height)), ImageFit::Cover { focal_point }) =
+            (self.size, self.fit)
+        else {
+            return None;
+        };
+        let (focal_x, focal_y) = focal_point;
+        let crop_width = ((height as f64 * target_width as f64 / target_height as f64).round()
+            as u32)
+            .clamp(1, width);
+        let crop_height = ((width as f64 * target_height as f64 / target_width as f64).round()
+            as u32)
+            .clamp(1, height);
+        let offset = |length: u32, crop: u32, focal: u8| {
+            let center = length as f64 * focal.min(100) as f64 / 100.;
+            ((center - crop as f64 / 2.).round().max(0.) as u32).min(length - crop)
+        };
+        Some((
+            offset(width, crop_width, focal_x),
+            offset(height, crop_height, focal_y),
+            crop_width,
+            crop_height,
+        ))
+    }
+
     /// Returns whether the image should be compressed
     pub fn compress(&self) -> bool {
         self.compress
     }
 
-    /// Sets whether the image should be compressed
+    /// Sets whether the image should be compressed. Png images that are not compressed are written without reducing the number of colors
     pub fn set_compress(&mut self, compress: bool) {
         self.compress = compress;
     }
 }
 
+/// Scales a size to the largest size that fits in the bounds while keeping the aspect ratio
+fn scale_to_fit((width, height): (u32, u32), (max_width, max_height): (u32, u32)) -> (u32, u32) {
+    let scale = f64::min(
+        max_width as f64 / width as f64,
+        max_height as f64 / height as f64,
+    );
+    let scaled = |length: u32, max: u32| ((length as f64 * scale).round() as u32).clamp(1, max);
+    (scaled(width, max_width), scaled(height, max_height))
+}
+
+/// How an image is fit into the size it is resized to
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Copy, Hash, Default)]
+pub enum ImageFit {
+    /// Stretch the image to the size. This may distort the image if it has a different aspect ratio than the size
+    #[default]
+    Fill,
+    /// Scale the image to the largest size that fits in the size while keeping the aspect ratio. One side of the image may be smaller than the size
+    Contain,
+    /// Scale the image to the smallest size that covers the size while keeping the aspect ratio and crop the parts that don't fit
+    Cover {
+        /// The point of the image that is kept in view when the image is cropped, as a percentage of the width and height of the image. `(50, 50)` is the center of the image
+        focal_point: (u8, u8),
+    },
+}
+
+impl ImageFit {
+    /// Covers the size and crops around the center of the image
+    pub const COVER: Self = Self::Cover {
+        focal_point: (50, 50),
+    };
+}
+
+impl Display for ImageFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fill => write!(f, "fill"),
+            Self::Contain => write!(f, "contain"),
+            Self::Cover {
+                focal_point: (x, y),
+            } => write!(f, "cover at {}% {}%", x, y),
+        }
+    }
+}
+
 /// The type of an image
-#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Clone, Copy, Hash)]
 pub enum ImageType {
     /// A png image
     Png,
@@ -178,6 +484,11 @@ impl ImageType {
             Self::Webp => "webp",
         }
     }
+
+    /// Returns whether images of this type can be encoded without losing any information
+    pub fn supports_lossless(&self) -> bool {
+        matches!(self, Self::Png | Self::Webp)
+    }
 }
 
 impl Display for ImageType {
@@ -209,6 +520,9 @@ pub struct VideoOptions {
     preload: bool,
     /// The type of the video
     ty: VideoType,
+    /// Information about the video read from its container
+    #[serde(default)]
+    metadata: VideoMetadata,
 }
 
 impl Display for VideoOptions {
@@ -231,9 +545,20 @@ impl VideoOptions {
             compress: true,
             ty,
             preload: false,
+            metadata: VideoMetadata::default(),
         }
     }
 
+    /// Returns the information about the video read from its container
+    pub fn metadata(&self) -> &VideoMetadata {
+        &self.metadata
+    }
+
+    /// Sets the information about the video
+    pub fn set_metadata(&mut self, metadata: VideoMetadata) {
+        self.metadata = metadata;
+    }
+
     /// Returns the type of the video
     pub fn ty(&self) -> &VideoType {
         &self.ty
@@ -270,6 +595,8 @@ impl VideoOptions {
 pub enum VideoType {
     /// An mp4 video
     MP4,
+    /// A QuickTime video
+    MOV,
     /// A webm video
     Webm,
     /// A gif video
@@ -281,6 +608,7 @@ impl VideoType {
     pub fn extension(&self) -> &'static str {
         match self {
             Self::MP4 => "mp4",
+            Self::MOV => "mov",
             Self::Webm => "webm",
             Self::GIF => "gif",
         }
@@ -299,6 +627,7 @@ impl FromStr for VideoType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "mp4" => Ok(Self::MP4),
+            "mov" => Ok(Self::MOV),
             "webm" => Ok(Self::Webm),
             "gif" => Ok(Self::GIF),
             _ => Err(()),
@@ -309,25 +638,107 @@ impl FromStr for VideoType {
 /// The options for a font asset
 #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
 pub struct FontOptions {
+    /// The type the font is converted to
     ty: FontType,
+    /// The text the font needs to support. If this is set, the font is subset to only include the glyphs in the text
+    #[serde(default)]
+    text: Option<String>,
+    /// The `@font-face` rule generated next to the font
+    #[serde(default)]
+    font_face: Option<FontFace>,
 }
 
 impl Display for FontOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.ty)
+        write!(f, "{}", self.ty)?;
+        if self.text.is_some() {
+            write!(f, " (subset)")?;
+        }
+        if let Some(font_face) = &self.font_face {
+            write!(f, " (font-face: {})", font_face.family)?;
+        }
+        Ok(())
     }
 }
 
 impl FontOptions {
     /// Creates a new font options struct
     pub fn new(ty: FontType) -> Self {
-        Self { ty }
+        Self {
+            ty,
+            text: None,
+            font_face: None,
+        }
     }
 
     /// Returns the type of the font
     pub fn ty(&self) -> &FontType {
         &self.ty
     }
+
+    /// Sets the type of the font
+    pub fn set_ty(&mut self, ty: FontType) {
+        self.ty = ty;
+    }
+
+    /// Returns the text the font is subset to
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Sets the text the font is subset to. Only the glyphs needed to render the text will be kept
+    pub fn set_text(&mut self, text: Option<String>) {
+        self.text = text;
+    }
+
+    /// Returns the `@font-face` rule generated for the font
+    pub fn font_face(&self) -> Option<&FontFace> {
+        self.font_face.as_ref()
+    }
+
+    /// Sets the `@font-face` rule generated for the font
+    pub fn set_font_face(&mut self, font_face: Option<FontFace>) {
+        self.font_face = font_face;
+    }
+}
+
+/// A `@font-face` rule that is written to a css file next to a font asset
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct FontFace {
+    /// The font family the font is registered as
+    family: String,
+    /// The [display](https://www.w3.org/TR/css-fonts-4/#font-display-desc) of the font
+    display: Option<String>,
+}
+
+impl FontFace {
+    /// Creates a new font face for the given font family
+    pub fn new(family: impl Into<String>) -> Self {
+        Self {
+            family: family.into(),
+            display: None,
+        }
+    }
+
+    /// Returns the font family
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    /// Returns the display of the font
+    pub fn display(&self) -> Option<&str> {
+        self.display.as_deref()
+    }
+
+    /// Sets the display of the font
+    pub fn set_display(&mut self, display: Option<String>) {
+        self.display = display;
+    }
+
+    /// The name of the css file generated next to the font with the given file name
+    pub fn css_file_name(font_file_name: &str) -> String {
+        format!("{font_file_name}.css")
+    }
 }
 
 /// The type of a font
@@ -380,6 +791,9 @@ impl Display for FontType {
 pub struct CssOptions {
     minify: bool,
     preload: bool,
+    /// Whether remote files the css links to should be downloaded and served with the css
+    #[serde(default)]
+    self_host: bool,
 }
 
 impl Default for CssOptions {
@@ -396,6 +810,9 @@ impl Display for CssOptions {
         if self.preload {
             write!(f, " (preload)")?;
         }
+        if self.self_host {
+            write!(f, " (self-hosted)")?;
+        }
         Ok(())
     }
 }
@@ -408,6 +825,7 @@ impl CssOptions {
         Self {
             minify: true,
             preload: false,
+            self_host: false,
         }
     }
 
@@ -430,6 +848,17 @@ impl CssOptions {
     pub fn set_preload(&mut self, preload: bool) {
         self.preload = preload;
     }
+
+    /// Returns whether remote files the css links to are self-hosted
+    pub fn self_host(&self) -> bool {
+        self.self_host
+    }
+
+    /// Sets whether remote files the css links to should be self-hosted. Each `url(...)` that points to a remote file is
+    /// downloaded as its own asset and the css is rewritten to load it from the served location
+    pub fn set_self_host(&mut self, self_host: bool) {
+        self.self_host = self_host;
+    }
 }
 
 /// The type of a Javascript asset
@@ -546,6 +975,166 @@ impl JsonOptions {
     }
 }
 
+/// The options for an svg asset
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct SvgOptions {
+    minify: bool,
+    /// The number of decimal places coordinates are rounded to when the svg is minified
+    precision: u8,
+    preload: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for SvgOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "svg")?;
+        if self.minify {
+            write!(f, " (minified to {} decimal places)", self.precision)?;
+        }
+        if self.preload {
+            write!(f, " (preload)")?;
+        }
+        Ok(())
+    }
+}
+
+impl SvgOptions {
+    /// The extension of the svg asset
+    pub const EXTENSION: &'static str = "svg";
+
+    /// The default number of decimal places coordinates are rounded to
+    pub const DEFAULT_PRECISION: u8 = 3;
+
+    /// Creates a new svg options struct
+    pub const fn new() -> Self {
+        Self {
+            minify: true,
+            precision: Self::DEFAULT_PRECISION,
+            preload: false,
+        }
+    }
+
+    /// Returns whether the svg should be minified
+    pub fn minify(&self) -> bool {
+        self.minify
+    }
+
+    /// Sets whether the svg should be minified. Minifying removes comments, editor metadata and groups that do nothing, and rounds coordinates to the precision
+    pub fn set_minify(&mut self, minify: bool) {
+        self.minify = minify;
+    }
+
+    /// Returns the number of decimal places coordinates are rounded to
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Sets the number of decimal places coordinates are rounded to when the svg is minified
+    pub fn set_precision(&mut self, precision: u8) {
+        self.precision = precision;
+    }
+
+    /// Returns whether the svg should be preloaded
+    pub fn preload(&self) -> bool {
+        self.preload
+    }
+
+    /// Sets whether the svg should be preloaded
+    pub fn set_preload(&mut self, preload: bool) {
+        self.preload = preload;
+    }
+}
+
+/// What an icon in an icon set is used for
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Clone, Copy, Hash)]
+pub enum IconPurpose {
+    /// The `favicon.ico` file with multiple sizes that browsers show in the tab bar
+    Favicon,
+    /// The icon iOS shows when the page is added to the home screen
+    AppleTouchIcon,
+    /// A png icon that is linked in the head and listed in the web app manifest
+    Icon,
+}
+
+/// The options for an icon in an icon set
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct IconOptions {
+    purpose: IconPurpose,
+    /// The width and height of each image in the icon. Icons are always square
+    sizes: Vec<u32>,
+}
+
+impl Display for IconOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.extension(), self.sizes_attribute())
+    }
+}
+
+impl IconOptions {
+    /// The sizes stored in the favicon. Browsers pick the size that matches the resolution of the screen
+    pub const FAVICON_SIZES: [u32; 3] = [16, 32, 48];
+
+    /// The size of the apple touch icon
+    pub const APPLE_TOUCH_ICON_SIZE: u32 = 180;
+
+    /// The sizes of the png icons. These are the sizes Chrome requires to install a web app
+    pub const ICON_SIZES: [u32; 2] = [192, 512];
+
+    /// Creates a new icon options struct
+    pub fn new(purpose: IconPurpose, sizes: Vec<u32>) -> Self {
+        Self { purpose, sizes }
+    }
+
+    /// Returns the options for every icon in the icon set generated from one image
+    pub fn icon_set() -> Vec<Self> {
+        let mut icons = vec![
+            Self::new(IconPurpose::Favicon, Self::FAVICON_SIZES.to_vec()),
+            Self::new(
+                IconPurpose::AppleTouchIcon,
+                vec![Self::APPLE_TOUCH_ICON_SIZE],
+            ),
+        ];
+        icons.extend(
+            Self::ICON_SIZES
+                .iter()
+                .map(|size| Self::new(IconPurpose::Icon, vec![*size])),
+        );
+        icons
+    }
+
+    /// Returns what the icon is used for
+    pub fn purpose(&self) -> IconPurpose {
+        self.purpose
+    }
+
+    /// Returns the sizes of the images in the icon
+    pub fn sizes(&self) -> &[u32] {
+        &self.sizes
+    }
+
+    /// Returns the sizes of the icon in the format of the html `sizes` attribute. For example: `16x16 32x32`
+    pub fn sizes_attribute(&self) -> String {
+        self.sizes
+            .iter()
+            .map(|size| format!("{size}x{size}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the extension of the icon. The favicon is an ico file and every other icon is a png
+    pub fn extension(&self) -> &'static str {
+        match self.purpose {
+            IconPurpose::Favicon => "ico",
+            IconPurpose::AppleTouchIcon | IconPurpose::Icon => "png",
+        }
+    }
+}
+
 /// The options for an unknown file asset
 #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash)]
 pub struct UnknownFileOptions {
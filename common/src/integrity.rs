@@ -0,0 +1,138 @@
+use std::{fmt::Display, str::FromStr};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// The hash algorithm used in an integrity digest
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum IntegrityAlgorithm {
+    /// The SHA-256 hash algorithm
+    Sha256,
+    /// The SHA-384 hash algorithm
+    Sha384,
+    /// The SHA-512 hash algorithm
+    Sha512,
+}
+
+impl IntegrityAlgorithm {
+    /// Returns the prefix used for this algorithm in an integrity string
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+}
+
+impl Display for IntegrityAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.prefix())
+    }
+}
+
+/// The digest of an asset in the [subresource integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity) format: `sha256-<base64 digest>`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Integrity {
+    algorithm: IntegrityAlgorithm,
+    digest: Vec<u8>,
+}
+
+impl Integrity {
+    /// Computes the integrity of the bytes with the given algorithm
+    pub fn compute(algorithm: IntegrityAlgorithm, bytes: &[u8]) -> Self {
+        let digest = match algorithm {
+            IntegrityAlgorithm::Sha256 => Sha256::digest(bytes).to_vec(),
+            IntegrityAlgorithm::Sha384 => Sha384::digest(bytes).to_vec(),
+            IntegrityAlgorithm::Sha512 => Sha512::digest(bytes).to_vec(),
+        };
+        Self { algorithm, digest }
+    }
+
+    /// Returns the algorithm used to compute the digest
+    pub fn algorithm(&self) -> IntegrityAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the raw digest
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Checks if the bytes match the digest
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        Self::compute(self.algorithm, bytes) == *self
+    }
+}
+
+impl Display for Integrity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digest = base64::engine::general_purpose::STANDARD.encode(&self.digest);
+        write!(f, "{}-{}", self.algorithm, digest)
+    }
+}
+
+impl FromStr for Integrity {
+    type Err = IntegrityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (prefix, digest) = s
+            .split_once('-')
+            .ok_or_else(|| IntegrityError::InvalidFormat(s.to_string()))?;
+        let (algorithm, len) = match prefix {
+            "sha256" => (IntegrityAlgorithm::Sha256, 32),
+            "sha384" => (IntegrityAlgorithm::Sha384, 48),
+            "sha512" => (IntegrityAlgorithm::Sha512, 64),
+            _ => return Err(IntegrityError::UnknownAlgorithm(prefix.to_string())),
+        };
+        let digest = base64::engine::general_purpose::STANDARD
+            .decode(digest)
+            .map_err(|_| IntegrityError::InvalidFormat(s.to_string()))?;
+        if digest.len() != len {
+            return Err(IntegrityError::InvalidFormat(s.to_string()));
+        }
+        Ok(Self { algorithm, digest })
+    }
+}
+
+impl TryFrom<String> for Integrity {
+    type Error = IntegrityError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Integrity> for String {
+    fn from(value: Integrity) -> Self {
+        value.to_string()
+    }
+}
+
+/// An error while parsing an integrity string
+#[derive(Debug)]
+pub enum IntegrityError {
+    /// The hash algorithm is not supported
+    UnknownAlgorithm(String),
+    /// The integrity string is not in the `<algorithm>-<base64 digest>` format
+    InvalidFormat(String),
+}
+
+impl Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownAlgorithm(algorithm) => write!(
+                f,
+                "unknown integrity algorithm `{algorithm}`. Supported algorithms are sha256, sha384, and sha512"
+            ),
+            Self::InvalidFormat(integrity) => write!(
+                f,
+                "`{integrity}` is not a valid integrity. Integrity must be in the format `sha256-<base64 digest>`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
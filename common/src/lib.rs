@@ -6,10 +6,18 @@ mod built;
 pub mod cache;
 mod config;
 mod file;
+mod integrity;
 pub mod linker;
 mod manifest;
+mod orientation;
+mod svg;
+mod video;
 
 pub use asset::*;
 pub use config::*;
 pub use file::*;
+pub use integrity::*;
 pub use manifest::*;
+pub use orientation::*;
+pub use svg::*;
+pub use video::*;
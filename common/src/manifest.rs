@@ -1,15 +1,33 @@
-use crate::AssetType;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{get_mime_from_ext, AssetType, Config, Integrity};
+
+/// The version of the manifest file format. This must be increased whenever the format changes
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// The name of the manifest file that is written next to the collected assets
+pub const MANIFEST_FILE_NAME: &str = "manganis-manifest.json";
 
 /// A manifest of all assets collected from dependencies
 #[derive(Debug, PartialEq, Default, Clone)]
 pub struct AssetManifest {
     pub(crate) assets: Vec<AssetType>,
+    pub(crate) integrity: BTreeMap<String, Integrity>,
 }
 
 impl AssetManifest {
     /// Creates a new asset manifest
     pub fn new(assets: Vec<AssetType>) -> Self {
-        Self { assets }
+        Self {
+            assets,
+            integrity: BTreeMap::new(),
+        }
     }
 
     /// Returns all assets collected from dependencies
@@ -17,38 +35,137 @@ impl AssetManifest {
         &self.assets
     }
 
+    /// Adds an asset to the manifest if it is not already in it.
+    ///
+    /// CLIs use this for assets that are found while processing other assets, like the fonts a self-hosted stylesheet links to
+    pub fn add_asset(&mut self, asset: AssetType) {
+        if !self.assets.contains(&asset) {
+            self.assets.push(asset);
+        }
+    }
+
+    /// Returns the [subresource integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity) of the processed file with the given unique name if it is known
+    pub fn integrity(&self, unique_name: &str) -> Option<&Integrity> {
+        self.integrity.get(unique_name)
+    }
+
+    /// Sets the subresource integrity of the processed file with the given unique name
+    pub fn set_integrity(&mut self, unique_name: impl Into<String>, integrity: Integrity) {
+        self.integrity.insert(unique_name.into(), integrity);
+    }
+
+    /// Creates the on-disk representation of the manifest for a build with the given config. The size of each asset is read from the folder the assets were copied to
+    pub fn to_manifest_file(&self, output_folder: &Path, config: &Config) -> ManifestFile {
+        let assets = self
+            .assets
+            .iter()
+            .map(|asset| {
+                let (served_location, output_size, mime_type, integrity) = match asset {
+                    AssetType::File(file) => (
+                        Some(file.served_location_with_config(config)),
+                        output_size(&output_folder.join(file.location().unique_name())),
+                        Some(get_mime_from_ext(file.options().extension()).to_string()),
+                        self.integrity(file.location().unique_name()).cloned(),
+                    ),
+                    AssetType::Folder(folder) => (
+                        Some(folder.served_location_with_config(config)),
+                        output_size(&output_folder.join(folder.unique_name())),
+                        None,
+                        None,
+                    ),
+                    AssetType::Tailwind(_) | AssetType::Metadata(_) => (None, None, None, None),
+                };
+                ManifestEntry {
+                    asset: asset.clone(),
+                    served_location,
+                    output_size,
+                    mime_type,
+                    integrity,
+                }
+            })
+            .collect();
+
+        ManifestFile {
+            version: MANIFEST_VERSION,
+            assets,
+        }
+    }
+
+    /// Creates a manifest from the on-disk representation of the manifest
+    pub fn from_manifest_file(file: ManifestFile) -> Self {
+        let mut manifest = Self::new(Vec::new());
+        for entry in file.assets {
+            if let (AssetType::File(file), Some(integrity)) = (&entry.asset, entry.integrity) {
+                manifest.set_integrity(file.location().unique_name(), integrity);
+            }
+            manifest.assets.push(entry.asset);
+        }
+        manifest
+    }
+
+    /// Reads a manifest file that was written while collecting assets
+    pub fn read_from(path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| ManifestError::IO(path.to_path_buf(), err))?;
+        Ok(Self::from_manifest_file(ManifestFile::parse(&json)?))
+    }
+
     #[cfg(feature = "html")]
-    /// Returns the HTML that should be injected into the head of the page
-    pub fn head(&self) -> String {
+    /// Returns the HTML that should be injected into the head of the page built with the given config
+    pub fn head(&self, config: &crate::Config) -> String {
         let mut head = String::new();
         for asset in &self.assets {
             if let crate::AssetType::File(file) = asset {
+                let integrity = self.integrity_attributes(file);
                 match file.options() {
                     crate::FileOptions::Css(css_options) => {
                         if css_options.preload() {
-                            if let Ok(asset_path) = file.served_location() {
-                                head.push_str(&format!(
-                                    "<link rel=\"preload\" as=\"style\" href=\"{asset_path}\">\n"
-                                ))
-                            }
+                            let asset_path = file.served_location_with_config(config);
+                            head.push_str(&format!(
+                                "<link rel=\"preload\" as=\"style\" href=\"{asset_path}\"{integrity}>\n"
+                            ))
                         }
                     }
                     crate::FileOptions::Image(image_options) => {
                         if image_options.preload() {
-                            if let Ok(asset_path) = file.served_location() {
-                                head.push_str(&format!(
-                                    "<link rel=\"preload\" as=\"image\" href=\"{asset_path}\">\n"
-                                ))
-                            }
+                            let asset_path = file.served_location_with_config(config);
+                            // Browsers skip preloading images with a type they don't support
+                            let mime_type = get_mime_from_ext(file.options().extension());
+                            head.push_str(&format!(
+                                "<link rel=\"preload\" as=\"image\" href=\"{asset_path}\" type=\"{mime_type}\"{integrity}>\n"
+                            ))
+                        }
+                    }
+                    crate::FileOptions::Svg(svg_options) => {
+                        if svg_options.preload() {
+                            let asset_path = file.served_location_with_config(config);
+                            head.push_str(&format!(
+                                "<link rel=\"preload\" as=\"image\" href=\"{asset_path}\" type=\"image/svg+xml\"{integrity}>\n"
+                            ))
                         }
                     }
+                    crate::FileOptions::Icon(icon_options) => {
+                        let asset_path = file.served_location_with_config(config);
+                        let sizes = icon_options.sizes_attribute();
+                        head.push_str(&match icon_options.purpose() {
+                            crate::IconPurpose::Favicon => format!(
+                                "<link rel=\"icon\" href=\"{asset_path}\" sizes=\"{sizes}\"{integrity}>\n"
+                            ),
+                            crate::IconPurpose::AppleTouchIcon => format!(
+                                "<link rel=\"apple-touch-icon\" href=\"{asset_path}\" sizes=\"{sizes}\"{integrity}>\n"
+                            ),
+                            crate::IconPurpose::Icon => format!(
+                                "<link rel=\"icon\" type=\"image/png\" href=\"{asset_path}\" sizes=\"{sizes}\"{integrity}>\n"
+                            ),
+                        })
+                    }
                     crate::FileOptions::Js(js_options) => {
                         if js_options.preload() {
-                            if let Ok(asset_path) = file.served_location() {
-                                head.push_str(&format!(
-                                    "<link rel=\"preload\" as=\"script\" href=\"{asset_path}\">\n"
-                                ))
-                            }
+                            let asset_path = file.served_location_with_config(config);
+                            head.push_str(&format!(
+                                "<link rel=\"preload\" as=\"script\" href=\"{asset_path}\"{integrity}>\n"
+                            ))
                         }
                     }
                     _ => {}
@@ -57,4 +174,130 @@ impl AssetManifest {
         }
         head
     }
+
+    #[cfg(feature = "html")]
+    /// Returns the integrity and crossorigin attributes for a file if the integrity of the file is known
+    fn integrity_attributes(&self, file: &crate::FileAsset) -> String {
+        // Url encoded files are inlined into the page, so they don't need an integrity check
+        if file.url_encoded() {
+            return String::new();
+        }
+        match self.integrity(file.location().unique_name()) {
+            Some(integrity) => format!(" integrity=\"{integrity}\" crossorigin=\"anonymous\""),
+            None => String::new(),
+        }
+    }
+}
+
+/// Returns the size of a file or the total size of all files in a folder
+fn output_size(path: &Path) -> Option<u64> {
+    let metadata = path.metadata().ok()?;
+    if !metadata.is_dir() {
+        return Some(metadata.len());
+    }
+    let mut size = 0;
+    for entry in std::fs::read_dir(path).ok()?.flatten() {
+        size += output_size(&entry.path())?;
+    }
+    Some(size)
+}
+
+/// The on-disk representation of an [`AssetManifest`]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ManifestFile {
+    version: u32,
+    assets: Vec<ManifestEntry>,
+}
+
+impl ManifestFile {
+    /// Parses a manifest file, rejecting manifests written with a different version of the format
+    pub fn parse(json: &str) -> Result<Self, ManifestError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+
+        let versioned: Versioned = serde_json::from_str(json).map_err(ManifestError::Parse)?;
+        if versioned.version != MANIFEST_VERSION {
+            return Err(ManifestError::UnsupportedVersion(versioned.version));
+        }
+        serde_json::from_str(json).map_err(ManifestError::Parse)
+    }
+
+    /// Returns the version of the format the manifest was written with
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns all entries in the manifest
+    pub fn assets(&self) -> &[ManifestEntry] {
+        &self.assets
+    }
+}
+
+/// An asset recorded in a [`ManifestFile`]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ManifestEntry {
+    asset: AssetType,
+    served_location: Option<String>,
+    output_size: Option<u64>,
+    mime_type: Option<String>,
+    integrity: Option<Integrity>,
+}
+
+impl ManifestEntry {
+    /// Returns the asset, including its source, options and unique name
+    pub fn asset(&self) -> &AssetType {
+        &self.asset
+    }
+
+    /// Returns the location the asset is served from
+    pub fn served_location(&self) -> Option<&str> {
+        self.served_location.as_deref()
+    }
+
+    /// Returns the size of the processed asset in bytes
+    pub fn output_size(&self) -> Option<u64> {
+        self.output_size
+    }
+
+    /// Returns the mime type of the processed asset
+    pub fn mime_type(&self) -> Option<&str> {
+        self.mime_type.as_deref()
+    }
+
+    /// Returns the subresource integrity of the processed asset
+    pub fn integrity(&self) -> Option<&Integrity> {
+        self.integrity.as_ref()
+    }
+}
+
+/// An error while reading a manifest file
+#[derive(Debug)]
+pub enum ManifestError {
+    /// The manifest file could not be read
+    IO(PathBuf, std::io::Error),
+    /// The manifest file is not valid JSON or doesn't match the format
+    Parse(serde_json::Error),
+    /// The manifest file was written with a different version of the format
+    UnsupportedVersion(u32),
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IO(path, err) => write!(f, "failed to read the asset manifest at `{}`: {}", path.display(), err),
+            Self::Parse(err) => write!(f, "failed to parse the asset manifest: {}", err),
+            Self::UnsupportedVersion(version) if *version < MANIFEST_VERSION => write!(
+                f,
+                "the asset manifest was written with version {version} of the format, but this version of manganis only reads version {MANIFEST_VERSION}. Collect the assets again to regenerate the manifest."
+            ),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "the asset manifest was written with version {version} of the format, but this version of manganis only reads version {MANIFEST_VERSION}. Update manganis to read this manifest."
+            ),
+        }
+    }
 }
+
+impl std::error::Error for ManifestError {}
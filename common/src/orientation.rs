@@ -0,0 +1,167 @@
+/// How the pixels of an image must be transformed to show the image upright. Cameras store the orientation in the EXIF metadata instead of rotating the pixels
+#[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Copy, Hash, Default)]
+pub enum ImageOrientation {
+    /// The image is already upright
+    #[default]
+    Normal,
+    /// The image is mirrored horizontally
+    FlipHorizontal,
+    /// The image is rotated 180 degrees
+    Rotate180,
+    /// The image is mirrored vertically
+    FlipVertical,
+    /// The image is mirrored along the diagonal from the top left to the bottom right
+    Transpose,
+    /// The image is rotated 90 degrees clockwise
+    Rotate90,
+    /// The image is mirrored along the diagonal from the top right to the bottom left
+    Transverse,
+    /// The image is rotated 270 degrees clockwise
+    Rotate270,
+}
+
+impl ImageOrientation {
+    /// Reads the orientation from the EXIF metadata of a jpg, png or webp image. Images without an orientation are upright
+    pub fn read(image: &[u8]) -> Self {
+        let exif = if image.starts_with(&[0xFF, 0xD8]) {
+            jpg_exif(image)
+        } else if image.starts_with(b"\x89PNG\r\n\x1a\n") {
+            png_exif(image)
+        } else if image.starts_with(b"RIFF") && image.get(8..12) == Some(&b"WEBP"[..]) {
+            webp_exif(image)
+        } else {
+            None
+        };
+        exif.and_then(exif_orientation)
+            .and_then(Self::from_exif)
+            .unwrap_or_default()
+    }
+
+    /// Returns the orientation for the value of the EXIF orientation tag
+    pub fn from_exif(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Self::Normal),
+            2 => Some(Self::FlipHorizontal),
+            3 => Some(Self::Rotate180),
+            4 => Some(Self::FlipVertical),
+            5 => Some(Self::Transpose),
+            6 => Some(Self::Rotate90),
+            7 => Some(Self::Transverse),
+            8 => Some(Self::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the width and height of the image are swapped when it is shown upright
+    pub fn swaps_dimensions(&self) -> bool {
+        matches!(
+            self,
+            Self::Transpose | Self::Rotate90 | Self::Transverse | Self::Rotate270
+        )
+    }
+
+    /// Returns the size of an image with the stored size when it is shown upright
+    pub fn upright_size(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        if self.swaps_dimensions() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+}
+
+/// Finds the EXIF data in the APP1 segment of a jpg
+fn jpg_exif(image: &[u8]) -> Option<&[u8]> {
+    let mut offset = 2;
+    loop {
+        // Markers may be padded with any number of fill bytes
+        while image.get(offset) == Some(&0xFF) && image.get(offset + 1) == Some(&0xFF) {
+            offset += 1;
+        }
+        if *image.get(offset)? != 0xFF {
+            return None;
+        }
+        let marker = *image.get(offset + 1)?;
+        // The metadata is always before the start of the scan
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes(image.get(offset + 2..offset + 4)?.try_into().ok()?);
+        let segment = image.get(offset + 4..offset + 2 + length as usize)?;
+        if marker == 0xE1 {
+            if let Some(exif) = segment.strip_prefix(b"Exif\0\0") {
+                return Some(exif);
+            }
+        }
+        offset += 2 + length as usize;
+    }
+}
+
+/// Finds the EXIF data in the eXIf chunk of a png
+fn png_exif(image: &[u8]) -> Option<&[u8]> {
+    let mut offset = 8;
+    loop {
+        let length = u32::from_be_bytes(image.get(offset..offset + 4)?.try_into().ok()?) as usize;
+        let ty = image.get(offset + 4..offset + 8)?;
+        // The metadata is always before the image data
+        if ty == b"IDAT" {
+            return None;
+        }
+        if ty == b"eXIf" {
+            return image.get(offset + 8..offset + 8 + length);
+        }
+        // Each chunk has a length, type, data and crc
+        offset += 12 + length;
+    }
+}
+
+/// Finds the EXIF data in the EXIF chunk of a webp
+fn webp_exif(image: &[u8]) -> Option<&[u8]> {
+    let mut offset = 12;
+    loop {
+        let ty = image.get(offset..offset + 4)?;
+        let length =
+            u32::from_le_bytes(image.get(offset + 4..offset + 8)?.try_into().ok()?) as usize;
+        if ty == b"EXIF" {
+            let exif = image.get(offset + 8..offset + 8 + length)?;
+            // Some encoders keep the prefix from the jpg segment
+            return Some(exif.strip_prefix(b"Exif\0\0").unwrap_or(exif));
+        }
+        // Chunks are padded to an even length
+        offset += 8 + length + (length & 1);
+    }
+}
+
+/// Reads the orientation tag from the first directory of the EXIF data
+fn exif_orientation(exif: &[u8]) -> Option<u16> {
+    let little_endian = match exif.get(0..4)? {
+        b"II*\0" => true,
+        b"MM\0*" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        let bytes = exif.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| {
+        let bytes = exif.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    const ORIENTATION_TAG: u16 = 0x0112;
+    let directory = u32_at(4)? as usize;
+    let entries = u16_at(directory)?;
+    (0..entries as usize)
+        .map(|index| directory + 2 + index * 12)
+        .find(|entry| u16_at(*entry) == Some(ORIENTATION_TAG))
+        // The orientation is a single short stored at the start of the value
+        .and_then(|entry| u16_at(entry + 8))
+}
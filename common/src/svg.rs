@@ -0,0 +1,112 @@
+/// The size of an svg without a width, height or viewBox
+const DEFAULT_SVG_SIZE: f32 = 100.;
+
+/// Returns whether the image is an svg document instead of a raster image
+pub fn is_svg(image: &[u8]) -> bool {
+    svg_root_attributes(image).is_some()
+}
+
+/// Reads the size an svg is rasterized at from the width, height and viewBox of the root element
+///
+/// Percentages are relative to the viewBox and absolute units are converted to pixels at 96 dpi
+pub fn svg_size(image: &[u8]) -> Option<(u32, u32)> {
+    let attributes = svg_root_attributes(image)?;
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.trim())
+    };
+    let view_box = attribute("viewBox").and_then(parse_view_box);
+
+    let resolve = |length: Option<&str>, view_box_length: Option<f32>| {
+        // Missing lengths fill the viewBox
+        let (number, unit) = length.and_then(parse_length).unwrap_or((100., "%"));
+        let pixels = match unit {
+            "%" => view_box_length.unwrap_or(DEFAULT_SVG_SIZE) * number / 100.,
+            "" | "px" => number,
+            "in" => number * 96.,
+            "cm" => number * 96. / 2.54,
+            "mm" => number * 96. / 25.4,
+            "pt" => number * 96. / 72.,
+            "pc" => number * 96. / 6.,
+            // Font relative units use the default font size
+            "em" => number * 12.,
+            "ex" => number * 6.,
+            _ => return None,
+        };
+        (pixels > 0.).then(|| (pixels.round() as u32).max(1))
+    };
+    Some((
+        resolve(attribute("width"), view_box.map(|(width, _)| width))?,
+        resolve(attribute("height"), view_box.map(|(_, height)| height))?,
+    ))
+}
+
+/// Splits a length into the number and the unit
+fn parse_length(length: &str) -> Option<(f32, &str)> {
+    let unit_start = length
+        .find(|char: char| char.is_ascii_alphabetic() || char == '%')
+        .unwrap_or(length.len());
+    let number = length[..unit_start].trim().parse().ok()?;
+    Some((number, length[unit_start..].trim()))
+}
+
+/// Returns the width and height of the viewBox
+fn parse_view_box(view_box: &str) -> Option<(f32, f32)> {
+    let numbers: Vec<f32> = view_box
+        .split(|char: char| char.is_ascii_whitespace() || char == ',')
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse().ok())
+        .collect::<Option<_>>()?;
+    match numbers[..] {
+        [_, _, width, height] if width > 0. && height > 0. => Some((width, height)),
+        _ => None,
+    }
+}
+
+/// Finds the root `<svg>` element and returns its attributes. Returns `None` if the document doesn't start with an svg element
+fn svg_root_attributes(image: &[u8]) -> Option<Vec<(&str, &str)>> {
+    let text = std::str::from_utf8(image).ok()?;
+    let mut rest = text.trim_start_matches('\u{feff}');
+    // Skip the xml declaration, comments and doctype before the root element
+    loop {
+        rest = rest.trim_start();
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = &comment[comment.find("-->")? + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[rest.find('>')? + 1..];
+        } else {
+            break;
+        }
+    }
+
+    let tag = rest.strip_prefix('<')?;
+    let name_end = tag
+        .find(|char: char| char.is_ascii_whitespace() || char == '>' || char == '/')
+        .unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name != "svg" && !name.ends_with(":svg") {
+        return None;
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = &tag[name_end..];
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() || rest.starts_with('>') || rest.starts_with('/') {
+            return Some(attributes);
+        }
+        let equals = rest.find('=')?;
+        let name = rest[..equals].trim();
+        let value = rest[equals + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|quote| *quote == '"' || *quote == '\'')?;
+        let value = &value[1..];
+        let end = value.find(quote)?;
+        attributes.push((name, &value[..end]));
+        rest = &value[end + 1..];
+    }
+}
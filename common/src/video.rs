@@ -0,0 +1,482 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::VideoType;
+
+/// Information about a video that is read from its container when the asset is collected
+#[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone, Hash, Default)]
+pub struct VideoMetadata {
+    duration: Option<Duration>,
+    width: Option<u32>,
+    height: Option<u32>,
+    codecs: Vec<String>,
+}
+
+impl VideoMetadata {
+    /// Reads the metadata from the container of a video. Any information that can't be read from the container is left empty
+    pub fn read(ty: &VideoType, bytes: &[u8]) -> Self {
+        let mut metadata = Self::default();
+        match ty {
+            // QuickTime videos are made of the same boxes as mp4 videos
+            VideoType::MP4 | VideoType::MOV => mp4::read(bytes, &mut metadata),
+            VideoType::Webm => webm::read(bytes, &mut metadata),
+            VideoType::GIF => gif::read(bytes, &mut metadata),
+        };
+        metadata
+    }
+
+    /// Returns the duration of the video
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Sets the duration of the video
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration = duration;
+    }
+
+    /// Returns the width the video is displayed at
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    /// Returns the height the video is displayed at
+    pub fn height(&self) -> Option<u32> {
+        self.height
+    }
+
+    /// Sets the size the video is displayed at
+    pub fn set_size(&mut self, size: Option<(u32, u32)>) {
+        self.width = size.map(|(width, _)| width);
+        self.height = size.map(|(_, height)| height);
+    }
+
+    /// Returns the [codec strings](https://developer.mozilla.org/en-US/docs/Web/Media/Formats/codecs_parameter) of every track in the video
+    pub fn codecs(&self) -> &[String] {
+        &self.codecs
+    }
+
+    /// Sets the codec strings of the video
+    pub fn set_codecs(&mut self, codecs: Vec<String>) {
+        self.codecs = codecs;
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        bytes.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+mod mp4 {
+    use super::*;
+
+    /// Iterates over the boxes in the contents of a box
+    fn boxes(bytes: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+        let mut remaining = bytes;
+        std::iter::from_fn(move || {
+            let size = read_u32(remaining, 0)? as u64;
+            let kind: [u8; 4] = remaining.get(4..8)?.try_into().ok()?;
+            let (size, header_len) = match size {
+                0 => (remaining.len() as u64, 8),
+                1 => (read_u64(remaining, 8)?, 16),
+                size => (size, 8),
+            };
+            let size = usize::try_from(size).ok()?;
+            let contents = remaining.get(header_len..size)?;
+            remaining = &remaining[size..];
+            Some((kind, contents))
+        })
+    }
+
+    fn child<'a>(bytes: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+        boxes(bytes)
+            .find(|(child, _)| child == kind)
+            .map(|(_, contents)| contents)
+    }
+
+    pub(super) fn read(bytes: &[u8], metadata: &mut VideoMetadata) {
+        let Some(moov) = child(bytes, b"moov") else {
+            return;
+        };
+
+        if let Some(mvhd) = child(moov, b"mvhd") {
+            let (timescale, duration) = match mvhd.first() {
+                Some(1) => (read_u32(mvhd, 20), read_u64(mvhd, 24)),
+                _ => (read_u32(mvhd, 12), read_u32(mvhd, 16).map(u64::from)),
+            };
+            if let (Some(timescale @ 1..), Some(duration)) = (timescale, duration) {
+                metadata.duration =
+                    Some(Duration::from_secs_f64(duration as f64 / timescale as f64));
+            }
+        }
+
+        for (kind, trak) in boxes(moov) {
+            if &kind != b"trak" {
+                continue;
+            }
+            // The display size is stored as 16.16 fixed point numbers at the end of the track header
+            if let Some(tkhd) = child(trak, b"tkhd") {
+                let width = tkhd.len().checked_sub(8).and_then(|at| read_u32(tkhd, at));
+                let height = tkhd.len().checked_sub(4).and_then(|at| read_u32(tkhd, at));
+                if let (Some(width), Some(height), None) = (width, height, metadata.width) {
+                    if width > 0 && height > 0 {
+                        metadata.set_size(Some((width >> 16, height >> 16)));
+                    }
+                }
+            }
+
+            let stsd = child(trak, b"mdia")
+                .and_then(|mdia| child(mdia, b"minf"))
+                .and_then(|minf| child(minf, b"stbl"))
+                .and_then(|stbl| child(stbl, b"stsd"));
+            // Skip the version, flags and entry count
+            if let Some((kind, entry)) = stsd.and_then(|stsd| boxes(stsd.get(8..)?).next()) {
+                metadata.codecs.push(codec(kind, entry));
+            }
+        }
+    }
+
+    /// Creates the codec string for a sample entry
+    fn codec(kind: [u8; 4], entry: &[u8]) -> String {
+        let fourcc = String::from_utf8_lossy(&kind).to_string();
+        // Visual sample entries have 78 bytes of fields before the child boxes, audio sample entries have 28
+        let children = |offset: usize| entry.get(offset..).unwrap_or_default();
+        let codec = match &kind {
+            b"avc1" | b"avc3" => child(children(78), b"avcC").and_then(|avcc| {
+                let [profile, compatibility, level] = avcc.get(1..4)?.try_into().ok()?;
+                Some(format!(
+                    "{fourcc}.{profile:02x}{compatibility:02x}{level:02x}"
+                ))
+            }),
+            b"hvc1" | b"hev1" => child(children(78), b"hvcC").and_then(|hvcc| hevc(&fourcc, hvcc)),
+            b"av01" => child(children(78), b"av1C").and_then(|av1c| {
+                let [_, profile_level, flags] = av1c.get(0..3)?.try_into().ok()?;
+                let tier = if flags & 0x80 != 0 { 'H' } else { 'M' };
+                let bit_depth = match (flags & 0x40 != 0, flags & 0x20 != 0) {
+                    (true, true) => 12,
+                    (true, false) => 10,
+                    _ => 8,
+                };
+                Some(format!(
+                    "av01.{}.{:02}{tier}.{bit_depth:02}",
+                    profile_level >> 5,
+                    profile_level & 0x1f
+                ))
+            }),
+            b"vp08" | b"vp09" => child(children(78), b"vpcC").and_then(|vpcc| {
+                // Skip the version and flags
+                let [profile, level, bit_depth] = vpcc.get(4..7)?.try_into().ok()?;
+                Some(format!(
+                    "{fourcc}.{profile:02}.{level:02}.{:02}",
+                    bit_depth >> 4
+                ))
+            }),
+            b"mp4a" => child(children(28), b"esds").and_then(|esds| mp4a(esds.get(4..)?)),
+            _ => None,
+        };
+        codec.unwrap_or(fourcc)
+    }
+
+    /// Creates the codec string for an HEVC video as described in ISO/IEC 14496-15 Annex E
+    fn hevc(fourcc: &str, hvcc: &[u8]) -> Option<String> {
+        let profile = *hvcc.get(1)?;
+        let profile_space = match profile >> 6 {
+            1 => "A",
+            2 => "B",
+            3 => "C",
+            _ => "",
+        };
+        let tier = if profile & 0x20 != 0 { 'H' } else { 'L' };
+        let compatibility = read_u32(hvcc, 2)?.reverse_bits();
+        let constraints = hvcc.get(6..12)?;
+        let level = *hvcc.get(12)?;
+        let mut codec = format!(
+            "{fourcc}.{profile_space}{}.{compatibility:x}.{tier}{level}",
+            profile & 0x1f
+        );
+        let used_constraints = constraints
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |last| last + 1);
+        for byte in &constraints[..used_constraints] {
+            codec.push_str(&format!(".{byte:x}"));
+        }
+        Some(codec)
+    }
+
+    /// Creates the codec string for an MPEG-4 audio track from its elementary stream descriptor
+    fn mp4a(mut descriptors: &[u8]) -> Option<String> {
+        let mut object_type = None;
+        loop {
+            let tag = *descriptors.first()?;
+            // The length is stored in 7 bit chunks with the high bit set if more chunks follow
+            let mut len = 0usize;
+            let mut header_len = 1;
+            loop {
+                let byte = *descriptors.get(header_len)?;
+                header_len += 1;
+                len = (len << 7) | (byte & 0x7f) as usize;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            let contents = descriptors.get(header_len..header_len + len)?;
+            match tag {
+                // The elementary stream descriptor contains the other descriptors after its header
+                0x03 => {
+                    let flags = *contents.get(2)?;
+                    let mut skip = 3;
+                    if flags & 0x80 != 0 {
+                        skip += 2;
+                    }
+                    if flags & 0x40 != 0 {
+                        skip += 1 + *contents.get(skip)? as usize;
+                    }
+                    if flags & 0x20 != 0 {
+                        skip += 2;
+                    }
+                    descriptors = contents.get(skip..)?;
+                    continue;
+                }
+                // The decoder config descriptor contains the object type and the decoder specific info
+                0x04 => {
+                    object_type = Some(*contents.first()?);
+                    descriptors = contents.get(13..)?;
+                    continue;
+                }
+                // The first 5 bits of the decoder specific info are the audio object type
+                0x05 => {
+                    let audio_object_type = contents.first()? >> 3;
+                    return Some(format!("mp4a.{:x}.{audio_object_type}", object_type?));
+                }
+                _ => descriptors = descriptors.get(header_len + len..)?,
+            }
+        }
+    }
+}
+
+mod webm {
+    use super::*;
+
+    const SEGMENT: u32 = 0x18538067;
+    const INFO: u32 = 0x1549A966;
+    const TIMECODE_SCALE: u32 = 0x2AD7B1;
+    const DURATION: u32 = 0x4489;
+    const TRACKS: u32 = 0x1654AE6B;
+    const TRACK_ENTRY: u32 = 0xAE;
+    const CODEC_ID: u32 = 0x86;
+    const VIDEO: u32 = 0xE0;
+    const PIXEL_WIDTH: u32 = 0xB0;
+    const PIXEL_HEIGHT: u32 = 0xBA;
+    const DISPLAY_WIDTH: u32 = 0x54B0;
+    const DISPLAY_HEIGHT: u32 = 0x54BA;
+    const CLUSTER: u32 = 0x1F43B675;
+
+    /// Reads a variable length integer. Element ids keep the length marker, sizes don't
+    fn read_vint(bytes: &[u8], keep_marker: bool) -> Option<(u64, usize)> {
+        let first = *bytes.first()?;
+        let len = first.leading_zeros() as usize + 1;
+        if len > 8 {
+            return None;
+        }
+        let mut value = if keep_marker {
+            first as u64
+        } else {
+            (first & (0xff >> len)) as u64
+        };
+        for byte in bytes.get(1..len)? {
+            value = (value << 8) | *byte as u64;
+        }
+        // A size with every bit set means the size is unknown
+        if !keep_marker && value == (1 << (7 * len)) - 1 {
+            value = u64::MAX;
+        }
+        Some((value, len))
+    }
+
+    /// Iterates over the elements in the contents of a master element
+    fn elements(bytes: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+        let mut remaining = bytes;
+        std::iter::from_fn(move || {
+            let (id, id_len) = read_vint(remaining, true)?;
+            let (size, size_len) = read_vint(remaining.get(id_len..)?, false)?;
+            let start = id_len + size_len;
+            // Elements with an unknown size extend to the end of their parent
+            let end = usize::try_from(size)
+                .ok()
+                .and_then(|size| start.checked_add(size))
+                .unwrap_or(remaining.len())
+                .min(remaining.len());
+            let contents = remaining.get(start..end)?;
+            remaining = &remaining[end..];
+            Some((id as u32, contents))
+        })
+    }
+
+    fn read_uint(bytes: &[u8]) -> u64 {
+        bytes
+            .iter()
+            .fold(0, |value, byte| (value << 8) | *byte as u64)
+    }
+
+    fn read_float(bytes: &[u8]) -> Option<f64> {
+        match bytes.len() {
+            4 => Some(f32::from_be_bytes(bytes.try_into().ok()?) as f64),
+            8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+            _ => None,
+        }
+    }
+
+    pub(super) fn read(bytes: &[u8], metadata: &mut VideoMetadata) {
+        let Some((_, segment)) = elements(bytes).find(|(id, _)| *id == SEGMENT) else {
+            return;
+        };
+
+        for (id, element) in elements(segment) {
+            match id {
+                INFO => {
+                    let mut timecode_scale = 1_000_000;
+                    let mut duration = None;
+                    for (id, element) in elements(element) {
+                        match id {
+                            TIMECODE_SCALE => timecode_scale = read_uint(element),
+                            DURATION => duration = read_float(element),
+                            _ => {}
+                        }
+                    }
+                    if let Some(duration) = duration {
+                        metadata.duration = Some(Duration::from_nanos(
+                            (duration * timecode_scale as f64) as u64,
+                        ));
+                    }
+                }
+                TRACKS => {
+                    for (id, track) in elements(element) {
+                        if id == TRACK_ENTRY {
+                            read_track(track, metadata);
+                        }
+                    }
+                }
+                // The rest of the file is the media data
+                CLUSTER => break,
+                _ => {}
+            }
+        }
+    }
+
+    fn read_track(track: &[u8], metadata: &mut VideoMetadata) {
+        for (id, element) in elements(track) {
+            match id {
+                CODEC_ID => {
+                    let codec_id = String::from_utf8_lossy(element);
+                    let codec = match codec_id.trim_end_matches('\0') {
+                        "V_VP8" => "vp8".to_string(),
+                        "V_VP9" => "vp9".to_string(),
+                        "V_AV1" => "av01".to_string(),
+                        "A_OPUS" => "opus".to_string(),
+                        "A_VORBIS" => "vorbis".to_string(),
+                        other => other.to_lowercase(),
+                    };
+                    metadata.codecs.push(codec);
+                }
+                VIDEO if metadata.width.is_none() => {
+                    let mut pixel_size = (None, None);
+                    let mut display_size = (None, None);
+                    for (id, element) in elements(element) {
+                        let value = Some(read_uint(element) as u32);
+                        match id {
+                            PIXEL_WIDTH => pixel_size.0 = value,
+                            PIXEL_HEIGHT => pixel_size.1 = value,
+                            DISPLAY_WIDTH => display_size.0 = value,
+                            DISPLAY_HEIGHT => display_size.1 = value,
+                            _ => {}
+                        }
+                    }
+                    match (display_size, pixel_size) {
+                        ((Some(width), Some(height)), _) | (_, (Some(width), Some(height))) => {
+                            metadata.set_size(Some((width, height)))
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+mod gif {
+    use super::*;
+
+    pub(super) fn read(bytes: &[u8], metadata: &mut VideoMetadata) {
+        if !bytes.starts_with(b"GIF") {
+            return;
+        }
+        let (Some(width), Some(height)) = (read_le_u16(bytes, 6), read_le_u16(bytes, 8)) else {
+            return;
+        };
+        metadata.set_size(Some((width as u32, height as u32)));
+        metadata.duration = duration(bytes);
+    }
+
+    /// Adds up the delay of every frame in the gif
+    fn duration(bytes: &[u8]) -> Option<Duration> {
+        let mut delay = 0u64;
+        let flags = *bytes.get(10)?;
+        let mut offset = 13 + color_table_len(flags);
+        loop {
+            match *bytes.get(offset)? {
+                // Extension
+                0x21 => {
+                    // The graphic control extension stores the delay of the next frame in hundredths of a second
+                    if *bytes.get(offset + 1)? == 0xF9 {
+                        delay += read_le_u16(bytes, offset + 4)? as u64;
+                    }
+                    offset = skip_sub_blocks(bytes, offset + 2)?;
+                }
+                // Image
+                0x2C => {
+                    let flags = *bytes.get(offset + 9)?;
+                    // Skip the image descriptor, color table and the lzw code size
+                    offset = skip_sub_blocks(bytes, offset + 11 + color_table_len(flags))?;
+                }
+                // Trailer
+                0x3B => break,
+                _ => return None,
+            }
+        }
+        Some(Duration::from_millis(delay * 10))
+    }
+
+    /// Unlike mp4 and webm, gif stores numbers in little endian
+    fn read_le_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+        Some(u16::from_le_bytes(
+            bytes.get(offset..offset + 2)?.try_into().ok()?,
+        ))
+    }
+
+    fn color_table_len(flags: u8) -> usize {
+        if flags & 0x80 != 0 {
+            3 * (1 << ((flags & 0x07) + 1))
+        } else {
+            0
+        }
+    }
+
+    fn skip_sub_blocks(bytes: &[u8], mut offset: usize) -> Option<usize> {
+        loop {
+            let len = *bytes.get(offset)? as usize;
+            offset += 1 + len;
+            if len == 0 {
+                return Some(offset);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use manganis_common::{AssetSource, Config, FileAsset, ImageType};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Set in the child processes spawned by the tests
+const CHILD_ENV_VAR: &str = "MANGANIS_CONFIG_TEST_CHILD";
+
+#[test]
+fn concurrent_builds_use_their_own_config() {
+    if std::env::var(CHILD_ENV_VAR).is_ok() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let asset = FileAsset::new(AssetSource::Local(path)).unwrap();
+        println!("served location: {}", asset.served_location().unwrap());
+        return;
+    }
+
+    // Each child process acts like a separate build running at the same time
+    let serve_locations = ["/first/", "/second/"];
+    let children: Vec<_> = serve_locations
+        .iter()
+        .map(|serve_location| {
+            let mut command = Command::new(std::env::current_exe().unwrap());
+            Config::default()
+                .with_assets_serve_location(*serve_location)
+                .apply_to_command(&mut command)
+                .args(["concurrent_builds_use_their_own_config", "--exact"])
+                .arg("--nocapture")
+                .env(CHILD_ENV_VAR, "true")
+                .env(manganis_common::SUPPORT_ENV_VAR, "true")
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    for (serve_location, child) in serve_locations.iter().zip(children) {
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains(&format!("served location: {serve_location}")),
+            "{stdout}"
+        );
+    }
+}
+
+#[test]
+fn require_alt_is_kept_when_changing_the_serve_location() {
+    let config = Config::default().with_require_alt(true);
+    assert!(config.require_alt());
+    assert!(config.with_assets_serve_location("/assets/").require_alt());
+    assert!(!Config::default().require_alt());
+}
+
+#[test]
+fn auto_image_settings_round_trip() {
+    let config = Config::default()
+        .with_auto_image_formats([ImageType::Webp, ImageType::Png])
+        .with_auto_image_quality(60)
+        .with_require_alt(true);
+    assert_eq!(
+        config.auto_image_formats(),
+        &[ImageType::Webp, ImageType::Png]
+    );
+    assert_eq!(config.auto_image_quality(), 60);
+
+    let mut command = Command::new("cargo");
+    config.apply_to_command(&mut command);
+    let (_, value) = command
+        .get_envs()
+        .find(|(key, _)| *key == manganis_common::CONFIG_ENV_VAR)
+        .unwrap();
+    let parsed: Config = toml::from_str(value.unwrap().to_str().unwrap()).unwrap();
+    assert_eq!(parsed, config);
+
+    // Configs saved before the auto image settings existed use the defaults
+    let old: Config = toml::from_str("assets_serve_location = \"/\"").unwrap();
+    assert_eq!(
+        old.auto_image_formats(),
+        Config::default().auto_image_formats()
+    );
+    assert_eq!(old.auto_image_quality(), 80);
+}
@@ -0,0 +1,47 @@
+use manganis_common::{data_url, FileOptions};
+
+#[test]
+fn svgs_are_percent_encoded() {
+    let svg =
+        r##"<svg xmlns="http://www.w3.org/2000/svg"><path fill="#fff" d="M0 0h1v1z"/></svg>"##;
+    let url = data_url("image/svg+xml", svg.as_bytes());
+    assert_eq!(
+        url,
+        "data:image/svg+xml,%3Csvg%20xmlns='http://www.w3.org/2000/svg'%3E%3Cpath%20fill='%23fff'%20d='M0%200h1v1z'/%3E%3C/svg%3E"
+    );
+    // The url can be put in a quoted html attribute or css url
+    assert!(!url.contains(['"', '<', '>', '#', ' ']));
+}
+
+#[test]
+fn double_quotes_are_kept_if_the_svg_has_single_quotes() {
+    let svg = r#"<svg><text title="it's">é</text></svg>"#;
+    let url = data_url("image/svg+xml", svg.as_bytes());
+    assert_eq!(
+        url,
+        "data:image/svg+xml,%3Csvg%3E%3Ctext%20title=%22it's%22%3E%C3%A9%3C/text%3E%3C/svg%3E"
+    );
+}
+
+#[test]
+fn other_files_are_base64_encoded() {
+    assert_eq!(
+        data_url("application/json", b"{}"),
+        "data:application/json;base64,e30"
+    );
+    // Svgs that aren't valid utf-8 can't be percent encoded as text
+    assert_eq!(
+        data_url("image/svg+xml", &[0xFF]),
+        "data:image/svg+xml;base64,/w"
+    );
+}
+
+#[test]
+fn svg_files_are_copied_by_default() {
+    // Only svgs collected with the svg builder are optimized
+    for extension in ["svg", "SVG"] {
+        let options = FileOptions::default_for_extension(Some(extension));
+        assert!(matches!(options, FileOptions::Other(_)), "{options:?}");
+        assert_eq!(options.extension(), Some(extension));
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "html")]
+
+use manganis_common::{
+    AssetManifest, AssetSource, AssetType, Config, FileAsset, FileOptions, IconOptions,
+    ImageOptions, ImageType, SvgOptions,
+};
+use std::path::PathBuf;
+
+fn image(ty: ImageType, preload: bool) -> AssetType {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let mut options = ImageOptions::new(ty, None);
+    options.set_preload(preload);
+    AssetType::File(
+        FileAsset::new(AssetSource::Local(path))
+            .unwrap()
+            .with_options(FileOptions::Image(options)),
+    )
+}
+
+#[test]
+fn preloaded_images_include_their_type() {
+    // Only the preferred format of an image with multiple formats is preloaded
+    let manifest = AssetManifest::new(vec![
+        image(ImageType::Avif, true),
+        image(ImageType::Png, false),
+    ]);
+    let head = manifest.head(&Config::default());
+    assert_eq!(head.matches("<link rel=\"preload\"").count(), 1, "{head}");
+    assert!(head.contains("as=\"image\""), "{head}");
+    assert!(head.contains("type=\"image/avif\""), "{head}");
+}
+
+#[test]
+fn preloaded_svgs_are_images() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let mut options = SvgOptions::new();
+    options.set_preload(true);
+    let svg = FileAsset::new(AssetSource::Local(path))
+        .unwrap()
+        .with_options(FileOptions::Svg(options));
+    let head = AssetManifest::new(vec![AssetType::File(svg)]).head(&Config::default());
+    assert!(head.contains("as=\"image\""), "{head}");
+    assert!(head.contains("type=\"image/svg+xml\""), "{head}");
+}
+
+#[test]
+fn icons_are_linked() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let icons = IconOptions::icon_set()
+        .into_iter()
+        .map(|options| {
+            AssetType::File(
+                FileAsset::new(AssetSource::Local(path.clone()))
+                    .unwrap()
+                    .with_options(FileOptions::Icon(options)),
+            )
+        })
+        .collect();
+    let head = AssetManifest::new(icons).head(&Config::default());
+    let links: Vec<_> = head.lines().collect();
+    assert_eq!(links.len(), 4, "{head}");
+    assert!(links[0].starts_with("<link rel=\"icon\" href=\""), "{head}");
+    assert!(
+        links[0].contains(".ico\" sizes=\"16x16 32x32 48x48\""),
+        "{head}"
+    );
+    assert!(
+        links[1].starts_with("<link rel=\"apple-touch-icon\""),
+        "{head}"
+    );
+    assert!(links[1].contains("sizes=\"180x180\""), "{head}");
+    assert!(links[2].contains("type=\"image/png\""), "{head}");
+    assert!(links[2].contains("sizes=\"192x192\""), "{head}");
+    assert!(links[3].contains("sizes=\"512x512\""), "{head}");
+}
+
+#[test]
+fn links_use_the_serve_location_of_the_build() {
+    let manifest = AssetManifest::new(vec![image(ImageType::Png, true)]);
+    for serve_location in ["/first/", "/second/"] {
+        let config = Config::default().with_assets_serve_location(serve_location);
+        let head = manifest.head(&config);
+        assert!(head.contains(&format!("href=\"{serve_location}")), "{head}");
+    }
+}
@@ -0,0 +1,149 @@
+use manganis_common::{FileOptions, ImageOptions, ImageOrientation, ImageType};
+
+/// EXIF data with only the orientation tag in the first directory
+fn exif(orientation: u16, little_endian: bool) -> Vec<u8> {
+    let u16_bytes = |value: u16| {
+        if little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+    let u32_bytes = |value: u32| {
+        if little_endian {
+            value.to_le_bytes()
+        } else {
+            value.to_be_bytes()
+        }
+    };
+    let mut exif = if little_endian {
+        b"II*\0".to_vec()
+    } else {
+        b"MM\0*".to_vec()
+    };
+    exif.extend(u32_bytes(8));
+    // One entry with the orientation tag, the short type, a count of one and the value
+    exif.extend(u16_bytes(1));
+    exif.extend(u16_bytes(0x0112));
+    exif.extend(u16_bytes(3));
+    exif.extend(u32_bytes(1));
+    exif.extend(u16_bytes(orientation));
+    exif.extend([0, 0]);
+    // There is no next directory
+    exif.extend([0, 0, 0, 0]);
+    exif
+}
+
+/// The start of a png with an eXIf chunk after the header
+fn png(width: u32, height: u32, exif: &[u8]) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(width.to_be_bytes());
+    png.extend(height.to_be_bytes());
+    png.extend([8, 6, 0, 0, 0]);
+    png.extend([0; 4]);
+    png.extend((exif.len() as u32).to_be_bytes());
+    png.extend(b"eXIf");
+    png.extend(exif);
+    png.extend([0; 4]);
+    png
+}
+
+#[test]
+fn reads_the_orientation_of_pngs() {
+    for little_endian in [true, false] {
+        for (value, orientation) in [
+            (1, ImageOrientation::Normal),
+            (3, ImageOrientation::Rotate180),
+            (6, ImageOrientation::Rotate90),
+            (8, ImageOrientation::Rotate270),
+        ] {
+            let png = png(4, 2, &exif(value, little_endian));
+            assert_eq!(ImageOrientation::read(&png), orientation);
+        }
+    }
+}
+
+#[test]
+fn reads_the_orientation_of_jpgs() {
+    let exif = exif(6, false);
+    let mut jpg = vec![0xFF, 0xD8];
+    // A JFIF segment comes before the EXIF segment
+    jpg.extend([0xFF, 0xE0, 0, 4, 0, 0]);
+    jpg.extend([0xFF, 0xE1]);
+    jpg.extend(((exif.len() + 8) as u16).to_be_bytes());
+    jpg.extend(b"Exif\0\0");
+    jpg.extend(&exif);
+    jpg.extend([0xFF, 0xDA]);
+    assert_eq!(ImageOrientation::read(&jpg), ImageOrientation::Rotate90);
+
+    // Metadata after the start of the scan is not read
+    let mut late = vec![0xFF, 0xD8, 0xFF, 0xDA];
+    late.extend(&jpg[2..]);
+    assert_eq!(ImageOrientation::read(&late), ImageOrientation::Normal);
+}
+
+#[test]
+fn reads_the_orientation_of_webps() {
+    // An odd length chunk before the EXIF chunk is padded to an even length
+    let mut chunks = b"VP8X".to_vec();
+    chunks.extend(3u32.to_le_bytes());
+    chunks.extend([0, 0, 0, 0]);
+    let exif = exif(8, true);
+    chunks.extend(b"EXIF");
+    chunks.extend((exif.len() as u32).to_le_bytes());
+    chunks.extend(&exif);
+
+    let mut webp = b"RIFF".to_vec();
+    webp.extend(((chunks.len() + 4) as u32).to_le_bytes());
+    webp.extend(b"WEBP");
+    webp.extend(chunks);
+    assert_eq!(ImageOrientation::read(&webp), ImageOrientation::Rotate270);
+}
+
+#[test]
+fn images_without_an_orientation_are_upright() {
+    assert_eq!(
+        ImageOrientation::read(b"not an image"),
+        ImageOrientation::Normal
+    );
+    assert_eq!(
+        ImageOrientation::read(&png(4, 2, b"not exif")),
+        ImageOrientation::Normal
+    );
+    assert_eq!(
+        ImageOrientation::read(&png(4, 2, &exif(9, true))),
+        ImageOrientation::Normal
+    );
+}
+
+#[test]
+fn output_size_accounts_for_the_orientation() {
+    let options = ImageOptions::new(ImageType::Avif, None);
+    assert_eq!(
+        options.output_size(&png(460, 307, &exif(6, true))),
+        Some((307, 460))
+    );
+    assert_eq!(
+        options.output_size(&png(460, 307, &exif(3, true))),
+        Some((460, 307))
+    );
+
+    // The image is rotated before it is resized
+    let mut options = ImageOptions::new(ImageType::Avif, None);
+    options.set_width(Some(100));
+    assert_eq!(
+        options.output_size(&png(400, 200, &exif(8, true))),
+        Some((100, 200))
+    );
+}
+
+#[test]
+fn uppercase_image_extensions_are_processed_as_images() {
+    // Photos from cameras often use uppercase extensions. They are processed so their metadata is removed
+    for extension in ["JPG", "Jpeg", "PNG"] {
+        assert!(matches!(
+            FileOptions::default_for_extension(Some(extension)),
+            FileOptions::Image(_)
+        ));
+    }
+}
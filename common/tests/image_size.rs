@@ -0,0 +1,89 @@
+use manganis_common::{ImageOptions, ImageType};
+
+/// The start of a png file with the size in the header. This is all that is needed to read the size of the image
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(width.to_be_bytes());
+    png.extend(height.to_be_bytes());
+    png.extend([8, 6, 0, 0, 0]);
+    png
+}
+
+#[test]
+fn reads_the_size_from_the_header() {
+    let options = ImageOptions::new(ImageType::Avif, None);
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((460, 307)));
+    assert_eq!(options.output_size(b"not an image"), None);
+}
+
+#[test]
+fn output_size_includes_the_resize() {
+    let options = ImageOptions::new(ImageType::Avif, Some((52, 52)));
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((52, 52)));
+
+    // Resizing to a width keeps the aspect ratio, but never scales the image up
+    let mut options = ImageOptions::new(ImageType::Avif, None);
+    options.set_width(Some(230));
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((230, 154)));
+    options.set_width(Some(1000));
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((460, 307)));
+}
+
+#[test]
+fn reads_the_size_of_svgs() {
+    let options = ImageOptions::new(ImageType::Png, None);
+    let size = |svg: &str| options.output_size(svg.as_bytes());
+    assert_eq!(
+        size(r#"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="24px"/>"#),
+        Some((48, 24))
+    );
+    // The viewBox is used when the size is missing or a percentage
+    assert_eq!(
+        size(
+            r#"<?xml version="1.0"?>
+<!-- Logo -->
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 32.4" width="50%">"#
+        ),
+        Some((32, 32))
+    );
+    assert_eq!(
+        size("<svg xmlns='http://www.w3.org/2000/svg'/>"),
+        Some((100, 100))
+    );
+    assert_eq!(size(r#"<svg width="1in" height="72pt"/>"#), Some((96, 96)));
+    assert_eq!(size("<html><svg/></html>"), None);
+
+    // Svgs are resized like any other image
+    let options = ImageOptions::new(ImageType::Png, Some((512, 512)));
+    assert_eq!(
+        options.output_size(br#"<svg viewBox="0 0 24 24"/>"#),
+        Some((512, 512))
+    );
+}
+
+#[test]
+fn density_variants_are_not_scaled_past_the_original() {
+    // The 1x size is 300x200, so the 2x size would be larger than the original image
+    let mut options = ImageOptions::new(ImageType::Avif, None);
+    options.set_width(Some(300));
+    options.scale(2);
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((460, 307)));
+    options.scale(3);
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((460, 307)));
+
+    // Variants that fit in the original are scaled normally
+    options.set_width(Some(200));
+    options.scale(2);
+    assert_eq!(options.output_size(&png_header(460, 307)), Some((400, 266)));
+
+    // An image that is already scaled up to its size stays at that size
+    let mut options = ImageOptions::new(ImageType::Avif, Some((100, 50)));
+    options.scale(2);
+    assert_eq!(options.output_size(&png_header(40, 40)), Some((100, 50)));
+
+    // Svgs have detail at any size
+    let mut options = ImageOptions::new(ImageType::Png, Some((16, 16)));
+    options.scale(3);
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24"/>"#;
+    assert_eq!(options.output_size(svg.as_bytes()), Some((48, 48)));
+}
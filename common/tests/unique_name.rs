@@ -0,0 +1,99 @@
+use manganis_common::{
+    AssetError, AssetSource, FileAsset, FileOptions, FolderAsset, ImageOptions, ImageType,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn write_file(path: &Path, contents: &[u8], modified: SystemTime) -> PathBuf {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(modified).unwrap();
+    path.to_path_buf()
+}
+
+fn file_asset(path: PathBuf) -> FileAsset {
+    FileAsset::new(AssetSource::Local(path)).unwrap()
+}
+
+#[test]
+fn identical_files_get_identical_names() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let new = SystemTime::now();
+    let first = write_file(&first.path().join("a/asset.txt"), b"hello world", old);
+    let second = write_file(&second.path().join("b/c/asset.txt"), b"hello world", new);
+
+    assert_eq!(
+        file_asset(first).location().unique_name(),
+        file_asset(second).location().unique_name()
+    );
+}
+
+#[test]
+fn file_contents_change_the_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let modified = SystemTime::now();
+    let first = write_file(&dir.path().join("a/asset.txt"), b"hello world", modified);
+    let second = write_file(&dir.path().join("b/asset.txt"), b"hello there", modified);
+
+    assert_ne!(
+        file_asset(first).location().unique_name(),
+        file_asset(second).location().unique_name()
+    );
+}
+
+#[test]
+fn file_options_change_the_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_file(
+        &dir.path().join("image.png"),
+        b"not really a png",
+        SystemTime::now(),
+    );
+
+    let png = file_asset(path.clone());
+    let resized = file_asset(path).with_options(FileOptions::Image(ImageOptions::new(
+        ImageType::Png,
+        Some((52, 52)),
+    )));
+
+    assert_ne!(
+        png.location().unique_name(),
+        resized.location().unique_name()
+    );
+}
+
+#[test]
+fn identical_folders_get_identical_names() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+    let first_root = first.path().join("assets");
+    let second_root = second.path().join("nested/assets");
+
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let new = SystemTime::now();
+    write_file(&first_root.join("style.css"), b"body {}", old);
+    write_file(&first_root.join("images/logo.svg"), b"<svg/>", old);
+    write_file(&second_root.join("images/logo.svg"), b"<svg/>", new);
+    write_file(&second_root.join("style.css"), b"body {}", new);
+
+    let first_folder = FolderAsset::new(AssetSource::Local(first_root.clone())).unwrap();
+    let second_folder = FolderAsset::new(AssetSource::Local(second_root)).unwrap();
+    assert_eq!(first_folder.unique_name(), second_folder.unique_name());
+
+    // Changing the contents of a nested file changes the name of the folder
+    write_file(&first_root.join("images/logo.svg"), b"<svg></svg>", old);
+    let changed_folder = FolderAsset::new(AssetSource::Local(first_root)).unwrap();
+    assert_ne!(first_folder.unique_name(), changed_folder.unique_name());
+}
+
+#[test]
+fn unreadable_files_are_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    // Hashing the path instead of the contents would make the name depend on the machine
+    let missing = FileAsset::new(AssetSource::Local(dir.path().join("missing.txt")));
+    assert!(matches!(missing, Err(AssetError::IO(..))));
+}
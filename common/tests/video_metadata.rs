@@ -0,0 +1,160 @@
+use manganis_common::{VideoMetadata, VideoType};
+use std::time::Duration;
+
+fn mp4_box(kind: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+    let mut mp4_box = ((contents.len() + 8) as u32).to_be_bytes().to_vec();
+    mp4_box.extend_from_slice(kind);
+    mp4_box.extend_from_slice(contents);
+    mp4_box
+}
+
+fn mp4_track(width: u32, height: u32, sample_entry: Vec<u8>) -> Vec<u8> {
+    let mut tkhd = vec![0; 76];
+    tkhd.extend_from_slice(&(width << 16).to_be_bytes());
+    tkhd.extend_from_slice(&(height << 16).to_be_bytes());
+    let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+    stsd.extend_from_slice(&sample_entry);
+    let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+    let mdia = mp4_box(b"mdia", &mp4_box(b"minf", &stbl));
+    mp4_box(b"trak", &[mp4_box(b"tkhd", &tkhd), mdia].concat())
+}
+
+#[test]
+fn reads_mp4_metadata() {
+    let mut mvhd = vec![0; 12];
+    mvhd.extend_from_slice(&1000u32.to_be_bytes());
+    mvhd.extend_from_slice(&2500u32.to_be_bytes());
+    mvhd.extend_from_slice(&[0; 80]);
+
+    let avcc = mp4_box(b"avcC", &[1, 0x64, 0x00, 0x1f, 0xff]);
+    let video = mp4_track(1280, 720, mp4_box(b"avc1", &[vec![0; 78], avcc].concat()));
+
+    let decoder_specific_info = [0x05, 2, 0x12, 0x10];
+    let mut decoder_config = vec![0x04, 13 + decoder_specific_info.len() as u8, 0x40, 0x15];
+    decoder_config.extend_from_slice(&[0; 11]);
+    decoder_config.extend_from_slice(&decoder_specific_info);
+    let mut es_descriptor = vec![0x03, 3 + decoder_config.len() as u8, 0, 1, 0];
+    es_descriptor.extend_from_slice(&decoder_config);
+    let esds = mp4_box(b"esds", &[vec![0; 4], es_descriptor].concat());
+    let audio = mp4_track(0, 0, mp4_box(b"mp4a", &[vec![0; 28], esds].concat()));
+
+    let moov = mp4_box(b"moov", &[mp4_box(b"mvhd", &mvhd), video, audio].concat());
+    let mp4 = [
+        mp4_box(b"ftyp", b"isom\0\0\0\0"),
+        moov,
+        mp4_box(b"mdat", &[0; 16]),
+    ]
+    .concat();
+
+    let metadata = VideoMetadata::read(&VideoType::MP4, &mp4);
+    assert_eq!(metadata.duration(), Some(Duration::from_millis(2500)));
+    assert_eq!(metadata.width(), Some(1280));
+    assert_eq!(metadata.height(), Some(720));
+    assert_eq!(metadata.codecs(), ["avc1.64001f", "mp4a.40.2"]);
+}
+
+#[test]
+fn reads_quicktime_metadata() {
+    let mut mvhd = vec![0; 12];
+    mvhd.extend_from_slice(&600u32.to_be_bytes());
+    mvhd.extend_from_slice(&1800u32.to_be_bytes());
+    mvhd.extend_from_slice(&[0; 80]);
+
+    let avcc = mp4_box(b"avcC", &[1, 0x4d, 0x40, 0x1e, 0xff]);
+    let video = mp4_track(640, 480, mp4_box(b"avc1", &[vec![0; 78], avcc].concat()));
+    let moov = mp4_box(b"moov", &[mp4_box(b"mvhd", &mvhd), video].concat());
+    let mov = [
+        mp4_box(b"ftyp", b"qt  \0\0\0\0"),
+        mp4_box(b"mdat", &[0; 16]),
+        moov,
+    ]
+    .concat();
+
+    assert_eq!("mov".parse(), Ok(VideoType::MOV));
+    let metadata = VideoMetadata::read(&VideoType::MOV, &mov);
+    assert_eq!(metadata.duration(), Some(Duration::from_secs(3)));
+    assert_eq!(metadata.width(), Some(640));
+    assert_eq!(metadata.height(), Some(480));
+    assert_eq!(metadata.codecs(), ["avc1.4d401e"]);
+}
+
+fn ebml(id: &[u8], contents: &[u8]) -> Vec<u8> {
+    [id, &[0x80 | contents.len() as u8], contents].concat()
+}
+
+#[test]
+fn reads_webm_metadata() {
+    let info = ebml(
+        &[0x15, 0x49, 0xA9, 0x66],
+        &[
+            ebml(&[0x2A, 0xD7, 0xB1], &[0x0F, 0x42, 0x40]),
+            ebml(&[0x44, 0x89], &1500f64.to_be_bytes()),
+        ]
+        .concat(),
+    );
+    let video_track = ebml(
+        &[0xAE],
+        &[
+            ebml(&[0x86], b"V_VP9"),
+            ebml(
+                &[0xE0],
+                &[ebml(&[0xB0], &[0x02, 0x80]), ebml(&[0xBA], &[0x01, 0x68])].concat(),
+            ),
+        ]
+        .concat(),
+    );
+    let audio_track = ebml(&[0xAE], &ebml(&[0x86], b"A_OPUS"));
+    let tracks = ebml(
+        &[0x16, 0x54, 0xAE, 0x6B],
+        &[video_track, audio_track].concat(),
+    );
+    let cluster = ebml(&[0x1F, 0x43, 0xB6, 0x75], &[0; 8]);
+
+    // Live recordings often don't know the size of the segment when it is written
+    let segment = [
+        &[0x18, 0x53, 0x80, 0x67, 0xFF][..],
+        &info,
+        &tracks,
+        &cluster,
+    ]
+    .concat();
+    let webm = [ebml(&[0x1A, 0x45, 0xDF, 0xA3], &[]), segment].concat();
+
+    let metadata = VideoMetadata::read(&VideoType::Webm, &webm);
+    assert_eq!(metadata.duration(), Some(Duration::from_millis(1500)));
+    assert_eq!(metadata.width(), Some(640));
+    assert_eq!(metadata.height(), Some(360));
+    assert_eq!(metadata.codecs(), ["vp9", "opus"]);
+}
+
+#[test]
+fn reads_gif_metadata() {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&[4, 0, 3, 0, 0, 0, 0]);
+    for delay in [10u8, 20] {
+        // Graphic control extension with the frame delay
+        gif.extend_from_slice(&[0x21, 0xF9, 4, 0, delay, 0, 0, 0]);
+        // Image descriptor followed by the image data
+        gif.extend_from_slice(&[0x2C, 0, 0, 0, 0, 4, 0, 3, 0, 0]);
+        gif.extend_from_slice(&[2, 2, 0x4C, 0x01, 0]);
+    }
+    gif.push(0x3B);
+
+    let metadata = VideoMetadata::read(&VideoType::GIF, &gif);
+    assert_eq!(metadata.duration(), Some(Duration::from_millis(300)));
+    assert_eq!(metadata.width(), Some(4));
+    assert_eq!(metadata.height(), Some(3));
+    assert!(metadata.codecs().is_empty());
+}
+
+#[test]
+fn unreadable_videos_have_no_metadata() {
+    assert_eq!(
+        VideoMetadata::read(&VideoType::MP4, &[]),
+        VideoMetadata::default()
+    );
+    assert_eq!(
+        VideoMetadata::read(&VideoType::Webm, b"not a video"),
+        VideoMetadata::default()
+    );
+}
@@ -4,6 +4,63 @@ use syn::{parenthesized, parse::Parse};
 
 use crate::generate_link_section;
 
+struct ParseFileOptions {
+    options: Vec<ParseFileOption>,
+}
+
+impl ParseFileOptions {
+    fn apply_to_options(self, file: &mut FileAsset) {
+        for option in self.options {
+            option.apply_to_options(file);
+        }
+    }
+}
+
+impl Parse for ParseFileOptions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = Vec::new();
+        while !input.is_empty() {
+            options.push(input.parse::<ParseFileOption>()?);
+        }
+        Ok(ParseFileOptions { options })
+    }
+}
+
+enum ParseFileOption {
+    Integrity(manganis_common::Integrity),
+}
+
+impl ParseFileOption {
+    fn apply_to_options(self, file: &mut FileAsset) {
+        match self {
+            ParseFileOption::Integrity(integrity) => {
+                file.set_integrity(Some(integrity));
+            }
+        }
+    }
+}
+
+impl Parse for ParseFileOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Token![.]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        let content;
+        parenthesized!(content in input);
+        match ident.to_string().as_str() {
+            "integrity" => Ok(ParseFileOption::Integrity(crate::parse_integrity(
+                &content,
+            )?)),
+            _ => Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "Unknown file option: {}. Supported options are integrity",
+                    ident
+                ),
+            )),
+        }
+    }
+}
+
 pub struct FileAssetParser {
     file_name: Result<String, ManganisSupportError>,
     asset: AssetType,
@@ -15,6 +72,14 @@ impl Parse for FileAssetParser {
         parenthesized!(inside in input);
         let path = inside.parse::<syn::LitStr>()?;
 
+        let parsed_options = {
+            if input.is_empty() {
+                None
+            } else {
+                Some(input.parse::<ParseFileOptions>()?)
+            }
+        };
+
         let path_as_str = path.value();
         let path = match AssetSource::parse_file(&path_as_str) {
             Ok(path) => path,
@@ -25,7 +90,12 @@ impl Parse for FileAssetParser {
                 ))
             }
         };
-        let this_file = FileAsset::new(path);
+        let mut this_file = crate::new_file_asset(path)?;
+        if let Some(parsed_options) = parsed_options {
+            parsed_options.apply_to_options(&mut this_file);
+        }
+        crate::verify_local_integrity(&this_file)?;
+
         let asset = manganis_common::AssetType::File(this_file.clone());
 
         let file_name = this_file.served_location();
@@ -20,7 +20,8 @@ impl Parse for FolderAssetParser {
             Ok(path) => path,
             Err(e) => return Err(syn::Error::new(proc_macro2::Span::call_site(), e)),
         };
-        let this_file = FolderAsset::new(path);
+        let this_file = FolderAsset::new(path)
+            .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
         let asset = manganis_common::AssetType::Folder(this_file.clone());
 
         let file_name = this_file.served_location();
@@ -1,4 +1,7 @@
-use manganis_common::{AssetSource, AssetType, CssOptions, FileAsset, ManganisSupportError};
+use manganis_common::{
+    AssetSource, AssetType, CssOptions, FileOptions, FontFace, FontOptions, FontType,
+    ManganisSupportError,
+};
 use quote::{quote, ToTokens};
 use syn::{bracketed, parenthesized, parse::Parse};
 
@@ -50,6 +53,9 @@ struct ParseFontOptions {
     weights: FontWeights,
     text: Option<String>,
     display: Option<String>,
+    integrity: Option<manganis_common::Integrity>,
+    format: Option<FontType>,
+    font_face: Option<String>,
 }
 
 impl ParseFontOptions {
@@ -95,6 +101,9 @@ impl Parse for ParseFontOptions {
         let mut weights = None;
         let mut text = None;
         let mut display = None;
+        let mut integrity = None;
+        let mut format = None;
+        let mut font_face = None;
         loop {
             if input.is_empty() {
                 break;
@@ -116,10 +125,19 @@ impl Parse for ParseFontOptions {
                 "display" => {
                     display = Some(inside.parse::<syn::LitStr>()?.value());
                 }
+                "integrity" => {
+                    integrity = Some(crate::parse_integrity(&inside)?);
+                }
+                "format" => {
+                    format = Some(inside.parse::<ParseFontType>()?.0);
+                }
+                "font_face" => {
+                    font_face = Some(inside.parse::<syn::LitStr>()?.value());
+                }
                 _ => {
                     return Err(syn::Error::new(
                         proc_macro2::Span::call_site(),
-                        format!("Unknown font option: {ident}. Supported options are families, weights, text, display"),
+                        format!("Unknown font option: {ident}. Supported options are families, weights, text, display, integrity, format, font_face"),
                     ))
                 }
             }
@@ -130,12 +148,40 @@ impl Parse for ParseFontOptions {
             weights: weights.unwrap_or_default(),
             text,
             display,
+            integrity,
+            format,
+            font_face,
         })
     }
 }
 
+struct ParseFontType(FontType);
+
+impl Parse for ParseFontType {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Ident>()?;
+        let _ = input.parse::<syn::Token![::]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        ident
+            .to_string()
+            .to_lowercase()
+            .parse::<FontType>()
+            .map_err(|_| {
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("Unknown font type: {ident}. Supported types are ttf, woff, woff2"),
+                )
+            })
+            .map(Self)
+    }
+}
+
 pub struct FontAssetParser {
     file_name: Result<String, ManganisSupportError>,
+    /// Local font files return a `FontAsset` while google fonts return the path to the stylesheet
+    local: bool,
+    /// The location of the `@font-face` stylesheet generated for a local font
+    font_face: Option<Result<String, ManganisSupportError>>,
     asset: AssetType,
 }
 
@@ -143,14 +189,84 @@ impl Parse for FontAssetParser {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let inside;
         parenthesized!(inside in input);
-        if !inside.is_empty() {
+        let path = if inside.is_empty() {
+            None
+        } else {
+            Some(inside.parse::<syn::LitStr>()?.value())
+        };
+
+        let options = input.parse::<ParseFontOptions>()?;
+
+        match path {
+            Some(path) => Self::local(&path, options),
+            None => Self::google(options),
+        }
+    }
+}
+
+impl FontAssetParser {
+    /// Parse a font file that is subset and converted by the CLI
+    fn local(path: &str, options: ParseFontOptions) -> syn::Result<Self> {
+        if !options.families.families.is_empty() || !options.weights.weights.is_empty() {
             return Err(syn::Error::new(
                 proc_macro2::Span::call_site(),
-                "Font assets do not support paths. Please use file() if you want to import a local font file",
+                "Font files do not support families or weights. Use font() without a path to import fonts from google fonts",
             ));
         }
+        let path: AssetSource = match AssetSource::parse_file(path) {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("{e}"),
+                ))
+            }
+        };
 
-        let options = input.parse::<ParseFontOptions>()?;
+        let mut font_options = FontOptions::new(options.format.unwrap_or(FontType::WOFF2));
+        font_options.set_text(options.text);
+        match (options.font_face, options.display) {
+            (Some(family), display) => {
+                let mut font_face = FontFace::new(family);
+                font_face.set_display(display);
+                font_options.set_font_face(Some(font_face));
+            }
+            (None, Some(_)) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "The display of a font file is set in the @font-face rule. Use font_face(\"Family\") to generate one",
+                ))
+            }
+            (None, None) => {}
+        }
+
+        let mut this_file =
+            crate::new_file_asset(path)?.with_options(FileOptions::Font(font_options));
+        this_file.set_integrity(options.integrity);
+        crate::verify_local_integrity(&this_file)?;
+        let asset = manganis_common::AssetType::File(this_file.clone());
+
+        let file_name = this_file.served_location();
+        let font_face = this_file
+            .font_face_stylesheet()
+            .map(|stylesheet| stylesheet.served_location());
+
+        Ok(FontAssetParser {
+            file_name,
+            local: true,
+            font_face,
+            asset,
+        })
+    }
+
+    /// Parse a font from google fonts
+    fn google(options: ParseFontOptions) -> syn::Result<Self> {
+        if options.format.is_some() || options.font_face.is_some() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Google fonts do not support format or font_face. Pass the path to a font file to font() to use them",
+            ));
+        }
 
         let url = options.url();
         let url: AssetSource = match AssetSource::parse_file(&url) {
@@ -162,13 +278,22 @@ impl Parse for FontAssetParser {
                 ))
             }
         };
-        let this_file = FileAsset::new(url.clone())
-            .with_options(manganis_common::FileOptions::Css(CssOptions::default()));
+        // Download the fonts the stylesheet links to so the page doesn't load anything from google at runtime
+        let mut css_options = CssOptions::default();
+        css_options.set_self_host(true);
+        let mut this_file = crate::new_file_asset(url.clone())?
+            .with_options(manganis_common::FileOptions::Css(css_options));
+        this_file.set_integrity(options.integrity);
         let asset = manganis_common::AssetType::File(this_file.clone());
 
         let file_name = this_file.served_location();
 
-        Ok(FontAssetParser { file_name, asset })
+        Ok(FontAssetParser {
+            file_name,
+            local: false,
+            font_face: None,
+            asset,
+        })
     }
 }
 
@@ -178,10 +303,26 @@ impl ToTokens for FontAssetParser {
 
         let link_section = generate_link_section(self.asset.clone());
 
+        if !self.local {
+            tokens.extend(quote! {
+                {
+                    #link_section
+                    #file_name
+                }
+            });
+            return;
+        }
+
+        let css = match (&self.file_name, &self.font_face) {
+            (Ok(_), Some(Ok(css))) => quote! { Some(#css) },
+            // If the font is not collected, the css file is not generated either
+            _ => quote! { None },
+        };
+
         tokens.extend(quote! {
             {
                 #link_section
-                #file_name
+                manganis::FontAsset::new(#file_name).with_css(#css)
             }
         })
     }
@@ -0,0 +1,95 @@
+use manganis_common::{
+    AssetSource, AssetType, FileOptions, IconOptions, IconPurpose, ManganisSupportError,
+};
+use quote::{quote, ToTokens};
+use syn::{parenthesized, parse::Parse};
+
+use crate::generate_link_section;
+
+pub struct IconAssetParser {
+    /// Every icon in the icon set with the path it is served at
+    icons: Vec<(IconOptions, Result<String, ManganisSupportError>)>,
+    assets: Vec<AssetType>,
+}
+
+impl Parse for IconAssetParser {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let inside;
+        parenthesized!(inside in input);
+        let path = inside.parse::<syn::LitStr>()?;
+
+        if !input.is_empty() {
+            let _ = input.parse::<syn::Token![.]>()?;
+            let ident = input.parse::<syn::Ident>()?;
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("Unknown icon option: {ident}. Icons don't have any options"),
+            ));
+        }
+
+        let path: AssetSource = match AssetSource::parse_file(&path.value()) {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("{e}"),
+                ))
+            }
+        };
+
+        // Each icon in the set is collected as its own file from the same source image
+        let mut icons = Vec::new();
+        let mut assets = Vec::new();
+        let source = crate::new_file_asset(path)?;
+        for options in IconOptions::icon_set() {
+            let file = source
+                .clone()
+                .with_options(FileOptions::Icon(options.clone()));
+            icons.push((options, file.served_location()));
+            assets.push(AssetType::File(file));
+        }
+
+        Ok(IconAssetParser { icons, assets })
+    }
+}
+
+impl IconAssetParser {
+    fn path(&self, purpose: IconPurpose) -> proc_macro2::TokenStream {
+        let path = self
+            .icons
+            .iter()
+            .find(|(options, _)| options.purpose() == purpose)
+            .map(|(_, path)| path)
+            .expect("the icon set has an icon for every purpose");
+        crate::quote_path(path)
+    }
+}
+
+impl ToTokens for IconAssetParser {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let link_sections = self.assets.iter().cloned().map(generate_link_section);
+        let favicon = self.path(IconPurpose::Favicon);
+        let apple_touch_icon = self.path(IconPurpose::AppleTouchIcon);
+        let icons = self
+            .icons
+            .iter()
+            .filter(|(options, _)| options.purpose() == IconPurpose::Icon)
+            .map(|(options, path)| {
+                let size = options.sizes()[0];
+                let path = crate::quote_path(path);
+                quote! { (#size, #path) }
+            });
+
+        // Each link section is in its own block so the statics don't conflict
+        tokens.extend(quote! {
+            {
+                #(
+                    {
+                        #link_sections
+                    }
+                )*
+                manganis::IconAsset::new(#favicon, #apple_touch_icon).with_icons(&[#(#icons),*])
+            }
+        })
+    }
+}
@@ -1,18 +1,19 @@
 use manganis_common::ManganisSupportError;
 use manganis_common::{AssetSource, AssetType, FileAsset, FileOptions, ImageOptions};
 use quote::{quote, ToTokens};
-use syn::{parenthesized, parse::Parse, Token};
+use syn::{bracketed, parenthesized, parse::Parse, Token};
 
 use crate::generate_link_section;
+use crate::placeholder::Placeholder;
 
 struct ParseImageOptions {
     options: Vec<ParseImageOption>,
 }
 
 impl ParseImageOptions {
-    fn apply_to_options(self, file: &mut FileAsset, low_quality_preview: &mut bool) {
+    fn apply_to_options(self, file: &mut FileAsset, outputs: &mut ImageOutputOptions) {
         for option in self.options {
-            option.apply_to_options(file, low_quality_preview);
+            option.apply_to_options(file, outputs);
         }
     }
 }
@@ -23,24 +24,117 @@ impl Parse for ParseImageOptions {
         while !input.is_empty() {
             options.push(input.parse::<ParseImageOption>()?);
         }
+        let has_format = options
+            .iter()
+            .any(|option| matches!(option, ParseImageOption::Format(_)));
+        let has_formats = options
+            .iter()
+            .any(|option| matches!(option, ParseImageOption::Formats(_)));
+        if has_format && has_formats {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Use either format or formats. The last format in formats is used as the fallback image",
+            ));
+        }
+        let has_format_auto = options
+            .iter()
+            .any(|option| matches!(option, ParseImageOption::FormatAuto(_)));
+        if has_format_auto && (has_format || has_formats) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "format_auto picks the format of the image, so it can't be used with format or formats",
+            ));
+        }
+
+        let has_quality = options
+            .iter()
+            .any(|option| matches!(option, ParseImageOption::Quality(_)));
+        let has_lossless = options
+            .iter()
+            .any(|option| matches!(option, ParseImageOption::Lossless(_)));
+        if has_quality && has_lossless {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Lossless images don't have a quality. Remove either quality or lossless",
+            ));
+        }
+
+        // The focal point is part of the cover fit, so merge it into the fit option
+        let focal_point = options.iter().find_map(|option| match option {
+            ParseImageOption::FocalPoint(focal_point) => Some(*focal_point),
+            _ => None,
+        });
+        if let Some(focal_point) = focal_point {
+            let cover = options.iter_mut().find_map(|option| match option {
+                ParseImageOption::Fit(fit @ manganis_common::ImageFit::Cover { .. }) => Some(fit),
+                _ => None,
+            });
+            let Some(cover) = cover else {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "The focal point is only used to crop images with .fit(ImageFit::Cover)",
+                ));
+            };
+            *cover = manganis_common::ImageFit::Cover { focal_point };
+            options.retain(|option| !matches!(option, ParseImageOption::FocalPoint(_)));
+        }
+
         Ok(ParseImageOptions { options })
     }
 }
 
+/// Options that change which images are collected rather than how each image is processed
+#[derive(Default)]
+struct ImageOutputOptions {
+    low_quality_preview: bool,
+    widths: Vec<u32>,
+    densities: Vec<u32>,
+    formats: Vec<manganis_common::ImageType>,
+    auto_format: bool,
+    placeholder: Option<Placeholder>,
+    sizes: Option<String>,
+    alt: Option<String>,
+    caption: Option<String>,
+}
+
 enum ParseImageOption {
     Format(manganis_common::ImageType),
     Size((u32, u32)),
     Preload(bool),
     UrlEncoded(bool),
     Lqip(bool),
+    Widths(Vec<u32>),
+    Densities(Vec<u32>),
+    Sizes(String),
+    Formats(Vec<manganis_common::ImageType>),
+    FormatAuto(bool),
+    Placeholder(Placeholder),
+    Alt(String),
+    Caption(String),
+    Fit(manganis_common::ImageFit),
+    FocalPoint((u8, u8)),
+    MaxWidth(u32),
+    MaxHeight(u32),
+    Quality(u8),
+    Lossless(bool),
+    Effort(u8),
+    PreserveIccProfile(bool),
 }
 
 impl ParseImageOption {
-    fn apply_to_options(self, file: &mut FileAsset, low_quality_preview: &mut bool) {
+    fn apply_to_options(self, file: &mut FileAsset, outputs: &mut ImageOutputOptions) {
         match self {
             ParseImageOption::Format(_)
             | ParseImageOption::Size(_)
-            | ParseImageOption::Preload(_) => file.with_options_mut(|options| {
+            | ParseImageOption::Preload(_)
+            | ParseImageOption::Fit(_)
+            | ParseImageOption::FocalPoint(_)
+            | ParseImageOption::MaxWidth(_)
+            | ParseImageOption::MaxHeight(_)
+            | ParseImageOption::Quality(_)
+            | ParseImageOption::Lossless(_)
+            | ParseImageOption::Effort(_)
+            | ParseImageOption::PreserveIccProfile(_) => file.with_options_mut(|options| {
                 if let FileOptions::Image(options) = options {
                     match self {
                         ParseImageOption::Format(format) => {
@@ -52,6 +146,27 @@ impl ParseImageOption {
                         ParseImageOption::Preload(preload) => {
                             options.set_preload(preload);
                         }
+                        ParseImageOption::Fit(fit) => {
+                            options.set_fit(fit);
+                        }
+                        ParseImageOption::MaxWidth(max_width) => {
+                            options.set_max_width(Some(max_width));
+                        }
+                        ParseImageOption::MaxHeight(max_height) => {
+                            options.set_max_height(Some(max_height));
+                        }
+                        ParseImageOption::Quality(quality) => {
+                            options.set_quality(Some(quality));
+                        }
+                        ParseImageOption::Lossless(lossless) => {
+                            options.set_lossless(lossless);
+                        }
+                        ParseImageOption::Effort(effort) => {
+                            options.set_effort(Some(effort));
+                        }
+                        ParseImageOption::PreserveIccProfile(preserve) => {
+                            options.set_preserve_icc_profile(preserve);
+                        }
                         _ => {}
                     }
                 }
@@ -60,7 +175,31 @@ impl ParseImageOption {
                 file.set_url_encoded(url_encoded);
             }
             ParseImageOption::Lqip(lqip) => {
-                *low_quality_preview = lqip;
+                outputs.low_quality_preview = lqip;
+            }
+            ParseImageOption::Widths(widths) => {
+                outputs.widths = widths;
+            }
+            ParseImageOption::Densities(densities) => {
+                outputs.densities = densities;
+            }
+            ParseImageOption::Formats(formats) => {
+                outputs.formats = formats;
+            }
+            ParseImageOption::FormatAuto(auto_format) => {
+                outputs.auto_format = auto_format;
+            }
+            ParseImageOption::Placeholder(placeholder) => {
+                outputs.placeholder = Some(placeholder);
+            }
+            ParseImageOption::Alt(alt) => {
+                outputs.alt = Some(alt);
+            }
+            ParseImageOption::Sizes(sizes) => {
+                outputs.sizes = Some(sizes);
+            }
+            ParseImageOption::Caption(caption) => {
+                outputs.caption = Some(caption);
             }
         }
     }
@@ -91,10 +230,73 @@ impl Parse for ParseImageOption {
             "low_quality_preview" => {
                 Ok(ParseImageOption::Lqip(true))
             }
+            "widths" => Ok(ParseImageOption::Widths(parse_positive_list(
+                &content,
+                "Image widths must be greater than zero",
+            )?)),
+            "densities" => Ok(ParseImageOption::Densities(parse_positive_list(
+                &content,
+                "Pixel densities must be greater than zero",
+            )?)),
+            "formats" => {
+                let formats = content.parse::<ImageFormats>()?;
+                Ok(ParseImageOption::Formats(formats.formats))
+            }
+            "format_auto" => Ok(ParseImageOption::FormatAuto(true)),
+            "fit" => {
+                let fit = content.parse::<ImageFit>()?;
+                Ok(ParseImageOption::Fit(fit.0))
+            }
+            "focal_point" => {
+                let x = content.parse::<syn::LitInt>()?;
+                let _ = content.parse::<syn::Token![,]>()?;
+                let y = content.parse::<syn::LitInt>()?;
+                let mut focal_point = [0; 2];
+                for (value, lit) in focal_point.iter_mut().zip([x, y]) {
+                    *value = lit.base10_parse::<u8>()?;
+                    if *value > 100 {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "The focal point is a percentage of the size of the image and must be between 0 and 100",
+                        ));
+                    }
+                }
+                Ok(ParseImageOption::FocalPoint((focal_point[0], focal_point[1])))
+            }
+            "max_width" => Ok(ParseImageOption::MaxWidth(parse_length(&content)?)),
+            "max_height" => Ok(ParseImageOption::MaxHeight(parse_length(&content)?)),
+            "quality" => Ok(ParseImageOption::Quality(parse_in_range(
+                &content,
+                1..=100,
+                "The quality must be between 1 and 100",
+            )?)),
+            "lossless" => Ok(ParseImageOption::Lossless(true)),
+            "effort" => Ok(ParseImageOption::Effort(parse_in_range(
+                &content,
+                1..=10,
+                "The effort must be between 1 and 10",
+            )?)),
+            "preserve_icc_profile" => Ok(ParseImageOption::PreserveIccProfile(true)),
+            "placeholder" => {
+                let placeholder = content.parse::<Placeholder>()?;
+                Ok(ParseImageOption::Placeholder(placeholder))
+            }
+            "alt" => {
+                let alt = content.parse::<syn::LitStr>()?;
+                Ok(ParseImageOption::Alt(alt.value()))
+            }
+            "sizes" => {
+                let sizes = content.parse::<syn::LitStr>()?;
+                Ok(ParseImageOption::Sizes(sizes.value()))
+            }
+            "caption" => {
+                let caption = content.parse::<syn::LitStr>()?;
+                Ok(ParseImageOption::Caption(caption.value()))
+            }
             _ => Err(syn::Error::new(
                 proc_macro2::Span::call_site(),
                 format!(
-                    "Unknown image option: {}. Supported options are format, size, preload, url_encoded, low_quality_preview",
+                    "Unknown image option: {}. Supported options are format, formats, format_auto, size, fit, focal_point, max_width, max_height, quality, lossless, effort, preserve_icc_profile, preload, url_encoded, low_quality_preview, placeholder, widths, densities, sizes, alt, caption",
                     ident
                 ),
             )),
@@ -109,13 +311,103 @@ struct ImageSize {
 
 impl Parse for ImageSize {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let width = input.parse::<syn::LitInt>()?;
+        let width = parse_length(input)?;
         let _ = input.parse::<syn::Token![,]>()?;
-        let height = input.parse::<syn::LitInt>()?;
-        Ok(ImageSize {
-            width: width.base10_parse()?,
-            height: height.base10_parse()?,
-        })
+        let height = parse_length(input)?;
+        Ok(ImageSize { width, height })
+    }
+}
+
+/// Parses a length in pixels that must be greater than zero
+fn parse_length(input: syn::parse::ParseStream) -> syn::Result<u32> {
+    let lit = input.parse::<syn::LitInt>()?;
+    let length = lit.base10_parse::<u32>()?;
+    if length == 0 {
+        return Err(syn::Error::new(
+            lit.span(),
+            "Image sizes must be greater than zero",
+        ));
+    }
+    Ok(length)
+}
+
+/// Parses a number that must be in the range
+fn parse_in_range(
+    input: syn::parse::ParseStream,
+    range: std::ops::RangeInclusive<u8>,
+    message: &str,
+) -> syn::Result<u8> {
+    let lit = input.parse::<syn::LitInt>()?;
+    match lit.base10_parse::<u8>() {
+        Ok(value) if range.contains(&value) => Ok(value),
+        _ => Err(syn::Error::new(lit.span(), message)),
+    }
+}
+
+struct ImageFit(manganis_common::ImageFit);
+
+impl Parse for ImageFit {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Ident>()?;
+        let _ = input.parse::<Token![::]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        match ident.to_string().as_str() {
+            "Fill" => Ok(Self(manganis_common::ImageFit::Fill)),
+            "Contain" => Ok(Self(manganis_common::ImageFit::Contain)),
+            "Cover" => Ok(Self(manganis_common::ImageFit::COVER)),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Unknown image fit: {}. Supported fits are Fill, Contain, and Cover",
+                    ident
+                ),
+            )),
+        }
+    }
+}
+
+/// Parses an array of numbers that must be greater than zero, like the widths or densities of an image
+fn parse_positive_list(input: syn::parse::ParseStream, message: &str) -> syn::Result<Vec<u32>> {
+    let inside;
+    bracketed!(inside in input);
+    let array =
+        syn::punctuated::Punctuated::<syn::LitInt, syn::Token![,]>::parse_separated_nonempty(
+            &inside,
+        )?;
+    let mut values = Vec::new();
+    for lit in array {
+        let value = lit.base10_parse::<u32>()?;
+        if value == 0 {
+            return Err(syn::Error::new(lit.span(), message));
+        }
+        values.push(value);
+    }
+    // The srcset lists the values from smallest to largest
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+struct ImageFormats {
+    formats: Vec<manganis_common::ImageType>,
+}
+
+impl Parse for ImageFormats {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let inside;
+        bracketed!(inside in input);
+        let array =
+            syn::punctuated::Punctuated::<ImageType, syn::Token![,]>::parse_separated_nonempty(
+                &inside,
+            )?;
+        // Keep the order the formats were listed in. The browser picks the first format it supports
+        let mut formats = Vec::new();
+        for format in array {
+            if !formats.contains(&format.0) {
+                formats.push(format.0);
+            }
+        }
+        Ok(ImageFormats { formats })
     }
 }
 
@@ -157,10 +449,182 @@ impl Default for ImageType {
     }
 }
 
+/// A format an image is collected in along with a resized copy of the image for each width or pixel density
+struct ImageOutput {
+    file: FileAsset,
+    /// The width and served location of each resized copy of the image
+    widths: Vec<(u32, Result<String, ManganisSupportError>)>,
+    /// The pixel density and served location of each scaled copy of the image
+    densities: Vec<(u32, Result<String, ManganisSupportError>)>,
+    srcset: Option<Result<String, ManganisSupportError>>,
+    variants: Vec<FileAsset>,
+}
+
+impl ImageOutput {
+    /// Collects the variants of the image. The contents of local images are used to describe each width with the width it is actually resized to
+    fn new(file: FileAsset, widths: &[u32], densities: &[u32], source: Option<&[u8]>) -> Self {
+        // Every width is collected as its own image with the rest of the options from the original image. Images are never scaled up,
+        // so a width that is wider than a local image ends up with the width of the image and is only collected once
+        let mut width_variants: Vec<FileAsset> = Vec::new();
+        let mut widths_of_variants: Vec<u32> = Vec::new();
+        for width in widths {
+            let variant = Self::variant(&file, |options| options.set_width(Some(*width)));
+            let actual_width = match (source, variant.options()) {
+                (Some(source), FileOptions::Image(options)) => options
+                    .output_size(source)
+                    .map_or(*width, |(actual_width, _)| actual_width),
+                _ => *width,
+            };
+            if !widths_of_variants.contains(&actual_width) {
+                width_variants.push(variant);
+                widths_of_variants.push(actual_width);
+            }
+        }
+        let widths = widths_of_variants.as_slice();
+        // The image itself is the 1x density, so only the higher densities are collected as their own images. Density variants are
+        // never scaled past the resolution of a local raster image, so densities that end up with the same size as a lower density are skipped
+        let size_of = |file: &FileAsset| match (source, file.options()) {
+            (Some(source), FileOptions::Image(options)) => options.output_size(source),
+            _ => None,
+        };
+        let mut density_variants: Vec<FileAsset> = Vec::new();
+        let mut kept_densities: Vec<u32> = Vec::new();
+        let mut sizes_of_densities: Vec<(u32, u32)> = Vec::new();
+        for density in densities {
+            let variant = match density {
+                1 => None,
+                _ => Some(Self::variant(&file, |options| options.scale(*density))),
+            };
+            if let Some(size) = size_of(variant.as_ref().unwrap_or(&file)) {
+                if sizes_of_densities.contains(&size) {
+                    continue;
+                }
+                sizes_of_densities.push(size);
+            }
+            density_variants.extend(variant);
+            kept_densities.push(*density);
+        }
+        let densities = kept_densities.as_slice();
+        let mut density_files = density_variants.iter();
+        let density_files: Vec<&FileAsset> = densities
+            .iter()
+            .map(|density| match density {
+                1 => &file,
+                _ => density_files.next().expect("every density has a file"),
+            })
+            .collect();
+
+        let srcset_for = |files: &[&FileAsset], descriptors: &[u32], unit: &str| {
+            files
+                .iter()
+                .zip(descriptors)
+                .map(|(file, descriptor)| {
+                    file.served_location()
+                        .map(|location| format!("{location} {descriptor}{unit}"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|sources| sources.join(", "))
+        };
+        let width_files: Vec<&FileAsset> = width_variants.iter().collect();
+        let srcset = if !width_files.is_empty() {
+            Some(srcset_for(&width_files, widths, "w"))
+        } else if !density_files.is_empty() {
+            Some(srcset_for(&density_files, densities, "x"))
+        } else {
+            None
+        };
+        let locations = |files: &[&FileAsset], descriptors: &[u32]| {
+            files
+                .iter()
+                .zip(descriptors)
+                .map(|(file, descriptor)| (*descriptor, file.served_location()))
+                .collect()
+        };
+        let widths = locations(&width_files, widths);
+        let densities = locations(&density_files, densities);
+
+        Self {
+            widths,
+            densities,
+            srcset,
+            variants: width_variants.into_iter().chain(density_variants).collect(),
+            file,
+        }
+    }
+
+    /// Copies the image with different image options
+    fn variant(file: &FileAsset, change: impl FnOnce(&mut ImageOptions)) -> FileAsset {
+        let mut variant = file.clone();
+        variant.with_options_mut(|options| {
+            if let FileOptions::Image(options) = options {
+                change(options);
+                // Only the image itself is preloaded. Preloading every variant would download all of them
+                options.set_preload(false);
+            }
+        });
+        variant
+    }
+
+    fn ty(&self) -> manganis_common::ImageType {
+        match self.file.options() {
+            FileOptions::Image(options) => *options.ty(),
+            _ => manganis_common::ImageType::Avif,
+        }
+    }
+
+    fn quote_srcset(&self) -> proc_macro2::TokenStream {
+        match &self.srcset {
+            Some(srcset) => {
+                let srcset = crate::quote_path(srcset);
+                quote! { Some(#srcset) }
+            }
+            None => quote! { None },
+        }
+    }
+
+    fn quote_widths(&self) -> proc_macro2::TokenStream {
+        quote_variants(&self.widths)
+    }
+
+    fn quote_densities(&self) -> proc_macro2::TokenStream {
+        quote_variants(&self.densities)
+    }
+
+    fn link_sections(&self) -> impl Iterator<Item = proc_macro2::TokenStream> + '_ {
+        std::iter::once(&self.file)
+            .chain(&self.variants)
+            .map(|file| generate_link_section(AssetType::File(file.clone())))
+    }
+}
+
+/// Quotes the descriptor and path of each variant of an image
+fn quote_variants(
+    variants: &[(u32, Result<String, ManganisSupportError>)],
+) -> proc_macro2::TokenStream {
+    let variants = variants.iter().map(|(descriptor, path)| {
+        let path = crate::quote_path(path);
+        quote! { (#descriptor, #path) }
+    });
+    quote! { &[#(#variants),*] }
+}
+
 pub struct ImageAssetParser {
     file_name: Result<String, ManganisSupportError>,
     low_quality_preview: Option<String>,
-    asset: AssetType,
+    /// The kind and hash of the placeholder of the image
+    placeholder: Option<(Placeholder, String)>,
+    /// The size of the image after it is processed if it is known at compile time
+    size: Option<(u32, u32)>,
+    /// The sizes attribute of a responsive image
+    sizes: Option<String>,
+    alt: Option<String>,
+    caption: Option<String>,
+    /// The local path or url the image was collected from
+    path: String,
+    /// The formats the image is collected in. The last output is the fallback image the asset points to
+    outputs: Vec<ImageOutput>,
+    /// Includes the local image in the expansion. The size, placeholder and variants are read from the image at compile time
+    tracked_file: proc_macro2::TokenStream,
 }
 
 impl Parse for ImageAssetParser {
@@ -188,15 +652,178 @@ impl Parse for ImageAssetParser {
             }
         };
         let mut this_file =
-            FileAsset::new(path.clone()).with_options(manganis_common::FileOptions::Image(
+            crate::new_file_asset(path.clone())?.with_options(manganis_common::FileOptions::Image(
                 ImageOptions::new(manganis_common::ImageType::Avif, None),
             ));
-        let mut low_quality_preview = false;
+        let mut output_options = ImageOutputOptions::default();
         if let Some(parsed_options) = parsed_options {
-            parsed_options.apply_to_options(&mut this_file, &mut low_quality_preview);
+            parsed_options.apply_to_options(&mut this_file, &mut output_options);
         }
+        let ImageOutputOptions {
+            low_quality_preview,
+            widths,
+            densities,
+            formats,
+            auto_format,
+            placeholder,
+            sizes,
+            alt,
+            caption,
+        } = output_options;
 
-        let asset = manganis_common::AssetType::File(this_file.clone());
+        if !widths.is_empty() {
+            if this_file.url_encoded() {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Responsive images can't be URL encoded. Remove either widths or url_encoded",
+                ));
+            }
+            if matches!(this_file.options(), FileOptions::Image(options) if options.size().is_some())
+            {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Responsive images can't have a fixed size. Each width keeps the aspect ratio of the image, so remove either widths or size",
+                ));
+            }
+        }
+        if sizes.is_some() && widths.is_empty() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Sizes tell the browser which width of a responsive image to pick. Add widths or remove sizes",
+            ));
+        }
+        if !densities.is_empty() {
+            if !widths.is_empty() {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Use either widths or densities. Widths let the browser pick an image for the layout and densities pick an image for the pixel density of the screen",
+                ));
+            }
+            if this_file.url_encoded() {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Images with multiple densities can't be URL encoded. Remove either densities or url_encoded",
+                ));
+            }
+            if matches!(this_file.options(), FileOptions::Image(options) if options.size().is_none())
+            {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Densities scale the size of the image. Set the 1x size of the image with size",
+                ));
+            }
+        }
+        let lossless =
+            matches!(this_file.options(), FileOptions::Image(options) if options.lossless());
+        if lossless && !auto_format {
+            let ty = match this_file.options() {
+                FileOptions::Image(options) => *options.ty(),
+                _ => manganis_common::ImageType::Avif,
+            };
+            let lossy = if formats.is_empty() {
+                (!ty.supports_lossless()).then_some(ty)
+            } else {
+                formats.iter().copied().find(|ty| !ty.supports_lossless())
+            };
+            if let Some(lossy) = lossy {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("{lossy} images can't be encoded losslessly. Use the png or webp format or remove lossless"),
+                ));
+            }
+        }
+        if formats.len() > 1 && this_file.url_encoded() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Images with multiple formats can't be URL encoded. Remove either formats or url_encoded",
+            ));
+        }
+
+        if auto_format {
+            #[cfg(not(feature = "auto-format"))]
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Automatic format selection is not enabled. Enable the auto-format feature to use this feature",
+            ));
+            #[cfg(feature = "auto-format")]
+            select_format(&mut this_file)?;
+        }
+
+        // Local images are available at compile time, so we can read the size of the image and its variants from the header of the image now
+        let source = match &path {
+            AssetSource::Local(_) => Some(
+                path.read_to_bytes()
+                    .map_err(|err| syn::Error::new(proc_macro2::Span::call_site(), err))?,
+            ),
+            AssetSource::Remote(_) => None,
+        };
+        let tracked_file = crate::tracked_file(&path);
+
+        // Collect a copy of the image for each format. Only the preferred format is preloaded
+        let outputs: Vec<ImageOutput> = if formats.is_empty() {
+            vec![ImageOutput::new(
+                this_file,
+                &widths,
+                &densities,
+                source.as_deref(),
+            )]
+        } else {
+            formats
+                .iter()
+                .enumerate()
+                .map(|(index, format)| {
+                    let mut file = this_file.clone();
+                    file.with_options_mut(|options| {
+                        if let FileOptions::Image(options) = options {
+                            options.set_ty(*format);
+                            if index > 0 {
+                                options.set_preload(false);
+                            }
+                        }
+                    });
+                    ImageOutput::new(file, &widths, &densities, source.as_deref())
+                })
+                .collect()
+        };
+        let this_file = &outputs
+            .last()
+            .expect("images have at least one format")
+            .file;
+
+        let contents = match (&source, this_file.options()) {
+            (Some(source), FileOptions::Image(options)) => Some((source.as_slice(), options)),
+            _ => None,
+        };
+        let size = contents
+            .as_ref()
+            .and_then(|(contents, options)| options.output_size(contents));
+        let placeholder = match (placeholder, &contents) {
+            #[cfg(not(feature = "placeholder"))]
+            (Some(_), _) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Placeholders are not enabled. Enable the placeholder feature to use this feature",
+                ))
+            }
+            #[cfg(feature = "placeholder")]
+            (Some(placeholder), Some((contents, options))) => {
+                let hash = placeholder.compute(contents, options).map_err(|err| {
+                    syn::Error::new(
+                        proc_macro2::Span::call_site(),
+                        format!("Failed to compute the placeholder of {path_as_str}: {err}"),
+                    )
+                })?;
+                Some((placeholder, hash))
+            }
+            #[cfg(feature = "placeholder")]
+            (Some(_), None) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "Placeholders are computed when the macro runs, so they are only supported for local images",
+                ))
+            }
+            (None, _) => None,
+        };
 
         let file_name = if this_file.url_encoded() {
             #[cfg(not(feature = "url-encoding"))]
@@ -205,7 +832,7 @@ impl Parse for ImageAssetParser {
                 "URL encoding is not enabled. Enable the url-encoding feature to use this feature",
             ));
             #[cfg(feature = "url-encoding")]
-            Ok(crate::url_encoded_asset(&this_file).map_err(|e| {
+            Ok(crate::url_encoded_asset(this_file).map_err(|e| {
                 syn::Error::new(
                     proc_macro2::Span::call_site(),
                     format!("Failed to encode file: {}", e),
@@ -214,7 +841,6 @@ impl Parse for ImageAssetParser {
         } else {
             this_file.served_location()
         };
-
         let low_quality_preview = if low_quality_preview {
             #[cfg(not(feature = "url-encoding"))]
             return Err(syn::Error::new(
@@ -235,12 +861,12 @@ impl Parse for ImageAssetParser {
                         (width, height)
                     })
                     .unwrap_or((32, 32));
-                let lqip = FileAsset::new(path).with_options(manganis_common::FileOptions::Image(
-                    ImageOptions::new(
+                let lqip = crate::new_file_asset(path)?.with_options(
+                    manganis_common::FileOptions::Image(ImageOptions::new(
                         manganis_common::ImageType::Avif,
                         Some(low_quality_preview_size),
-                    ),
-                ));
+                    )),
+                );
 
                 Some(crate::url_encoded_asset(&lqip).map_err(|e| {
                     syn::Error::new(
@@ -256,11 +882,84 @@ impl Parse for ImageAssetParser {
         Ok(ImageAssetParser {
             file_name,
             low_quality_preview,
-            asset,
+            placeholder,
+            size,
+            sizes,
+            alt,
+            caption,
+            path: path_as_str,
+            outputs,
+            tracked_file,
         })
     }
 }
 
+impl ImageAssetParser {
+    /// Makes sure the image has alt text if the manganis config requires it. This only applies to the image builder. The `mg!("image.png")`
+    /// shorthand returns the path of the image as a string, so there is nowhere to put alt text
+    pub(crate) fn verify_alt(&self) -> syn::Result<()> {
+        if self.alt.is_none() && manganis_common::Config::current().require_alt() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "The image {} is missing alt text and the manganis config requires it. Describe the image with .alt(\"...\") or use .alt(\"\") if the image is only decorative",
+                    self.path
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Encodes the image in each format allowed by the config and switches the image to the smallest one that still looks as good as the quality of the image. Lossless images only pick between the formats that support lossless encoding
+#[cfg(feature = "auto-format")]
+fn select_format(file: &mut FileAsset) -> syn::Result<()> {
+    let config = manganis_common::Config::current();
+    let lossless = matches!(file.options(), FileOptions::Image(options) if options.lossless());
+    let formats: Vec<_> = config
+        .auto_image_formats()
+        .iter()
+        .copied()
+        .filter(|ty| !lossless || ty.supports_lossless())
+        .collect();
+    if formats.is_empty() {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "None of the formats in the manganis config support lossless images. Add png or webp to the auto image formats or remove lossless",
+        ));
+    }
+    // Every format is encoded with the quality of the image and must look at least that good. Images without a quality use the quality in the config
+    let mut min_quality = config.auto_image_quality();
+    file.with_options_mut(|options| {
+        if let FileOptions::Image(options) = options {
+            match options.quality() {
+                Some(quality) => min_quality = quality,
+                None if !options.lossless() => options.set_quality(Some(min_quality)),
+                None => {}
+            }
+        }
+    });
+    let format = manganis_cli_support::select_image_format(
+        file,
+        &formats,
+        min_quality,
+        &manganis_common::cache::image_format_cache_dir(),
+        &manganis_cli_support::RemoteAssetCache::default(),
+    )
+    .map_err(|e| {
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!("Failed to pick a format for the image: {}", e),
+        )
+    })?;
+    file.with_options_mut(|options| {
+        if let FileOptions::Image(options) = options {
+            options.set_ty(format);
+        }
+    });
+    Ok(())
+}
+
 impl ToTokens for ImageAssetParser {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let file_name = crate::quote_path(&self.file_name);
@@ -269,12 +968,79 @@ impl ToTokens for ImageAssetParser {
             None => quote! { None },
         };
 
-        let link_section = generate_link_section(self.asset.clone());
+        let placeholder = match &self.placeholder {
+            Some((kind, hash)) => {
+                quote! { Some(manganis::ImagePlaceholder::new(#kind, #hash)) }
+            }
+            None => quote! { None },
+        };
+
+        let size = match self.size {
+            Some((width, height)) => quote! { Some((#width, #height)) },
+            None => quote! { None },
+        };
+        let alt = match &self.alt {
+            Some(alt) => quote! { Some(#alt) },
+            None => quote! { None },
+        };
+        let caption = match &self.caption {
+            Some(caption) => quote! { Some(#caption) },
+            None => quote! { None },
+        };
+        let sizes = match &self.sizes {
+            Some(sizes) => quote! { Some(#sizes) },
+            None => quote! { None },
+        };
+
+        let (fallback, sources) = self
+            .outputs
+            .split_last()
+            .expect("images have at least one format");
+        let srcset = fallback.quote_srcset();
+        let widths = fallback.quote_widths();
+        let densities = fallback.quote_densities();
+        let sources = sources.iter().map(|source| {
+            let ty = match source.ty() {
+                manganis_common::ImageType::Png => quote! { Png },
+                manganis_common::ImageType::Jpg => quote! { Jpg },
+                manganis_common::ImageType::Avif => quote! { Avif },
+                manganis_common::ImageType::Webp => quote! { Webp },
+            };
+            let path = crate::quote_path(&source.file.served_location());
+            let srcset = source.quote_srcset();
+            let widths = source.quote_widths();
+            let densities = source.quote_densities();
+            quote! {
+                manganis::ImageSource::new(manganis::ImageType::#ty, #path)
+                    .with_srcset(#srcset)
+                    .with_widths(#widths)
+                    .with_densities(#densities)
+            }
+        });
+
+        // Each link section is in its own block so the statics don't conflict
+        let link_sections = self.outputs.iter().flat_map(ImageOutput::link_sections);
+        let tracked_file = &self.tracked_file;
 
         tokens.extend(quote! {
             {
-                #link_section
-                manganis::ImageAsset::new(#file_name).with_preview(#low_quality_preview)
+                #(
+                    {
+                        #link_sections
+                    }
+                )*
+                #tracked_file
+                manganis::ImageAsset::new(#file_name)
+                    .with_preview(#low_quality_preview)
+                    .with_placeholder(#placeholder)
+                    .with_size(#size)
+                    .with_alt(#alt)
+                    .with_caption(#caption)
+                    .with_srcset(#srcset)
+                    .with_sizes(#sizes)
+                    .with_widths(#widths)
+                    .with_densities(#densities)
+                    .with_sources(&[#(#sources),*])
             }
         })
     }
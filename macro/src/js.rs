@@ -112,7 +112,7 @@ impl Parse for JsAssetParser {
                 ))
             }
         };
-        let mut this_file = FileAsset::new(path.clone())
+        let mut this_file = crate::new_file_asset(path.clone())?
             .with_options(manganis_common::FileOptions::Js(JsOptions::new(JsType::Js)));
         if let Some(parsed_options) = parsed_options {
             parsed_options.apply_to_options(&mut this_file);
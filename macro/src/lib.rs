@@ -5,6 +5,7 @@ use css::CssAssetParser;
 use file::FileAssetParser;
 use folder::FolderAssetParser;
 use font::FontAssetParser;
+use icon::IconAssetParser;
 use image::ImageAssetParser;
 use js::JsAssetParser;
 use json::JsonAssetParser;
@@ -13,18 +14,25 @@ use manganis_common::{AssetSource, MetadataAsset, TailwindAsset};
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
 use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::TokenTree;
 use quote::{quote, quote_spanned, ToTokens};
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use svg::SvgAssetParser;
 use syn::{parse::Parse, parse_macro_input, LitStr};
+use video::VideoAssetParser;
 
 mod css;
 mod file;
 mod folder;
 mod font;
+mod icon;
 mod image;
 mod js;
 mod json;
+mod placeholder;
+mod svg;
+mod video;
 
 static LOG_FILE_FRESH: AtomicBool = AtomicBool::new(false);
 
@@ -53,7 +61,7 @@ fn generate_link_section(asset: manganis_common::AssetType) -> TokenStream2 {
 
     let asset_description = serde_json::to_string(&asset).unwrap();
 
-    let len = asset_description.as_bytes().len();
+    let len = asset_description.len();
 
     let asset_bytes = syn::LitByteStr::new(asset_description.as_bytes(), position);
 
@@ -109,6 +117,10 @@ pub fn classes(input: TokenStream) -> TokenStream {
 /// ```rust
 /// const _: &str = manganis::mg!("https://rustacean.net/assets/rustacean-flat-happy.png");
 /// ```
+/// Files can be pinned to a digest so they are rejected if the contents change. Local files are checked when the macro runs and remote files are checked by the CLI
+/// ```rust
+/// const _: &str = manganis::mg!(file("/assets/icon.svg").integrity("sha256-eszLqE5WvBkA2Gx6bqNDKUhhhdr6CdRWpYIccqtcNlk="));
+/// ```
 ///
 /// # Images
 ///
@@ -143,6 +155,10 @@ pub fn classes(input: TokenStream) -> TokenStream {
 /// ```rust
 /// const _: &str = manganis::mg!(font().families(["Roboto"]).weights([200]).text("Hello, world!"));
 /// ```
+/// Local font files are subset to the text and converted to woff2. You can generate a `@font-face` rule for the font
+/// ```rust
+/// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").text("Hello, world!").font_face("Test Sans"));
+/// ```
 #[proc_macro]
 pub fn mg(input: TokenStream) -> TokenStream {
     trace_to_file();
@@ -156,6 +172,31 @@ pub fn mg(input: TokenStream) -> TokenStream {
     .into()
 }
 
+/// The served location of assets depends on the config and support the CLI passes to the build through the environment. Rustc only
+/// rebuilds a crate when an environment variable changes if the code reads it with `env!` or `option_env!`, so the expansion reads
+/// both variables. Otherwise cargo would reuse the old expansion and keep the old served locations when only the config changes
+fn tracked_env() -> TokenStream2 {
+    let config = manganis_common::CONFIG_ENV_VAR;
+    let support = manganis_common::SUPPORT_ENV_VAR;
+    quote! {
+        const _: (Option<&str>, Option<&str>) = (option_env!(#config), option_env!(#support));
+    }
+}
+
+/// Cargo only rebuilds a crate when a file changes if the code reads it with `include_bytes!` or `include_str!`. Macros that read a local
+/// file at compile time include it in the expansion, so the expansion is regenerated when the file changes
+fn tracked_file(source: &AssetSource) -> TokenStream2 {
+    match source.as_path() {
+        Some(path) => {
+            let path = path.to_string_lossy();
+            quote! {
+                const _: &[u8] = include_bytes!(#path);
+            }
+        }
+        None => TokenStream2::new(),
+    }
+}
+
 #[derive(Copy, Clone, Default, PartialEq)]
 enum ReturnType {
     #[default]
@@ -184,10 +225,22 @@ impl ToTokens for AnyAssetParser {
                     tokens
                 }
             }
+            Ok(AnyAssetParserType::Video(video)) => {
+                let tokens = video.into_token_stream();
+                if self.return_type == ReturnType::StaticStr {
+                    quote! {
+                        #tokens.path()
+                    }
+                } else {
+                    tokens
+                }
+            }
             Ok(AnyAssetParserType::Font(font)) => font.into_token_stream(),
             Ok(AnyAssetParserType::Css(css)) => css.into_token_stream(),
             Ok(AnyAssetParserType::Js(js)) => js.into_token_stream(),
             Ok(AnyAssetParserType::Json(js)) => js.into_token_stream(),
+            Ok(AnyAssetParserType::Svg(svg)) => svg.into_token_stream(),
+            Ok(AnyAssetParserType::Icon(icon)) => icon.into_token_stream(),
             Err(e) => e.to_compile_error(),
         };
         let source = &self.source;
@@ -197,10 +250,12 @@ impl ToTokens for AnyAssetParser {
                 &#source
             };
         };
+        let tracked_env = tracked_env();
 
         tokens.extend(quote! {
             {
                 #source
+                #tracked_env
                 #asset
             }
         })
@@ -244,9 +299,14 @@ impl Parse for AnyAssetParser {
             return Ok(asset);
         }
 
-        let builder_tokens = { input.fork().parse::<TokenStream2>()? };
+        let builder_tokens = local_font_builder(input.fork().parse::<TokenStream2>()?);
 
-        let asset = input.parse::<AnyAssetParserType>();
+        let asset = input.parse::<AnyAssetParserType>().and_then(|asset| {
+            if let AnyAssetParserType::Image(image) = &asset {
+                image.verify_alt()?;
+            }
+            Ok(asset)
+        });
         Ok(AnyAssetParser {
             return_type: ReturnType::AssetSpecific,
             asset_type: asset,
@@ -255,14 +315,29 @@ impl Parse for AnyAssetParser {
     }
 }
 
+/// `font()` imports google fonts while `font("path")` imports a font file. Rust functions can't be overloaded, so the
+/// builder for font files is type checked as `font_file("path")`
+fn local_font_builder(builder_tokens: TokenStream2) -> TokenStream2 {
+    let mut tokens: Vec<_> = builder_tokens.into_iter().collect();
+    if let [TokenTree::Ident(ident), TokenTree::Group(arguments), ..] = tokens.as_mut_slice() {
+        if ident == "font" && !arguments.stream().is_empty() {
+            *ident = Ident::new("font_file", ident.span());
+        }
+    }
+    tokens.into_iter().collect()
+}
+
 enum AnyAssetParserType {
     File(FileAssetParser),
     Folder(FolderAssetParser),
     Image(ImageAssetParser),
+    Video(VideoAssetParser),
     Font(FontAssetParser),
     Css(CssAssetParser),
     Js(JsAssetParser),
     Json(JsonAssetParser),
+    Svg(SvgAssetParser),
+    Icon(IconAssetParser),
 }
 
 impl Parse for AnyAssetParserType {
@@ -271,19 +346,21 @@ impl Parse for AnyAssetParserType {
         let as_string = ident.to_string();
 
         Ok(match &*as_string {
-            // videos and files don't have any special settings yet, we just parse them as files
-            "video" | "file" => Self::File(input.parse::<FileAssetParser>()?),
+            "file" => Self::File(input.parse::<FileAssetParser>()?),
+            "video" => Self::Video(input.parse::<VideoAssetParser>()?),
             "folder" => Self::Folder(input.parse::<FolderAssetParser>()?),
             "image" => Self::Image(input.parse::<ImageAssetParser>()?),
-            "font" => Self::Font(input.parse::<FontAssetParser>()?),
+            "font" | "font_file" => Self::Font(input.parse::<FontAssetParser>()?),
             "css" => Self::Css(input.parse::<CssAssetParser>()?),
             "js" => Self::Js(input.parse::<JsAssetParser>()?),
             "json" => Self::Json(input.parse::<JsonAssetParser>()?),
+            "svg" => Self::Svg(input.parse::<SvgAssetParser>()?),
+            "icon" => Self::Icon(input.parse::<IconAssetParser>()?),
             _ => {
                 return Err(syn::Error::new(
                     proc_macro2::Span::call_site(),
                     format!(
-                        "Unknown asset type: {as_string}. Supported types are file, image, font, and css"
+                        "Unknown asset type: {as_string}. Supported types are file, image, video, font, css, js, json, svg, and icon"
                     ),
                 ))
             }
@@ -353,8 +430,6 @@ fn quote_path(path: &Result<String, manganis_common::ManganisSupportError>) -> T
 pub(crate) fn url_encoded_asset(
     file_asset: &manganis_common::FileAsset,
 ) -> Result<String, syn::Error> {
-    use base64::Engine;
-
     let target_directory =
         std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
     let output_folder = std::path::Path::new(&target_directory)
@@ -379,9 +454,49 @@ pub(crate) fn url_encoded_asset(
             format!("Failed to read file: {}", e),
         )
     })?;
-    let data = base64::engine::general_purpose::STANDARD_NO_PAD.encode(data);
     let mime = manganis_common::get_mime_from_ext(file_asset.options().extension());
-    Ok(format!("data:{mime};base64,{data}"))
+    Ok(manganis_common::data_url(mime, &data))
+}
+
+/// Parse a `sha256-<base64 digest>` integrity from a string literal
+pub(crate) fn parse_integrity(
+    input: syn::parse::ParseStream,
+) -> syn::Result<manganis_common::Integrity> {
+    let integrity = input.parse::<LitStr>()?;
+    integrity
+        .value()
+        .parse()
+        .map_err(|err| syn::Error::new(integrity.span(), err))
+}
+
+/// Creates a file asset and turns a file that can't be read into a compile error
+pub(crate) fn new_file_asset(source: AssetSource) -> syn::Result<manganis_common::FileAsset> {
+    manganis_common::FileAsset::new(source)
+        .map_err(|err| syn::Error::new(proc_macro2::Span::call_site(), err))
+}
+
+/// Local files are available at compile time, so we can check the integrity of the file immediately
+pub(crate) fn verify_local_integrity(file: &manganis_common::FileAsset) -> syn::Result<()> {
+    let (Some(integrity), AssetSource::Local(_)) = (file.integrity(), file.location().source())
+    else {
+        return Ok(());
+    };
+    let contents = file
+        .location()
+        .source()
+        .read_to_bytes()
+        .map_err(|err| syn::Error::new(proc_macro2::Span::call_site(), err))?;
+    if !integrity.matches(&contents) {
+        let actual = manganis_common::Integrity::compute(integrity.algorithm(), &contents);
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "Integrity check failed for {}\nexpected: {integrity}\nactual:   {actual}",
+                file.location().source()
+            ),
+        ));
+    }
+    Ok(())
 }
 
 pub(crate) fn verify_preload_valid(ident: &Ident) -> Result<(), syn::Error> {
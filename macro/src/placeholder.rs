@@ -0,0 +1,127 @@
+#[cfg(feature = "placeholder")]
+use manganis_common::{ImageOptions, ImageOrientation};
+use quote::{quote, ToTokens};
+use syn::{parse::Parse, Token};
+
+/// The largest size of the image a placeholder is computed from. ThumbHash only accepts images up to 100x100
+#[cfg(feature = "placeholder")]
+const MAX_SIZE: u32 = 100;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Placeholder {
+    ThumbHash,
+    BlurHash,
+    DominantColor,
+}
+
+impl Parse for Placeholder {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Ident>()?;
+        let _ = input.parse::<Token![::]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        match ident.to_string().as_str() {
+            "ThumbHash" => Ok(Self::ThumbHash),
+            "BlurHash" => Ok(Self::BlurHash),
+            "DominantColor" => Ok(Self::DominantColor),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Unknown placeholder: {}. Supported placeholders are ThumbHash, BlurHash, and DominantColor",
+                    ident
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "placeholder")]
+impl Placeholder {
+    /// Computes the placeholder of the image as it looks after it is processed with the options
+    pub(crate) fn compute(self, image: &[u8], options: &ImageOptions) -> Result<String, String> {
+        if manganis_common::is_svg(image) {
+            return Err("Placeholders are not supported for svg images. Svgs are rasterized by the CLI after the macro runs".to_string());
+        }
+        let orientation = ImageOrientation::read(image);
+        let image = image::load_from_memory(image)
+            .map_err(|err| format!("Failed to decode the image: {err}"))?;
+        // Rotate the image upright the same way the CLI does before it is resized
+        let mut image = match orientation {
+            ImageOrientation::Normal => image,
+            ImageOrientation::FlipHorizontal => image.fliph(),
+            ImageOrientation::Rotate180 => image.rotate180(),
+            ImageOrientation::FlipVertical => image.flipv(),
+            ImageOrientation::Transpose => image.rotate90().fliph(),
+            ImageOrientation::Rotate90 => image.rotate90(),
+            ImageOrientation::Transverse => image.rotate270().fliph(),
+            ImageOrientation::Rotate270 => image.rotate270(),
+        };
+        let original = (image.width(), image.height());
+        if let Some((x, y, width, height)) = options.crop(original) {
+            image = image.crop_imm(x, y, width, height);
+        }
+        // The placeholder only needs the rough shape of the image, so shrink it to the aspect ratio it is served at
+        let (width, height) = options.resized_size(original);
+        let scale = (MAX_SIZE as f64 / width.max(height) as f64).min(1.);
+        let width = ((width as f64 * scale).round() as u32).max(1);
+        let height = ((height as f64 * scale).round() as u32).max(1);
+        let rgba = image
+            .resize_exact(width, height, image::imageops::FilterType::Triangle)
+            .into_rgba8();
+
+        match self {
+            Self::ThumbHash => {
+                let hash =
+                    thumbhash::rgba_to_thumb_hash(width as usize, height as usize, rgba.as_raw());
+                Ok(base64::Engine::encode(
+                    &base64::engine::general_purpose::STANDARD,
+                    hash,
+                ))
+            }
+            Self::BlurHash => {
+                // Use more components along the longer side of the image
+                let (x_components, y_components) = if width >= height { (4, 3) } else { (3, 4) };
+                blurhash::encode(x_components, y_components, width, height, rgba.as_raw())
+                    .map_err(|err| format!("Failed to compute the BlurHash: {err}"))
+            }
+            Self::DominantColor => Ok(dominant_color(rgba.as_raw())),
+        }
+    }
+}
+
+#[cfg(feature = "placeholder")]
+/// Returns the most common color in the image as a hex color. Similar colors are grouped together and mostly transparent pixels are ignored unless the whole image is transparent
+fn dominant_color(rgba: &[u8]) -> String {
+    let opaque = rgba.chunks_exact(4).any(|pixel| pixel[3] >= 128);
+    // Each bucket holds the number of pixels and the sum of their colors
+    let mut buckets = std::collections::HashMap::<[u8; 3], (u32, [u32; 3])>::new();
+    for pixel in rgba.chunks_exact(4) {
+        if opaque && pixel[3] < 128 {
+            continue;
+        }
+        let (count, sum) = buckets
+            .entry([pixel[0] >> 4, pixel[1] >> 4, pixel[2] >> 4])
+            .or_default();
+        *count += 1;
+        for (sum, channel) in sum.iter_mut().zip(pixel) {
+            *sum += *channel as u32;
+        }
+    }
+    // Break ties by the bucket so the color doesn't depend on the iteration order of the map
+    let (count, sum) = buckets
+        .into_iter()
+        .max_by_key(|(bucket, (count, _))| (*count, *bucket))
+        .map(|(_, bucket)| bucket)
+        .unwrap_or((1, [0; 3]));
+    let [red, green, blue] = sum.map(|sum| sum / count);
+    format!("#{red:02x}{green:02x}{blue:02x}")
+}
+
+impl ToTokens for Placeholder {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        tokens.extend(match self {
+            Self::ThumbHash => quote! { manganis::Placeholder::ThumbHash },
+            Self::BlurHash => quote! { manganis::Placeholder::BlurHash },
+            Self::DominantColor => quote! { manganis::Placeholder::DominantColor },
+        })
+    }
+}
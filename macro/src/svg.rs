@@ -0,0 +1,175 @@
+use manganis_common::{
+    AssetSource, AssetType, FileAsset, FileOptions, ManganisSupportError, SvgOptions,
+};
+use quote::{quote, ToTokens};
+use syn::{parenthesized, parse::Parse, LitBool, LitInt};
+
+use crate::generate_link_section;
+
+struct ParseSvgOptions {
+    options: Vec<ParseSvgOption>,
+}
+
+impl ParseSvgOptions {
+    fn apply_to_options(self, file: &mut FileAsset) {
+        for option in self.options {
+            option.apply_to_options(file);
+        }
+    }
+}
+
+impl Parse for ParseSvgOptions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = Vec::new();
+        while !input.is_empty() {
+            options.push(input.parse::<ParseSvgOption>()?);
+        }
+        Ok(ParseSvgOptions { options })
+    }
+}
+
+enum ParseSvgOption {
+    UrlEncoded(bool),
+    Preload(bool),
+    Minify(bool),
+    Precision(u8),
+}
+
+impl ParseSvgOption {
+    fn apply_to_options(self, file: &mut FileAsset) {
+        match self {
+            ParseSvgOption::Preload(_)
+            | ParseSvgOption::Minify(_)
+            | ParseSvgOption::Precision(_) => file.with_options_mut(|options| {
+                if let FileOptions::Svg(options) = options {
+                    match self {
+                        ParseSvgOption::Minify(format) => {
+                            options.set_minify(format);
+                        }
+                        ParseSvgOption::Preload(preload) => {
+                            options.set_preload(preload);
+                        }
+                        ParseSvgOption::Precision(precision) => {
+                            options.set_precision(precision);
+                        }
+                        _ => {}
+                    }
+                }
+            }),
+            ParseSvgOption::UrlEncoded(url_encoded) => {
+                file.set_url_encoded(url_encoded);
+            }
+        }
+    }
+}
+
+impl Parse for ParseSvgOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Token![.]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        let content;
+        parenthesized!(content in input);
+        match ident.to_string().as_str() {
+            "preload" => {
+                crate::verify_preload_valid(&ident)?;
+                Ok(ParseSvgOption::Preload(true))
+            }
+            "url_encoded" => {
+                Ok(ParseSvgOption::UrlEncoded(true))
+            }
+            "minify" => {
+                Ok(ParseSvgOption::Minify(content.parse::<LitBool>()?.value()))
+            }
+            "precision" => {
+                let precision = content.parse::<LitInt>()?;
+                match precision.base10_parse::<u8>() {
+                    Ok(value @ 0..=8) => Ok(ParseSvgOption::Precision(value)),
+                    _ => Err(syn::Error::new(
+                        precision.span(),
+                        "The precision must be a number of decimal places between 0 and 8",
+                    )),
+                }
+            }
+            _ => Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "Unknown Svg option: {}. Supported options are preload, url_encoded, minify, and precision",
+                    ident
+                ),
+            )),
+        }
+    }
+}
+
+pub struct SvgAssetParser {
+    file_name: Result<String, ManganisSupportError>,
+    asset: AssetType,
+}
+
+impl Parse for SvgAssetParser {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let inside;
+        parenthesized!(inside in input);
+        let path = inside.parse::<syn::LitStr>()?;
+
+        let parsed_options = {
+            if input.is_empty() {
+                None
+            } else {
+                Some(input.parse::<ParseSvgOptions>()?)
+            }
+        };
+
+        let path_as_str = path.value();
+        let path: AssetSource = match AssetSource::parse_file(&path_as_str) {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("{e}"),
+                ))
+            }
+        };
+        let mut this_file = crate::new_file_asset(path.clone())?
+            .with_options(manganis_common::FileOptions::Svg(SvgOptions::new()));
+        if let Some(parsed_options) = parsed_options {
+            parsed_options.apply_to_options(&mut this_file);
+        }
+
+        let asset = manganis_common::AssetType::File(this_file.clone());
+
+        let file_name = if this_file.url_encoded() {
+            #[cfg(not(feature = "url-encoding"))]
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "URL encoding is not enabled. Enable the url-encoding feature to use this feature",
+            ));
+            #[cfg(feature = "url-encoding")]
+            Ok(crate::url_encoded_asset(&this_file).map_err(|e| {
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("Failed to encode file: {}", e),
+                )
+            })?)
+        } else {
+            this_file.served_location()
+        };
+
+        Ok(SvgAssetParser { file_name, asset })
+    }
+}
+
+impl ToTokens for SvgAssetParser {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let file_name = crate::quote_path(&self.file_name);
+
+        let link_section = generate_link_section(self.asset.clone());
+
+        tokens.extend(quote! {
+            {
+                #link_section
+                #file_name
+            }
+        })
+    }
+}
@@ -0,0 +1,175 @@
+use manganis_common::{
+    AssetSource, AssetType, FileOptions, ManganisSupportError, VideoMetadata, VideoOptions,
+    VideoType,
+};
+use quote::{quote, ToTokens};
+use syn::{parenthesized, parse::Parse, LitBool};
+
+use crate::generate_link_section;
+
+struct ParseVideoOptions {
+    options: Vec<ParseVideoOption>,
+}
+
+impl ParseVideoOptions {
+    fn apply_to_options(self, options: &mut VideoOptions) {
+        for option in self.options {
+            option.apply_to_options(options);
+        }
+    }
+}
+
+impl Parse for ParseVideoOptions {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = Vec::new();
+        while !input.is_empty() {
+            options.push(input.parse::<ParseVideoOption>()?);
+        }
+        Ok(ParseVideoOptions { options })
+    }
+}
+
+enum ParseVideoOption {
+    Preload(bool),
+    Compress(bool),
+}
+
+impl ParseVideoOption {
+    fn apply_to_options(self, options: &mut VideoOptions) {
+        match self {
+            ParseVideoOption::Preload(preload) => options.set_preload(preload),
+            ParseVideoOption::Compress(compress) => options.set_compress(compress),
+        }
+    }
+}
+
+impl Parse for ParseVideoOption {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let _ = input.parse::<syn::Token![.]>()?;
+        let ident = input.parse::<syn::Ident>()?;
+        let content;
+        parenthesized!(content in input);
+        match ident.to_string().as_str() {
+            "preload" => {
+                crate::verify_preload_valid(&ident)?;
+                Ok(ParseVideoOption::Preload(true))
+            }
+            "compress" => Ok(ParseVideoOption::Compress(
+                content.parse::<LitBool>()?.value(),
+            )),
+            _ => Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "Unknown video option: {}. Supported options are preload and compress",
+                    ident
+                ),
+            )),
+        }
+    }
+}
+
+pub struct VideoAssetParser {
+    file_name: Result<String, ManganisSupportError>,
+    options: VideoOptions,
+    asset: AssetType,
+}
+
+impl Parse for VideoAssetParser {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let inside;
+        parenthesized!(inside in input);
+        let path = inside.parse::<syn::LitStr>()?;
+
+        let parsed_options = {
+            if input.is_empty() {
+                None
+            } else {
+                Some(input.parse::<ParseVideoOptions>()?)
+            }
+        };
+
+        let path_as_str = path.value();
+        let path: AssetSource = match AssetSource::parse_file(&path_as_str) {
+            Ok(path) => path,
+            Err(e) => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!("{e}"),
+                ))
+            }
+        };
+        let Some(ty) = path
+            .extension()
+            .and_then(|extension| extension.parse::<VideoType>().ok())
+        else {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "Unknown video type: {}. Supported types are mp4, mov, webm, and gif. Use `file(...)` to collect other files unchanged",
+                    path_as_str
+                ),
+            ));
+        };
+
+        let mut options = VideoOptions::new(ty.clone());
+        if let Some(parsed_options) = parsed_options {
+            parsed_options.apply_to_options(&mut options);
+        }
+        // Local videos are available at compile time, so we can read the metadata from the container now
+        if let AssetSource::Local(_) = &path {
+            let contents = path
+                .read_to_bytes()
+                .map_err(|err| syn::Error::new(proc_macro2::Span::call_site(), err))?;
+            options.set_metadata(VideoMetadata::read(&ty, &contents));
+        }
+
+        let this_file =
+            crate::new_file_asset(path)?.with_options(FileOptions::Video(options.clone()));
+        let asset = manganis_common::AssetType::File(this_file.clone());
+
+        let file_name = this_file.served_location();
+
+        Ok(VideoAssetParser {
+            file_name,
+            options,
+            asset,
+        })
+    }
+}
+
+impl ToTokens for VideoAssetParser {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let file_name = crate::quote_path(&self.file_name);
+        let ty = match self.options.ty() {
+            VideoType::MP4 => quote! { manganis::VideoType::MP4 },
+            VideoType::MOV => quote! { manganis::VideoType::MOV },
+            VideoType::Webm => quote! { manganis::VideoType::Webm },
+            VideoType::GIF => quote! { manganis::VideoType::GIF },
+        };
+        let metadata = self.options.metadata();
+        let duration = match metadata.duration() {
+            Some(duration) => {
+                let nanos = duration.as_nanos() as u64;
+                quote! { Some(std::time::Duration::from_nanos(#nanos)) }
+            }
+            None => quote! { None },
+        };
+        let size = match (metadata.width(), metadata.height()) {
+            (Some(width), Some(height)) => quote! { Some((#width, #height)) },
+            _ => quote! { None },
+        };
+        let codecs = metadata.codecs();
+
+        let link_section = generate_link_section(self.asset.clone());
+
+        tokens.extend(quote! {
+            {
+                #link_section
+                manganis::VideoAsset::new(#file_name, #ty)
+                    .with_duration(#duration)
+                    .with_size(#size)
+                    .with_codecs(&[#(#codecs),*])
+            }
+        })
+    }
+}
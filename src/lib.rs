@@ -13,6 +13,22 @@ pub struct ImageAsset {
     preview: Option<&'static str>,
     /// A caption for the image
     caption: Option<&'static str>,
+    /// The alt text of the image
+    alt: Option<&'static str>,
+    /// The size of the image after it is processed
+    size: Option<(u32, u32)>,
+    /// The srcset of a responsive image
+    srcset: Option<&'static str>,
+    /// The sizes attribute of a responsive image
+    sizes: Option<&'static str>,
+    /// The width and path of each variant of a responsive image
+    widths: &'static [(u32, &'static str)],
+    /// The pixel density and path of each variant of the image
+    densities: &'static [(u32, &'static str)],
+    /// The other formats of the image in the order the browser should prefer them
+    sources: &'static [ImageSource],
+    /// A compact placeholder that can be shown while the image loads
+    placeholder: Option<ImagePlaceholder>,
 }
 
 impl ImageAsset {
@@ -22,6 +38,14 @@ impl ImageAsset {
             path,
             preview: None,
             caption: None,
+            alt: None,
+            size: None,
+            srcset: None,
+            sizes: None,
+            widths: &[],
+            densities: &[],
+            sources: &[],
+            placeholder: None,
         }
     }
 
@@ -49,6 +73,421 @@ impl ImageAsset {
     pub const fn with_caption(self, caption: Option<&'static str>) -> Self {
         Self { caption, ..self }
     }
+
+    /// Returns the alt text of the image
+    pub const fn alt(&self) -> Option<&'static str> {
+        self.alt
+    }
+
+    /// Sets the alt text of the image
+    pub const fn with_alt(self, alt: Option<&'static str>) -> Self {
+        Self { alt, ..self }
+    }
+
+    /// Returns the width of the image after it is processed. This is known for local images
+    ///
+    /// Setting the `width` and `height` attributes of an `<img>` lets the browser reserve space for the image before it loads, which avoids layout shift
+    pub const fn width(&self) -> Option<u32> {
+        match self.size {
+            Some((width, _)) => Some(width),
+            None => None,
+        }
+    }
+
+    /// Returns the height of the image after it is processed. This is known for local images
+    pub const fn height(&self) -> Option<u32> {
+        match self.size {
+            Some((_, height)) => Some(height),
+            None => None,
+        }
+    }
+
+    /// Sets the size of the image after it is processed
+    pub const fn with_size(self, size: Option<(u32, u32)>) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Returns the [srcset](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img#srcset) of a responsive image. This is set if the image was collected with [`ImageAssetBuilder::widths`] or [`ImageAssetBuilder::densities`]
+    pub const fn srcset(&self) -> Option<&'static str> {
+        self.srcset
+    }
+
+    /// Sets the srcset of the image
+    pub const fn with_srcset(self, srcset: Option<&'static str>) -> Self {
+        Self { srcset, ..self }
+    }
+
+    /// Returns the [sizes](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img#sizes) of a responsive image. This is set if the image was collected with [`ImageAssetBuilder::sizes`]
+    pub const fn sizes(&self) -> Option<&'static str> {
+        self.sizes
+    }
+
+    /// Sets the sizes of the image
+    pub const fn with_sizes(self, sizes: Option<&'static str>) -> Self {
+        Self { sizes, ..self }
+    }
+
+    /// Returns the width and path of each variant of a responsive image from the smallest to the largest width
+    pub const fn widths(&self) -> &'static [(u32, &'static str)] {
+        self.widths
+    }
+
+    /// Sets the width and path of each variant of the image
+    pub const fn with_widths(self, widths: &'static [(u32, &'static str)]) -> Self {
+        Self { widths, ..self }
+    }
+
+    /// Returns the path to the variant of a responsive image with the given width
+    pub fn path_for_width(&self, width: u32) -> Option<&'static str> {
+        self.widths
+            .iter()
+            .find(|(variant_width, _)| *variant_width == width)
+            .map(|(_, path)| *path)
+    }
+
+    /// Returns the pixel density and path of each variant of the image from the lowest to the highest density. This is set if the image was collected with [`ImageAssetBuilder::densities`]
+    pub const fn densities(&self) -> &'static [(u32, &'static str)] {
+        self.densities
+    }
+
+    /// Sets the pixel density and path of each variant of the image
+    pub const fn with_densities(self, densities: &'static [(u32, &'static str)]) -> Self {
+        Self { densities, ..self }
+    }
+
+    /// Returns the path to the variant of the image for a screen with the given scale factor
+    ///
+    /// The lowest density that is at least as high as the scale factor is picked so the image stays sharp. Screens with a higher scale factor than every density get the highest density. If the image doesn't have any densities, this returns [`ImageAsset::path`]
+    ///
+    /// ```rust
+    /// const LOGO: manganis::ImageAsset = manganis::ImageAsset::new("/logo.png")
+    ///     .with_densities(&[(1, "/logo.png"), (2, "/logo@2x.png"), (3, "/logo@3x.png")]);
+    /// assert_eq!(LOGO.path_for_scale_factor(1.5), "/logo@2x.png");
+    /// ```
+    pub fn path_for_scale_factor(&self, scale_factor: f64) -> &'static str {
+        self.densities
+            .iter()
+            .find(|(density, _)| *density as f64 >= scale_factor)
+            .or(self.densities.last())
+            .map_or(self.path, |(_, path)| *path)
+    }
+
+    /// Returns the other formats of the image in the order the browser should prefer them. This is set if the image was collected with [`ImageAssetBuilder::formats`]
+    pub const fn sources(&self) -> &'static [ImageSource] {
+        self.sources
+    }
+
+    /// Sets the other formats of the image
+    pub const fn with_sources(self, sources: &'static [ImageSource]) -> Self {
+        Self { sources, ..self }
+    }
+
+    /// Returns the placeholder of the image. This is set if the image was collected with [`ImageAssetBuilder::placeholder`]
+    pub const fn placeholder(&self) -> Option<ImagePlaceholder> {
+        self.placeholder
+    }
+
+    /// Sets the placeholder of the image
+    pub const fn with_placeholder(self, placeholder: Option<ImagePlaceholder>) -> Self {
+        Self {
+            placeholder,
+            ..self
+        }
+    }
+
+    /// Renders a [`<picture>`](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/picture) element with a `<source>` for each of the other formats of the image and an `<img>` that falls back to [`ImageAsset::path`]. The alt text and size of the image are added to the `<img>` if they are known
+    ///
+    /// With the `placeholder` feature, the placeholder of the image is set as the background of the `<img>` so it is visible until the image loads. The background stays behind transparent parts of the image
+    ///
+    /// Responsive images get a `sizes` attribute so the browser can pick a width before the layout is known. Images without [`ImageAsset::sizes`] are assumed to be as wide as the viewport
+    ///
+    /// ```rust
+    /// const HERO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").formats([ImageType::Avif, ImageType::Png]));
+    /// let picture = HERO.picture();
+    /// assert!(picture.starts_with("<picture><source type=\"image/avif\""));
+    /// ```
+    pub fn picture(&self) -> String {
+        // Width descriptors in a srcset need sizes to tell the browser how wide the image is displayed
+        let sizes = if self.widths.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " sizes=\"{}\"",
+                escape_attribute(self.sizes.unwrap_or("100vw"))
+            )
+        };
+        let mut html = String::from("<picture>");
+        for source in self.sources {
+            html.push_str(&format!(
+                "<source type=\"{}\" srcset=\"{}\"{sizes}>",
+                source.ty().mime_type(),
+                escape_attribute(source.srcset().unwrap_or(source.path()))
+            ));
+        }
+        html.push_str(&format!("<img src=\"{}\"", escape_attribute(self.path)));
+        if let Some(srcset) = self.srcset {
+            html.push_str(&format!(" srcset=\"{}\"{sizes}", escape_attribute(srcset)));
+        }
+        if let Some(alt) = self.alt {
+            html.push_str(&format!(" alt=\"{}\"", escape_attribute(alt)));
+        }
+        if let Some((width, height)) = self.size {
+            html.push_str(&format!(" width=\"{width}\" height=\"{height}\""));
+        }
+        #[cfg(feature = "placeholder")]
+        if let Some(background) = self
+            .placeholder
+            .and_then(|placeholder| placeholder.to_css())
+        {
+            html.push_str(&format!(
+                " style=\"background:{}\"",
+                escape_attribute(&background)
+            ));
+        }
+        html.push_str("></picture>");
+        html
+    }
+}
+
+/// Escapes a value so it can be used inside of a double quoted html attribute
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+/// A compact placeholder for an image that is computed at compile time with [`ImageAssetBuilder::placeholder`]
+///
+/// The placeholder is only a few bytes, so it can be shown before the image or any other asset loads. With the `placeholder` feature, use `ImagePlaceholder::to_css` to show it as the background of an element, or `ImagePlaceholder::to_rgba` to draw it yourself
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+pub struct ImagePlaceholder {
+    /// The kind of the placeholder
+    kind: Placeholder,
+    /// The encoded placeholder
+    hash: &'static str,
+}
+
+impl ImagePlaceholder {
+    /// Creates a new image placeholder
+    pub const fn new(kind: Placeholder, hash: &'static str) -> Self {
+        Self { kind, hash }
+    }
+
+    /// Returns the kind of the placeholder
+    pub const fn kind(&self) -> Placeholder {
+        self.kind
+    }
+
+    /// Returns the encoded placeholder. ThumbHash placeholders are base64 encoded, BlurHash placeholders are base83 encoded, and dominant colors are a hex color like `#1a2b3c`
+    pub const fn hash(&self) -> &'static str {
+        self.hash
+    }
+
+    /// Decodes the placeholder into an RGBA buffer with the size. Placeholders only store the rough shape of the image, so small sizes like 32x32 are enough
+    ///
+    /// Returns `None` if the placeholder is not valid. This requires the `placeholder` feature
+    ///
+    /// ```rust
+    /// let placeholder = manganis::ImagePlaceholder::new(manganis::Placeholder::DominantColor, "#ff8000");
+    /// let rgba = placeholder.to_rgba(2, 2).unwrap();
+    /// assert_eq!(rgba, [255, 128, 0, 255].repeat(4));
+    /// ```
+    #[cfg(feature = "placeholder")]
+    pub fn to_rgba(&self, width: u32, height: u32) -> Option<Vec<u8>> {
+        match self.kind {
+            Placeholder::ThumbHash => {
+                let hash =
+                    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, self.hash)
+                        .ok()?;
+                let (hash_width, hash_height, rgba) = thumbhash::thumb_hash_to_rgba(&hash).ok()?;
+                Some(resize_rgba(
+                    (hash_width as u32, hash_height as u32),
+                    &rgba,
+                    (width, height),
+                ))
+            }
+            Placeholder::BlurHash => blurhash::decode(self.hash, width, height, 1.).ok(),
+            Placeholder::DominantColor => {
+                let [red, green, blue] = parse_hex_color(self.hash)?;
+                Some([red, green, blue, 255].repeat(width as usize * height as usize))
+            }
+        }
+    }
+
+    /// Returns a value for the CSS [`background`](https://developer.mozilla.org/en-US/docs/Web/CSS/background) property that draws the placeholder with a few gradients
+    ///
+    /// Returns `None` if the placeholder is not valid. This requires the `placeholder` feature
+    ///
+    /// ```rust
+    /// let placeholder = manganis::ImagePlaceholder::new(manganis::Placeholder::DominantColor, "#ff8000");
+    /// assert_eq!(placeholder.to_css().unwrap(), "#ff8000");
+    /// ```
+    #[cfg(feature = "placeholder")]
+    pub fn to_css(&self) -> Option<String> {
+        if self.kind == Placeholder::DominantColor {
+            parse_hex_color(self.hash)?;
+            return Some(self.hash.to_string());
+        }
+
+        // Each row of the placeholder is drawn as a horizontal gradient and the average color fills in the rest
+        const COLUMNS: u32 = 4;
+        const ROWS: u32 = 3;
+        let rgba = self.to_rgba(COLUMNS, ROWS)?;
+        let color = |pixel: &[u8]| {
+            format!(
+                "rgba({},{},{},{:.2})",
+                pixel[0],
+                pixel[1],
+                pixel[2],
+                pixel[3] as f32 / 255.
+            )
+        };
+        let mut layers: Vec<String> = rgba
+            .chunks_exact(4 * COLUMNS as usize)
+            .enumerate()
+            .map(|(row, pixels)| {
+                let stops: Vec<String> = pixels
+                    .chunks_exact(4)
+                    .enumerate()
+                    .map(|(column, pixel)| {
+                        format!("{} {}%", color(pixel), column as u32 * 100 / (COLUMNS - 1))
+                    })
+                    .collect();
+                format!(
+                    "linear-gradient(to right,{}) 0 {}%/100% {:.2}% no-repeat",
+                    stops.join(","),
+                    row as u32 * 100 / (ROWS - 1),
+                    100. / ROWS as f32 + 0.01
+                )
+            })
+            .collect();
+        let pixels = (COLUMNS * ROWS) as usize;
+        let mut average = [0; 4];
+        for pixel in rgba.chunks_exact(4) {
+            for (sum, channel) in average.iter_mut().zip(pixel) {
+                *sum += *channel as usize;
+            }
+        }
+        layers.push(color(&average.map(|sum| (sum / pixels) as u8)));
+        Some(layers.join(","))
+    }
+}
+
+/// Parses a hex color like `#1a2b3c`
+#[cfg(feature = "placeholder")]
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Resizes an RGBA buffer with bilinear filtering
+#[cfg(feature = "placeholder")]
+fn resize_rgba(
+    (width, height): (u32, u32),
+    rgba: &[u8],
+    (to_width, to_height): (u32, u32),
+) -> Vec<u8> {
+    // Maps the center of a pixel in the output to the pixels around it in the input
+    let sample = |to: u32, length: u32, to_length: u32| {
+        let position = ((to as f32 + 0.5) * length as f32 / to_length as f32 - 0.5)
+            .clamp(0., (length - 1) as f32);
+        let before = position.floor() as u32;
+        let after = (before + 1).min(length - 1);
+        (before, after, position - before as f32)
+    };
+    let pixel = |x: u32, y: u32| &rgba[(y * width + x) as usize * 4..][..4];
+    let mut resized = Vec::with_capacity(to_width as usize * to_height as usize * 4);
+    for y in 0..to_height {
+        let (top, bottom, y_weight) = sample(y, height, to_height);
+        for x in 0..to_width {
+            let (left, right, x_weight) = sample(x, width, to_width);
+            for channel in 0..4 {
+                let lerp = |a: u8, b: u8, weight: f32| a as f32 + (b as f32 - a as f32) * weight;
+                let top = lerp(
+                    pixel(left, top)[channel],
+                    pixel(right, top)[channel],
+                    x_weight,
+                );
+                let bottom = lerp(
+                    pixel(left, bottom)[channel],
+                    pixel(right, bottom)[channel],
+                    x_weight,
+                );
+                resized.push((top + (bottom - top) * y_weight).round() as u8);
+            }
+        }
+    }
+    resized
+}
+
+/// One format of an image that is built by the [`mg!`] macro with [`ImageAssetBuilder::formats`]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct ImageSource {
+    /// The format of the image
+    ty: ImageType,
+    /// The path to the image
+    path: &'static str,
+    /// The srcset of the image if it is responsive
+    srcset: Option<&'static str>,
+    /// The width and path of each variant of the image if it is responsive
+    widths: &'static [(u32, &'static str)],
+    /// The pixel density and path of each variant of the image
+    densities: &'static [(u32, &'static str)],
+}
+
+impl ImageSource {
+    /// Creates a new image source
+    pub const fn new(ty: ImageType, path: &'static str) -> Self {
+        Self {
+            ty,
+            path,
+            srcset: None,
+            widths: &[],
+            densities: &[],
+        }
+    }
+
+    /// Returns the format of the image
+    pub const fn ty(&self) -> ImageType {
+        self.ty
+    }
+
+    /// Returns the path to the image
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the srcset of the image if it is responsive
+    pub const fn srcset(&self) -> Option<&'static str> {
+        self.srcset
+    }
+
+    /// Sets the srcset of the image
+    pub const fn with_srcset(self, srcset: Option<&'static str>) -> Self {
+        Self { srcset, ..self }
+    }
+
+    /// Returns the width and path of each variant of the image from the smallest to the largest width
+    pub const fn widths(&self) -> &'static [(u32, &'static str)] {
+        self.widths
+    }
+
+    /// Sets the width and path of each variant of the image
+    pub const fn with_widths(self, widths: &'static [(u32, &'static str)]) -> Self {
+        Self { widths, ..self }
+    }
+
+    /// Returns the pixel density and path of each variant of the image from the lowest to the highest density
+    pub const fn densities(&self) -> &'static [(u32, &'static str)] {
+        self.densities
+    }
+
+    /// Sets the pixel density and path of each variant of the image
+    pub const fn with_densities(self, densities: &'static [(u32, &'static str)]) -> Self {
+        Self { densities, ..self }
+    }
 }
 
 impl std::ops::Deref for ImageAsset {
@@ -57,58 +496,575 @@ impl std::ops::Deref for ImageAsset {
     fn deref(&self) -> &Self::Target {
         self.path
     }
-}
+}
+
+impl std::fmt::Display for ImageAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.path.fmt(f)
+    }
+}
+
+/// A video asset that is built by the [`mg!`] macro
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct VideoAsset {
+    /// The path to the video
+    path: &'static str,
+    /// The type of the video
+    ty: VideoType,
+    /// The duration of the video
+    duration: Option<std::time::Duration>,
+    /// The size the video is displayed at
+    size: Option<(u32, u32)>,
+    /// The codecs of the tracks in the video
+    codecs: &'static [&'static str],
+}
+
+impl VideoAsset {
+    /// Creates a new video asset
+    pub const fn new(path: &'static str, ty: VideoType) -> Self {
+        Self {
+            path,
+            ty,
+            duration: None,
+            size: None,
+            codecs: &[],
+        }
+    }
+
+    /// Returns the path to the video
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the type of the video
+    pub const fn ty(&self) -> VideoType {
+        self.ty
+    }
+
+    /// Returns the mime type of the video
+    pub const fn mime_type(&self) -> &'static str {
+        match self.ty {
+            VideoType::MP4 => "video/mp4",
+            VideoType::MOV => "video/quicktime",
+            VideoType::Webm => "video/webm",
+            VideoType::GIF => "image/gif",
+        }
+    }
+
+    /// Returns the duration of the video
+    pub const fn duration(&self) -> Option<std::time::Duration> {
+        self.duration
+    }
+
+    /// Sets the duration of the video
+    pub const fn with_duration(self, duration: Option<std::time::Duration>) -> Self {
+        Self { duration, ..self }
+    }
+
+    /// Returns the width the video is displayed at
+    pub const fn width(&self) -> Option<u32> {
+        match self.size {
+            Some((width, _)) => Some(width),
+            None => None,
+        }
+    }
+
+    /// Returns the height the video is displayed at
+    pub const fn height(&self) -> Option<u32> {
+        match self.size {
+            Some((_, height)) => Some(height),
+            None => None,
+        }
+    }
+
+    /// Sets the size the video is displayed at
+    pub const fn with_size(self, size: Option<(u32, u32)>) -> Self {
+        Self { size, ..self }
+    }
+
+    /// Returns the [codec strings](https://developer.mozilla.org/en-US/docs/Web/Media/Formats/codecs_parameter) of the tracks in the video
+    pub const fn codecs(&self) -> &'static [&'static str] {
+        self.codecs
+    }
+
+    /// Sets the codec strings of the video
+    pub const fn with_codecs(self, codecs: &'static [&'static str]) -> Self {
+        Self { codecs, ..self }
+    }
+
+    /// Returns the value for the `type` attribute of a `<source>` element, including the codecs if they are known. For example: `video/mp4; codecs="avc1.64001f, mp4a.40.2"`
+    pub fn source_type(&self) -> String {
+        if self.codecs.is_empty() {
+            self.mime_type().to_string()
+        } else {
+            format!(
+                "{}; codecs=\"{}\"",
+                self.mime_type(),
+                self.codecs.join(", ")
+            )
+        }
+    }
+}
+
+impl std::ops::Deref for VideoAsset {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.path
+    }
+}
+
+impl std::fmt::Display for VideoAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.path.fmt(f)
+    }
+}
+
+/// The type of a video
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+pub enum VideoType {
+    /// An mp4 video
+    MP4,
+    /// A QuickTime video
+    MOV,
+    /// A webm video
+    Webm,
+    /// A gif video
+    GIF,
+}
+
+/// A font file asset that is built by the [`mg!`] macro
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct FontAsset {
+    /// The path to the font
+    path: &'static str,
+    /// The path to the css file with the `@font-face` rule for the font
+    css: Option<&'static str>,
+}
+
+impl FontAsset {
+    /// Creates a new font asset
+    pub const fn new(path: &'static str) -> Self {
+        Self { path, css: None }
+    }
+
+    /// Returns the path to the font
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the path to the css file with the `@font-face` rule for the font
+    pub const fn css(&self) -> Option<&'static str> {
+        self.css
+    }
+
+    /// Sets the path to the css file with the `@font-face` rule for the font
+    pub const fn with_css(self, css: Option<&'static str>) -> Self {
+        Self { css, ..self }
+    }
+}
+
+impl std::ops::Deref for FontAsset {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.path
+    }
+}
+
+impl std::fmt::Display for FontAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.path.fmt(f)
+    }
+}
+
+/// A set of icons generated from one image by the [`mg!`] macro. The icons are linked in the head of the page by the manifest
+#[derive(Debug, PartialEq, PartialOrd, Clone, Hash)]
+pub struct IconAsset {
+    /// The path to the `favicon.ico` file with multiple sizes
+    favicon: &'static str,
+    /// The path to the icon iOS shows on the home screen
+    apple_touch_icon: &'static str,
+    /// The paths to the png icons by size
+    icons: &'static [(u32, &'static str)],
+}
+
+impl IconAsset {
+    /// Creates a new icon asset
+    pub const fn new(favicon: &'static str, apple_touch_icon: &'static str) -> Self {
+        Self {
+            favicon,
+            apple_touch_icon,
+            icons: &[],
+        }
+    }
+
+    /// Returns the path to the `favicon.ico` file with multiple sizes
+    pub const fn favicon(&self) -> &'static str {
+        self.favicon
+    }
+
+    /// Returns the path to the icon iOS shows on the home screen
+    pub const fn apple_touch_icon(&self) -> &'static str {
+        self.apple_touch_icon
+    }
+
+    /// Returns the size and path of each png icon
+    pub const fn icons(&self) -> &'static [(u32, &'static str)] {
+        self.icons
+    }
+
+    /// Sets the size and path of each png icon
+    pub const fn with_icons(self, icons: &'static [(u32, &'static str)]) -> Self {
+        Self { icons, ..self }
+    }
+
+    /// Returns the path to the png icon with the size
+    pub fn icon(&self, size: u32) -> Option<&'static str> {
+        self.icons
+            .iter()
+            .find(|(icon_size, _)| *icon_size == size)
+            .map(|(_, path)| *path)
+    }
+
+    /// Returns the json for the `icons` list of a [web app manifest](https://developer.mozilla.org/en-US/docs/Web/Manifest/icons)
+    pub fn manifest_icons(&self) -> String {
+        let icons: Vec<_> = self
+            .icons
+            .iter()
+            .map(|(size, path)| {
+                let path = path.replace('\\', "\\\\").replace('"', "\\\"");
+                format!(r#"{{"src":"{path}","sizes":"{size}x{size}","type":"image/png"}}"#)
+            })
+            .collect();
+        format!("[{}]", icons.join(","))
+    }
+}
+
+impl std::ops::Deref for IconAsset {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.favicon
+    }
+}
+
+impl std::fmt::Display for IconAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.favicon.fmt(f)
+    }
+}
+
+/// The type of a font
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+pub enum FontType {
+    /// A ttf (TrueType) font. This is supported by every browser, but it is not compressed
+    TTF,
+    /// A woff (Web Open Font Format) font
+    WOFF,
+    /// A woff2 (Web Open Font Format 2) font. Woff2 fonts are compressed with brotli and are the smallest font format
+    WOFF2,
+}
+
+/// The type of an image. You can read more about the tradeoffs between image formats [here](https://developer.mozilla.org/en-US/docs/Web/Media/Formats/Image_types)
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+pub enum ImageType {
+    /// A png image. Png images cannot contain transparency and tend to compress worse than other formats
+    Png,
+    /// A jpg image. Jpg images can contain transparency and tend to compress better than png images
+    Jpg,
+    /// A webp image. Webp images can contain transparency and tend to compress better than jpg images
+    Webp,
+    /// An avif image. Avif images can compress slightly better than webp images but are not supported by all browsers
+    Avif,
+}
+
+impl ImageType {
+    /// Returns the mime type of the image format
+    pub const fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpg => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+}
+
+/// How an image is fit into the size set with [`ImageAssetBuilder::size`]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
+pub enum ImageFit {
+    /// Stretch the image to the size. This may distort the image if it has a different aspect ratio than the size
+    Fill,
+    /// Scale the image to the largest size that fits in the size while keeping the aspect ratio. One side of the image may be smaller than the size
+    Contain,
+    /// Scale the image to the smallest size that covers the size while keeping the aspect ratio and crop the parts that don't fit. Use [`ImageAssetBuilder::focal_point`] to choose the part of the image that is kept
+    Cover,
+}
+
+/// A kind of compact placeholder that is computed for an image at compile time with [`ImageAssetBuilder::placeholder`]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Clone, Copy, Hash)]
+pub enum Placeholder {
+    /// A [ThumbHash](https://evanw.github.io/thumbhash/). ThumbHashes are about 25 bytes, keep the aspect ratio and transparency of the image and tend to look the closest to the image
+    ThumbHash,
+    /// A [BlurHash](https://blurha.sh/). BlurHashes are about 30 characters and are supported by many other libraries, but don't keep transparency
+    BlurHash,
+    /// The most common color in the image
+    DominantColor,
+}
+
+/// A builder for an image asset. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct ImageAssetBuilder;
+
+impl ImageAssetBuilder {
+    /// Sets the format of the image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Choosing the right format can make your site load much faster. Webp and avif images tend to be a good default for most images
+    ///
+    /// Animated gifs keep every frame and their timing when they are converted to webp. Other formats only keep the first frame
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Webp));
+    /// ```
+    #[allow(unused)]
+    pub const fn format(self, format: ImageType) -> Self {
+        Self
+    }
+
+    /// Picks the smallest format that still looks as good as the image's quality
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// The image is encoded in each format allowed by the manganis config, decoded again and compared with the source. The smallest format that meets the quality of the image, or the auto image quality in the config if the image doesn't set one, is served. Transparent images are never served as jpg. This requires the `auto-format` feature
+    ///
+    /// ```rust,ignore
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format_auto());
+    /// ```
+    #[allow(unused)]
+    pub const fn format_auto(self) -> Self {
+        Self
+    }
+
+    /// Collects a copy of the image in each format to use as fallbacks in a `<picture>` element
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// List the formats from the most to the least preferred. Browsers use the first format they support, so newer formats should come first and a widely supported format like png or jpg should come last. [`ImageAsset::path`] points to the last format and the rest are available from [`ImageAsset::sources`]. Use [`ImageAsset::picture`] to render all of them. If the image is preloaded, only the first format is preloaded
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").formats([ImageType::Avif, ImageType::Webp, ImageType::Png]));
+    /// ```
+    #[allow(unused)]
+    pub const fn formats<const N: usize>(self, formats: [ImageType; N]) -> Self {
+        Self
+    }
+
+    /// Sets the size of the image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// If you only use the image in one place, you can set the size of the image to the size it will be displayed at. This will make the image load faster
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").size(512, 512));
+    /// ```
+    #[allow(unused)]
+    pub const fn size(self, x: u32, y: u32) -> Self {
+        Self
+    }
+
+    /// Sets how the image is fit into the size (default: [`ImageFit::Fill`])
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// By default the image is stretched to the size. Use [`ImageFit::Contain`] or [`ImageFit::Cover`] to keep the aspect ratio of the image
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").size(52, 26).fit(ImageFit::Cover));
+    /// ```
+    #[allow(unused)]
+    pub const fn fit(self, fit: ImageFit) -> Self {
+        Self
+    }
+
+    /// Sets the point of the image that stays in view when the image is cropped with [`ImageFit::Cover`]
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// The point is a percentage of the width and height of the image. `(50, 50)` is the center of the image, which is the default. `(50, 0)` keeps the top of the image
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").size(52, 26).fit(ImageFit::Cover).focal_point(50, 0));
+    /// ```
+    #[allow(unused)]
+    pub const fn focal_point(self, x: u8, y: u8) -> Self {
+        Self
+    }
+
+    /// Sets the largest width of the image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Wider images are scaled down while keeping the aspect ratio. Smaller images are never scaled up
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").max_width(1024));
+    /// ```
+    #[allow(unused)]
+    pub const fn max_width(self, max_width: u32) -> Self {
+        Self
+    }
+
+    /// Sets the largest height of the image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Taller images are scaled down while keeping the aspect ratio. Smaller images are never scaled up
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").max_height(1024));
+    /// ```
+    #[allow(unused)]
+    pub const fn max_height(self, max_height: u32) -> Self {
+        Self
+    }
+
+    /// Sets the quality of the encoded image from 1 to 100
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Lower qualities make smaller images with more compression artifacts. If the quality is not set, the default quality of the encoder is used
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Jpg).quality(60));
+    /// ```
+    #[allow(unused)]
+    pub const fn quality(self, quality: u8) -> Self {
+        Self
+    }
+
+    /// Encodes the image without losing any information
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Only png and webp images support lossless compression. Lossless jpg and avif images are a compile error, and `format_auto` only picks between png and webp
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Webp).lossless());
+    /// ```
+    #[allow(unused)]
+    pub const fn lossless(self) -> Self {
+        Self
+    }
+
+    /// Sets how much effort the encoder spends on making the image smaller from 1 to 10
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Higher values make smaller images, but take longer to build. If the effort is not set, the default effort of the encoder is used
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Avif).effort(8));
+    /// ```
+    #[allow(unused)]
+    pub const fn effort(self, effort: u8) -> Self {
+        Self
+    }
+
+    /// Keeps the ICC color profile of the image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Images are rotated upright and all of their metadata, including EXIF, XMP and GPS locations, is removed when they are processed. Photos with a wide gamut color profile lose some of their colors without the profile. Avif images can't store a color profile
+    ///
+    /// ```rust
+    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Jpg).preserve_icc_profile());
+    /// ```
+    #[allow(unused)]
+    pub const fn preserve_icc_profile(self) -> Self {
+        Self
+    }
 
-impl std::fmt::Display for ImageAsset {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.path.fmt(f)
+    /// Collects a resized copy of the image for each width to use in a responsive image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Each copy keeps the aspect ratio of the image. The paths are available from [`ImageAsset::widths`] and a [`ImageAsset::srcset`] that lists every width is generated for you. This can't be combined with `size` or `url_encoded`
+    ///
+    /// Images are never scaled up. Local images are listed with the width their copy actually has, and widths that end up with the same copy are only collected once. The size of remote images is not known when the macro runs, so they are listed with the requested widths
+    ///
+    /// ```rust
+    /// const HERO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").widths([32, 64]));
+    /// assert_eq!(HERO.widths().len(), 2);
+    /// ```
+    #[allow(unused)]
+    pub const fn widths<const N: usize>(self, widths: [u32; N]) -> Self {
+        Self
     }
-}
 
-/// The type of an image. You can read more about the tradeoffs between image formats [here](https://developer.mozilla.org/en-US/docs/Web/Media/Formats/Image_types)
-#[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Hash)]
-pub enum ImageType {
-    /// A png image. Png images cannot contain transparency and tend to compress worse than other formats
-    Png,
-    /// A jpg image. Jpg images can contain transparency and tend to compress better than png images
-    Jpg,
-    /// A webp image. Webp images can contain transparency and tend to compress better than jpg images
-    Webp,
-    /// An avif image. Avif images can compress slightly better than webp images but are not supported by all browsers
-    Avif,
-}
+    /// Collects a scaled copy of the image for each pixel density to use on high-DPI screens
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// The `size` of the image is the 1x size and each density multiplies it, so `.size(32, 32).densities([1, 2])` collects a 32x32 and a 64x64 image. The paths are available from [`ImageAsset::densities`], a [`ImageAsset::srcset`] with an `x` descriptor for each density is generated for you and [`ImageAsset::path_for_scale_factor`] picks the variant for a screen. This requires `size` and can't be combined with `widths` or `url_encoded`
+    ///
+    /// Raster images are never scaled past the resolution of the original image, because the extra pixels would not add any detail. Densities of a local image that end up with the same size as a lower density are left out. Svgs are drawn at every density
+    ///
+    /// ```rust
+    /// const LOGO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").size(24, 24).densities([1, 2]));
+    /// assert_eq!(LOGO.densities().len(), 2);
+    /// ```
+    #[allow(unused)]
+    pub const fn densities<const N: usize>(self, densities: [u32; N]) -> Self {
+        Self
+    }
 
-/// A builder for an image asset. This must be used in the [`mg!`] macro.
-///
-/// > **Note**: This will do nothing outside of the `mg!` macro
-pub struct ImageAssetBuilder;
+    /// Sets the [sizes](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img#sizes) of a responsive image
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// The sizes tell the browser how wide the image is displayed, so it can pick one of the `widths` before the page is laid out. They are available from [`ImageAsset::sizes`] and added to [`ImageAsset::picture`]. This requires `widths`
+    ///
+    /// ```rust
+    /// const HERO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").widths([32, 64]).sizes("(max-width: 600px) 100vw, 50vw"));
+    /// assert_eq!(HERO.sizes(), Some("(max-width: 600px) 100vw, 50vw"));
+    /// ```
+    #[allow(unused)]
+    pub const fn sizes(self, sizes: &'static str) -> Self {
+        Self
+    }
 
-impl ImageAssetBuilder {
-    /// Sets the format of the image
+    /// Sets the alt text of the image
     ///
     /// > **Note**: This will do nothing outside of the `mg!` macro
     ///
-    /// Choosing the right format can make your site load much faster. Webp and avif images tend to be a good default for most images
+    /// The alt text is available from [`ImageAsset::alt`]. Use an empty alt text for images that are only decorative. If [`Config::with_require_alt`](https://docs.rs/manganis-common/latest/manganis_common/struct.Config.html#method.with_require_alt) is enabled, every image collected with the image builder must set alt text
     ///
     /// ```rust
-    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").format(ImageType::Webp));
+    /// const LOGO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").alt("The Dioxus logo"));
+    /// assert_eq!(LOGO.alt(), Some("The Dioxus logo"));
     /// ```
     #[allow(unused)]
-    pub const fn format(self, format: ImageType) -> Self {
+    pub const fn alt(self, alt: &'static str) -> Self {
         Self
     }
 
-    /// Sets the size of the image
+    /// Sets the caption of the image
     ///
     /// > **Note**: This will do nothing outside of the `mg!` macro
     ///
-    /// If you only use the image in one place, you can set the size of the image to the size it will be displayed at. This will make the image load faster
+    /// The caption is available from [`ImageAsset::caption`]
     ///
     /// ```rust
-    /// const _: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").size(512, 512));
+    /// const LOGO: manganis::ImageAsset = manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386?s=48&v=4").caption("Our new logo"));
+    /// assert_eq!(LOGO.caption(), Some("Our new logo"));
     /// ```
     #[allow(unused)]
-    pub const fn size(self, x: u32, y: u32) -> Self {
+    pub const fn caption(self, caption: &'static str) -> Self {
         Self
     }
 
@@ -126,6 +1082,27 @@ impl ImageAssetBuilder {
         Self
     }
 
+    /// Computes a compact placeholder for the image that can be shown while it loads
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// The placeholder is available from [`ImageAsset::placeholder`] and is only a few bytes, unlike [`ImageAssetBuilder::low_quality_preview`]. Placeholders are computed when the macro runs, so they are only supported for local images. This requires the `placeholder` feature
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "placeholder")]
+    /// # fn main() {
+    /// const HERO: manganis::ImageAsset = manganis::mg!(image("/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png").placeholder(Placeholder::ThumbHash));
+    /// let background = HERO.placeholder().unwrap().to_css().unwrap();
+    /// assert!(background.starts_with("linear-gradient"));
+    /// # }
+    /// # #[cfg(not(feature = "placeholder"))]
+    /// # fn main() {}
+    /// ```
+    #[allow(unused)]
+    pub const fn placeholder(self, placeholder: Placeholder) -> Self {
+        Self
+    }
+
     /// Make the image preloaded
     ///
     /// > **Note**: This will do nothing outside of the `mg!` macro
@@ -171,6 +1148,10 @@ impl ImageAssetBuilder {
 /// ```rust
 /// const _: manganis::ImageAsset = manganis::mg!(image("rustacean-flat-gesture.png").format(ImageType::Avif).size(52, 52));
 /// ```
+/// Svgs are rasterized at the size of the image, so they stay sharp at any size:
+/// ```rust
+/// const _: manganis::ImageAsset = manganis::mg!(image("assets/icon.svg").size(512, 512).format(ImageType::Png));
+/// ```
 /// You can mark images as preloaded to make them load faster in your app
 /// ```rust
 /// const _: manganis::ImageAsset = manganis::mg!(image("rustacean-flat-gesture.png").preload());
@@ -330,6 +1311,108 @@ pub const fn css(path: &'static str) -> CssAssetBuilder {
     CssAssetBuilder
 }
 
+/// A builder for an svg asset. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct SvgAssetBuilder;
+
+impl SvgAssetBuilder {
+    /// Sets whether the svg should be minified (default: true)
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Minifying removes comments, editor metadata and groups that do nothing, and rounds the coordinates in the svg
+    ///
+    /// ```rust
+    /// const _: &str = manganis::mg!(svg("assets/icon.svg").minify(false));
+    /// ```
+    #[allow(unused)]
+    pub const fn minify(self, minify: bool) -> Self {
+        Self
+    }
+
+    /// Sets the number of decimal places coordinates are rounded to when the svg is minified (default: 3)
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Icons that are shown at a small size rarely need more than one or two decimal places. Opacities and gradient offsets always keep at least 3 decimal places
+    ///
+    /// ```rust
+    /// const _: &str = manganis::mg!(svg("assets/icon.svg").precision(1));
+    /// ```
+    #[allow(unused)]
+    pub const fn precision(self, precision: u8) -> Self {
+        Self
+    }
+
+    /// Make the svg preloaded
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Preloading an svg will make the svg start to load as soon as possible. This is useful for svgs that will be displayed soon after the page loads or svgs that may not be visible immediately, but should start loading sooner
+    ///
+    /// Libraries can't preload assets, so this example is not compiled
+    ///
+    /// ```rust,ignore
+    /// const _: &str = manganis::mg!(svg("assets/icon.svg").preload());
+    /// ```
+    #[allow(unused)]
+    pub const fn preload(self) -> Self {
+        Self
+    }
+
+    /// Make the svg URL encoded
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// URL encoding inlines the svg into the URL. Svgs are percent encoded instead of base64 encoded which keeps the URL smaller. This requires the `url-encoding` feature
+    ///
+    /// ```rust,ignore
+    /// const _: &str = manganis::mg!(svg("assets/icon.svg").url_encoded());
+    /// ```
+    #[allow(unused)]
+    pub const fn url_encoded(self) -> Self {
+        Self
+    }
+}
+
+/// Create an svg asset from the local path or url to the svg
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+///
+/// You can collect svgs which will be automatically minified with the svg builder:
+/// ```rust
+/// const _: &str = manganis::mg!(svg("assets/icon.svg"));
+/// ```
+/// Svgs collected with `file` or as part of a folder are copied unchanged
+/// You can round the coordinates of the svg to fewer decimal places to make it smaller:
+/// ```rust
+/// const _: &str = manganis::mg!(svg("assets/icon.svg").minify(true).precision(1));
+/// ```
+#[allow(unused)]
+pub const fn svg(path: &'static str) -> SvgAssetBuilder {
+    SvgAssetBuilder
+}
+
+/// A builder for an icon set. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct IconAssetBuilder;
+
+/// Create a set of icons from the local path or url to an image. Svgs make the sharpest icons
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+///
+/// The icon set has a `favicon.ico` with 16, 32 and 48 pixel images, a 180 pixel apple touch icon and 192 and 512 pixel png icons. The manifest links every icon in the head of the page:
+/// ```rust
+/// const ICON: manganis::IconAsset = manganis::mg!(icon("assets/icon.svg"));
+/// assert!(ICON.icon(192).is_some());
+/// ```
+#[allow(unused)]
+pub const fn icon(path: &'static str) -> IconAssetBuilder {
+    IconAssetBuilder
+}
+
 /// A builder for a font asset. This must be used in the `mg!` macro.
 ///
 /// > **Note**: This will do nothing outside of the `mg!` macro
@@ -383,6 +1466,20 @@ impl FontAssetBuilder {
     pub const fn display(self, display: &'static str) -> Self {
         Self
     }
+
+    /// Pins the font stylesheet to a specific digest. The CLI will refuse to bundle the stylesheet if the downloaded contents don't match
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Google changes the stylesheet when the font is updated, so there is no digest that stays correct. Copy the digest from the error the CLI shows when the stylesheet doesn't match or from the lockfile the CLI records the first download in
+    ///
+    /// ```rust,ignore
+    /// const _: &str = manganis::mg!(font().families(["Roboto"]).integrity("sha256-<digest of the stylesheet>"));
+    /// ```
+    #[allow(unused)]
+    pub const fn integrity(self, integrity: &'static str) -> Self {
+        Self
+    }
 }
 
 /// Create a font asset
@@ -390,6 +1487,8 @@ impl FontAssetBuilder {
 /// > **Note**: This will do nothing outside of the `mg!` macro
 ///
 /// You can use the font builder to collect fonts that will be included in the final binary from google fonts
+///
+/// The stylesheet and the font files it links to are downloaded when the assets are collected, so the fonts are served with the rest of your assets instead of from google
 /// ```rust
 /// const _: &str = manganis::mg!(font().families(["Roboto"]));
 /// ```
@@ -401,11 +1500,121 @@ impl FontAssetBuilder {
 /// ```rust
 /// const _: &str = manganis::mg!(font().families(["Roboto"]).weights([200]).text("Hello, world!"));
 /// ```
+/// To use your own fonts instead of google fonts, pass the path to the font file. See [`font_file`] for more information
+/// ```rust
+/// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").text("Hello, world!"));
+/// ```
 #[allow(unused)]
 pub const fn font() -> FontAssetBuilder {
     FontAssetBuilder
 }
 
+/// A builder for a font file asset. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct LocalFontAssetBuilder;
+
+impl LocalFontAssetBuilder {
+    /// Sets the subset of text that the font needs to support. Glyphs for any other characters are removed from the font which can make the font file size significantly smaller
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// ```rust
+    /// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").text("Hello, world!"));
+    /// ```
+    #[allow(unused)]
+    pub const fn text(self, text: &'static str) -> Self {
+        Self
+    }
+
+    /// Sets the format the font is converted to (default: woff2)
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// ```rust
+    /// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").format(FontType::WOFF));
+    /// ```
+    #[allow(unused)]
+    pub const fn format(self, format: FontType) -> Self {
+        Self
+    }
+
+    /// Generates a css file with a `@font-face` rule that registers the font as the font family. The path to the css file is available with [`FontAsset::css`]
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// ```rust
+    /// const FONT: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").font_face("Test Sans"));
+    /// let stylesheet = FONT.css();
+    /// ```
+    #[allow(unused)]
+    pub const fn font_face(self, family: &'static str) -> Self {
+        Self
+    }
+
+    /// Sets the [display](https://www.w3.org/TR/css-fonts-4/#font-display-desc) in the `@font-face` rule. The display control what happens when the font is unavailable
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// ```rust
+    /// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").font_face("Test Sans").display("swap"));
+    /// ```
+    #[allow(unused)]
+    pub const fn display(self, display: &'static str) -> Self {
+        Self
+    }
+
+    /// Pins the font file to a specific digest. The font will be rejected if the contents don't match
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// ```rust
+    /// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").integrity("sha256-pEBYxSkEj/ztc3ikJ3j3c/S0nDk9y+lTQigfHWHxjsI="));
+    /// ```
+    #[allow(unused)]
+    pub const fn integrity(self, integrity: &'static str) -> Self {
+        Self
+    }
+}
+
+/// Create a font asset from the local path or url to a ttf, otf or woff font file
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+///
+/// Inside the `mg!` macro, `font("path")` is the same as `font_file("path")`. Font files are converted to woff2 by default
+/// ```rust
+/// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf"));
+/// ```
+/// You can subset the font to the text you need and generate a `@font-face` rule for it
+/// ```rust
+/// const _: manganis::FontAsset = manganis::mg!(font("/test-package/test.ttf").text("Hello, world!").font_face("Test Sans"));
+/// ```
+#[allow(unused)]
+pub const fn font_file(path: &'static str) -> LocalFontAssetBuilder {
+    LocalFontAssetBuilder
+}
+
+/// A builder for a file asset. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct FileAssetBuilder;
+
+impl FileAssetBuilder {
+    /// Pins the file to a specific digest in the [subresource integrity](https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity) format
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Local files are checked when the macro runs and remote files that don't match the digest will be rejected by the CLI. If the digest is wrong, the error will include the actual digest of the file so it can be pinned
+    ///
+    /// ```rust
+    /// const _: &str = manganis::mg!(file("/assets/icon.svg").integrity("sha256-eszLqE5WvBkA2Gx6bqNDKUhhhdr6CdRWpYIccqtcNlk="));
+    /// ```
+    #[allow(unused)]
+    pub const fn integrity(self, integrity: &'static str) -> Self {
+        Self
+    }
+}
+
 /// Create an file asset from the local path or url to the file
 ///
 /// > **Note**: This will do nothing outside of the `mg!` macro
@@ -419,26 +1628,66 @@ pub const fn font() -> FontAssetBuilder {
 /// const _: &str = manganis::mg!("https://rustacean.net/assets/rustacean-flat-happy.png");
 /// ```
 #[allow(unused)]
-pub const fn file(path: &'static str) -> &'static str {
-    path
+pub const fn file(path: &'static str) -> FileAssetBuilder {
+    FileAssetBuilder
+}
+
+/// A builder for a video asset. This must be used in the [`mg!`] macro.
+///
+/// > **Note**: This will do nothing outside of the `mg!` macro
+pub struct VideoAssetBuilder;
+
+impl VideoAssetBuilder {
+    /// Sets whether the video should be compressed (default: true)
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Compressing the video removes metadata that is not needed for playback. Mp4 videos are always rearranged so they can start playing before they are fully downloaded
+    ///
+    /// ```rust
+    /// const _: manganis::VideoAsset = manganis::mg!(video("/test-package/test.mp4").compress(false));
+    /// ```
+    #[allow(unused)]
+    pub const fn compress(self, compress: bool) -> Self {
+        Self
+    }
+
+    /// Make the video preloaded
+    ///
+    /// > **Note**: This will do nothing outside of the `mg!` macro
+    ///
+    /// Preloading a video will make the video start to load as soon as possible. This is useful for videos that will be played soon after the page loads
+    ///
+    /// Only the application can preload assets, so this example can't be compiled in the docs of a library
+    ///
+    /// ```rust,ignore
+    /// const _: manganis::VideoAsset = manganis::mg!(video("/test-package/test.mp4").preload());
+    /// ```
+    #[allow(unused)]
+    pub const fn preload(self) -> Self {
+        Self
+    }
 }
 
 /// Create a video asset from the local path or url to the video
 ///
 /// > **Note**: This will do nothing outside of the `mg!` macro
 ///
-/// The video builder collects an arbitrary file. Relative paths are resolved relative to the package root
+/// The video builder collects a mp4, mov, webm, or gif video. Relative paths are resolved relative to the package root. Other videos can be collected unchanged with the [`file`] builder
 /// ```rust
-/// const _: &str = manganis::mg!(video("/assets/video.mp4"));
+/// const _: manganis::VideoAsset = manganis::mg!(video("/test-package/test.mp4"));
 /// ```
-/// Or you can use URLs to read the asset at build time from a remote location
+/// The duration, size and codecs of local videos are read from the video at compile time
 /// ```rust
-/// const _: &str = manganis::mg!(video("https://private-user-images.githubusercontent.com/66571940/355646745-10781eef-de07-491d-aaa3-f75949b32190.mov?jwt=eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJpc3MiOiJnaXRodWIuY29tIiwiYXVkIjoicmF3LmdpdGh1YnVzZXJjb250ZW50LmNvbSIsImtleSI6ImtleTUiLCJleHAiOjE3MjMxMzI5NTcsIm5iZiI6MTcyMzEzMjY1NywicGF0aCI6Ii82NjU3MTk0MC8zNTU2NDY3NDUtMTA3ODFlZWYtZGUwNy00OTFkLWFhYTMtZjc1OTQ5YjMyMTkwLm1vdj9YLUFtei1BbGdvcml0aG09QVdTNC1ITUFDLVNIQTI1NiZYLUFtei1DcmVkZW50aWFsPUFLSUFWQ09EWUxTQTUzUFFLNFpBJTJGMjAyNDA4MDglMkZ1cy1lYXN0LTElMkZzMyUyRmF3czRfcmVxdWVzdCZYLUFtei1EYXRlPTIwMjQwODA4VDE1NTczN1omWC1BbXotRXhwaXJlcz0zMDAmWC1BbXotU2lnbmF0dXJlPTVkODEwZjI4ODE2ZmM4MjE3MWQ2ZDk3MjQ0NjQxYmZlMDI2OTAyMzhjNGU4MzlkYTdmZWM1MjI4ZWQ5NDg3M2QmWC1BbXotU2lnbmVkSGVhZGVycz1ob3N0JmFjdG9yX2lkPTAma2V5X2lkPTAmcmVwb19pZD0wIn0.jlX5E6WGjZeqZind6UCRLFrJ9NHcsV8xXy-Ls30tKPQ"));
+/// const VIDEO: manganis::VideoAsset = manganis::mg!(video("/test-package/test.mp4"));
+/// let source_type = VIDEO.source_type();
+/// let width = VIDEO.width();
 /// ```
 #[allow(unused)]
-pub const fn video(path: &'static str) -> &'static str {
-    path
+pub const fn video(path: &'static str) -> VideoAssetBuilder {
+    VideoAssetBuilder
 }
+
 /// Create an folder asset from the local path
 ///
 /// > **Note**: This will do nothing outside of the `mg!` macro
@@ -464,10 +1713,15 @@ mod __private {
 
     pub trait Sealed {}
 
+    impl Sealed for FileAssetBuilder {}
     impl Sealed for ImageAssetBuilder {}
+    impl Sealed for VideoAssetBuilder {}
     impl Sealed for FontAssetBuilder {}
+    impl Sealed for LocalFontAssetBuilder {}
     impl Sealed for JsAssetBuilder {}
     impl Sealed for JsonAssetBuilder {}
     impl Sealed for CssAssetBuilder {}
+    impl Sealed for SvgAssetBuilder {}
+    impl Sealed for IconAssetBuilder {}
     impl Sealed for &'static str {}
 }
@@ -22,6 +22,8 @@ const ALL_ASSETS: &[&str] = &[
     RESIZED_AVIF_ASSET.path(),
     WEBP_ASSET.path(),
     RESIZED_WEBP_ASSET.path(),
+    RESPONSIVE_ASSET.path(),
+    PICTURE_ASSET.path(),
     ROBOTO_FONT,
     COMFORTAA_FONT,
     ROBOTO_FONT_LIGHT_FONT,
@@ -30,13 +32,25 @@ const ALL_ASSETS: &[&str] = &[
     FOLDER,
 ];
 
+// The size of local images is read at compile time
+const _: () = assert!(matches!(JPEG_ASSET.width(), Some(460)));
+const _: () = assert!(matches!(JPEG_ASSET.height(), Some(307)));
+const _: () = assert!(matches!(RESIZED_JPEG_ASSET.width(), Some(52)));
+
 fn main() {
     tracing_subscriber::fmt::init();
 
     let external_paths_should_exist: bool = option_env!("MANGANIS_SUPPORT").is_some();
 
     // Make sure the macro paths match with the paths that actually exist
-    for path in ALL_ASSETS {
+    let responsive_widths = RESPONSIVE_ASSET.widths().iter().map(|(_, path)| *path);
+    let picture_sources = PICTURE_ASSET.sources().iter().map(|source| source.path());
+    for path in ALL_ASSETS
+        .iter()
+        .copied()
+        .chain(responsive_widths)
+        .chain(picture_sources)
+    {
         // Skip remote assets
         if path.starts_with("http") {
             continue;
@@ -21,3 +21,10 @@ pub const RESIZED_WEBP_ASSET: manganis::ImageAsset =
     manganis::mg!(image("./all_the_assets/rustacean-flat-gesture.png")
         .format(ImageType::Webp)
         .size(52, 52));
+pub const RESPONSIVE_ASSET: manganis::ImageAsset =
+    manganis::mg!(image("./all_the_assets/rustacean-flat-gesture.png")
+        .format(ImageType::Webp)
+        .widths([32, 64]));
+pub const PICTURE_ASSET: manganis::ImageAsset =
+    manganis::mg!(image("./all_the_assets/rustacean-flat-gesture.png")
+        .formats([ImageType::Webp, ImageType::Png]));
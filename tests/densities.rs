@@ -0,0 +1,69 @@
+use manganis::ImageAsset;
+
+const LOGO: ImageAsset = manganis::mg!(image("/assets/icon.svg")
+    .format(ImageType::Png)
+    .size(16, 16)
+    .densities([3, 1, 2]));
+
+#[test]
+fn every_density_is_collected() {
+    assert_eq!(
+        LOGO.densities()
+            .iter()
+            .map(|(density, _)| *density)
+            .collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    // The size of the image is the 1x size
+    assert_eq!((LOGO.width(), LOGO.height()), (Some(16), Some(16)));
+    assert_eq!(LOGO.densities()[0].1, LOGO.path());
+    let srcset = LOGO.srcset().unwrap();
+    assert!(srcset.ends_with(" 3x"), "{srcset}");
+    assert_eq!(srcset.matches('x').count(), 3, "{srcset}");
+}
+
+#[test]
+fn the_variant_for_the_scale_factor_is_picked() {
+    const LOGO: ImageAsset = ImageAsset::new("/logo.png").with_densities(&[
+        (1, "/logo.png"),
+        (2, "/logo@2x.png"),
+        (3, "/logo@3x.png"),
+    ]);
+    assert_eq!(LOGO.path_for_scale_factor(1.), "/logo.png");
+    assert_eq!(LOGO.path_for_scale_factor(1.25), "/logo@2x.png");
+    assert_eq!(LOGO.path_for_scale_factor(2.), "/logo@2x.png");
+    assert_eq!(LOGO.path_for_scale_factor(4.), "/logo@3x.png");
+    // Images without densities always use the image itself
+    assert_eq!(
+        ImageAsset::new("/logo.png").path_for_scale_factor(2.),
+        "/logo.png"
+    );
+}
+
+#[test]
+fn densities_are_not_scaled_past_the_original() {
+    // The image is 460x307, so the 2x and 3x variants would both be larger than the original. Only the 2x variant is kept and it has the size of the original
+    const PHOTO: ImageAsset = manganis::mg!(image(
+        "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+    )
+    .format(ImageType::Png)
+    .size(300, 200)
+    .densities([1, 2, 3]));
+    assert_eq!(
+        PHOTO
+            .densities()
+            .iter()
+            .map(|(density, _)| *density)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+
+    // If the 1x image is already the size of the original, there is nothing to scale up
+    const FULL_SIZE: ImageAsset = manganis::mg!(image(
+        "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+    )
+    .format(ImageType::Png)
+    .size(460, 307)
+    .densities([1, 2]));
+    assert_eq!(FULL_SIZE.densities().len(), 1);
+}
@@ -0,0 +1,29 @@
+use manganis::IconAsset;
+
+const ICON: IconAsset = manganis::mg!(icon("/assets/icon.svg"));
+
+#[test]
+fn every_icon_in_the_set_is_collected() {
+    assert_eq!(
+        ICON.icons()
+            .iter()
+            .map(|(size, _)| *size)
+            .collect::<Vec<_>>(),
+        [192, 512]
+    );
+    // Without the CLI the paths are empty, but each icon is still its own asset
+    assert_eq!(ICON.icon(192), Some(ICON.icons()[0].1));
+    assert_eq!(ICON.icon(64), None);
+    assert_eq!(&*ICON, ICON.favicon());
+}
+
+#[test]
+fn manifest_icons_are_json() {
+    const ICON: IconAsset = IconAsset::new("/assets/favicon.ico", "/assets/apple.png")
+        .with_icons(&[(192, "/assets/icon-192.png"), (512, "/assets/icon-512.png")]);
+    assert_eq!(
+        ICON.manifest_icons(),
+        r#"[{"src":"/assets/icon-192.png","sizes":"192x192","type":"image/png"},{"src":"/assets/icon-512.png","sizes":"512x512","type":"image/png"}]"#
+    );
+    assert_eq!(IconAsset::new("", "").manifest_icons(), "[]");
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "placeholder")]
+
+use manganis::{ImageAsset, ImagePlaceholder, Placeholder};
+
+const THUMB_HASH: ImageAsset = manganis::mg!(image(
+    "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+)
+.placeholder(Placeholder::ThumbHash));
+const BLUR_HASH: ImageAsset = manganis::mg!(image(
+    "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+)
+.placeholder(Placeholder::BlurHash));
+const DOMINANT_COLOR: ImageAsset = manganis::mg!(image(
+    "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+)
+.placeholder(Placeholder::DominantColor));
+const COVER: ImageAsset = manganis::mg!(image(
+    "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+)
+.size(32, 64)
+.fit(ImageFit::Cover)
+.placeholder(Placeholder::ThumbHash));
+
+#[test]
+fn placeholders_are_computed_at_compile_time() {
+    let thumb_hash = THUMB_HASH.placeholder().unwrap();
+    assert_eq!(thumb_hash.kind(), Placeholder::ThumbHash);
+    assert!(thumb_hash.hash().len() < 50, "{}", thumb_hash.hash());
+
+    // A BlurHash with 4x3 components is 4 characters for the header and 2 for each component
+    let blur_hash = BLUR_HASH.placeholder().unwrap();
+    assert_eq!(blur_hash.kind(), Placeholder::BlurHash);
+    assert_eq!(blur_hash.hash().len(), 4 + 2 * 12);
+
+    let dominant_color = DOMINANT_COLOR.placeholder().unwrap();
+    assert_eq!(dominant_color.kind(), Placeholder::DominantColor);
+    assert!(dominant_color.hash().starts_with('#'));
+    assert_eq!(dominant_color.hash().len(), 7);
+
+    // Images without a placeholder don't have one
+    const PLAIN: ImageAsset = manganis::mg!(image(
+        "/test-package/test-package-nested-dependency/all_the_assets/rustacean-flat-gesture.png"
+    ));
+    assert_eq!(PLAIN.placeholder(), None);
+}
+
+#[test]
+fn placeholders_decode_to_rgba() {
+    for asset in [THUMB_HASH, BLUR_HASH, DOMINANT_COLOR, COVER] {
+        let placeholder = asset.placeholder().unwrap();
+        let rgba = placeholder.to_rgba(16, 8).unwrap();
+        assert_eq!(rgba.len(), 16 * 8 * 4, "{placeholder:?}");
+    }
+
+    // The placeholder has the aspect ratio the image is served at. The original image is wider than it is tall, but the cropped image is taller than it is wide
+    let aspect_ratio = |asset: ImageAsset| {
+        let thumb_hash = base64_decode(asset.placeholder().unwrap().hash());
+        thumbhash::thumb_hash_to_approximate_aspect_ratio(&thumb_hash).unwrap()
+    };
+    assert!(aspect_ratio(THUMB_HASH) > 1.);
+    assert!(aspect_ratio(COVER) < 1.);
+}
+
+#[test]
+fn placeholders_render_as_css() {
+    for asset in [THUMB_HASH, BLUR_HASH] {
+        let css = asset.placeholder().unwrap().to_css().unwrap();
+        assert_eq!(css.matches("linear-gradient").count(), 3, "{css}");
+        assert!(css.ends_with(')'), "{css}");
+    }
+    let dominant_color = DOMINANT_COLOR.placeholder().unwrap();
+    assert_eq!(
+        dominant_color.to_css().as_deref(),
+        Some(dominant_color.hash())
+    );
+
+    // The placeholder is the background of the image until it loads
+    let picture = THUMB_HASH.picture();
+    assert!(
+        picture.contains(" style=\"background:linear-gradient"),
+        "{picture}"
+    );
+}
+
+#[test]
+fn invalid_placeholders_do_not_decode() {
+    for placeholder in [
+        ImagePlaceholder::new(Placeholder::ThumbHash, "not base64!"),
+        ImagePlaceholder::new(Placeholder::BlurHash, "short"),
+        ImagePlaceholder::new(Placeholder::DominantColor, "#12345"),
+        ImagePlaceholder::new(Placeholder::DominantColor, "red"),
+    ] {
+        assert_eq!(placeholder.to_rgba(4, 4), None, "{placeholder:?}");
+        assert_eq!(placeholder.to_css(), None, "{placeholder:?}");
+    }
+}
+
+fn base64_decode(value: &str) -> Vec<u8> {
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, value).unwrap()
+}
@@ -0,0 +1,15 @@
+#[test]
+fn require_alt() {
+    // The macro reads the config from the environment when trybuild compiles the cases
+    std::env::set_var("MANGANIS_CONFIG", "require_alt = true");
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/require_alt_shorthand.rs");
+    t.compile_fail("tests/ui/require_alt_builder.rs");
+}
+
+#[cfg(not(feature = "placeholder"))]
+#[test]
+fn placeholders_require_the_feature() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/placeholder_without_feature.rs");
+}
@@ -0,0 +1,8 @@
+// Placeholders are computed by the macro with the image decoders of the placeholder feature
+const LOGO: manganis::ImageAsset = manganis::mg!(image(
+    "https://avatars.githubusercontent.com/u/79236386.png"
+)
+.alt("")
+.placeholder(Placeholder::ThumbHash));
+
+fn main() {}
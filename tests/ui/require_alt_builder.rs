@@ -0,0 +1,5 @@
+// The image builder can set alt text, so it must set it when the config requires it
+const LOGO: manganis::ImageAsset =
+    manganis::mg!(image("https://avatars.githubusercontent.com/u/79236386.png"));
+
+fn main() {}
@@ -0,0 +1,6 @@
+// The shorthand only returns the path of the image, so it doesn't need alt text
+const LOGO: &str = manganis::mg!("https://avatars.githubusercontent.com/u/79236386.png");
+
+fn main() {
+    assert!(!LOGO.is_empty());
+}
@@ -0,0 +1,37 @@
+use manganis::ImageAsset;
+
+const HERO: ImageAsset = manganis::mg!(image("/assets/icon.svg")
+    .format(ImageType::Png)
+    .widths([8, 16, 64, 128])
+    .sizes("(max-width: 600px) 100vw, 50vw"));
+
+#[test]
+fn widths_wider_than_the_image_use_the_width_of_the_image() {
+    // The svg is 24 pixels wide and images are never scaled up
+    assert_eq!(
+        HERO.widths()
+            .iter()
+            .map(|(width, _)| *width)
+            .collect::<Vec<_>>(),
+        [8, 16, 24]
+    );
+    let srcset = HERO.srcset().unwrap();
+    assert!(srcset.ends_with(" 24w"), "{srcset}");
+    assert_eq!(srcset.matches(", ").count(), 2, "{srcset}");
+}
+
+#[test]
+fn pictures_of_responsive_images_have_sizes() {
+    let picture = HERO.picture();
+    assert!(
+        picture.contains(" sizes=\"(max-width: 600px) 100vw, 50vw\""),
+        "{picture}"
+    );
+
+    // Images without sizes are assumed to be as wide as the viewport
+    const PHOTO: ImageAsset = ImageAsset::new("/photo.png")
+        .with_srcset(Some("/photo-400.png 400w, /photo-800.png 800w"))
+        .with_widths(&[(400, "/photo-400.png"), (800, "/photo-800.png")]);
+    assert!(PHOTO.picture().contains(" sizes=\"100vw\""));
+    assert!(!ImageAsset::new("/photo.png").picture().contains("sizes"));
+}